@@ -0,0 +1,497 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Named actions ("Jump", "MoveX"), bound to keys, mouse buttons and
+//! controller inputs.
+//!
+//! Bindings are saved as a text file, with one action per line:
+//! `NAME: BINDING BINDING...`, where each binding is the name of a key
+//! ("Space", "A"), a mouse button ("LeftButton"), a controller button
+//! ("CAccept", "CL1") or a controller axis ("CMoveX", or "CMoveX+" for only
+//! the positive half).  A binding ending in `~` is negative.  Lines
+//! starting with `#` are comments.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use {Axis, Controller, Event, Key, KeyState, Keyboard, MouseButtons};
+
+const AXES: [(Axis, &str); 6] = [
+	(Axis::MoveX, "CMoveX"),
+	(Axis::MoveY, "CMoveY"),
+	(Axis::CameraX, "CCameraX"),
+	(Axis::CameraY, "CCameraY"),
+	(Axis::ThrottleL, "CThrottleL"),
+	(Axis::ThrottleR, "CThrottleR"),
+];
+
+const BUTTONS: [(Binding, &str); 14] = [
+	(Binding::LeftButton, "LeftButton"),
+	(Binding::MiddleButton, "MiddleButton"),
+	(Binding::RightButton, "RightButton"),
+	(Binding::CAccept, "CAccept"),
+	(Binding::CCancel, "CCancel"),
+	(Binding::CExecute, "CExecute"),
+	(Binding::CAction, "CAction"),
+	(Binding::CMenu, "CMenu"),
+	(Binding::CUp, "CUp"),
+	(Binding::CDown, "CDown"),
+	(Binding::CLeft, "CLeft"),
+	(Binding::CRight, "CRight"),
+	(Binding::CMoveStick, "CMoveStick"),
+	(Binding::CCamStick, "CCamStick"),
+];
+
+/// Something an action can be bound to.  Controller bindings are for any
+/// controller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Binding {
+	/// A key.
+	Key(Key),
+	/// Left mouse button.
+	LeftButton,
+	/// Middle mouse button.
+	MiddleButton,
+	/// Right mouse button.
+	RightButton,
+	/// Controller: Accept (A Button / Left Top Button - Missle / Circle)
+	CAccept,
+	/// Controller: Cancel (B Button / Side Button / Cross)
+	CCancel,
+	/// Controller: Execute (X Button / Trigger / Triangle)
+	CExecute,
+	/// Controller: Action (Y Button / Right Top Button / Square)
+	CAction,
+	/// Controller: Left Button, see `Event::CL`.
+	CL(u8),
+	/// Controller: Right Button, see `Event::CR`.
+	CR(u8),
+	/// Controller: Pause Menu (Start Button)
+	CMenu,
+	/// Controller: HAT/DPAD Up Button
+	CUp,
+	/// Controller: HAT/DPAD Down Button
+	CDown,
+	/// Controller: HAT/DPAD Left Button
+	CLeft,
+	/// Controller: HAT/DPAD Right Button
+	CRight,
+	/// Controller: Movement stick Push
+	CMoveStick,
+	/// Controller: Camera stick Push
+	CCamStick,
+	/// Controller: An axis, and which half (`None` for the whole axis).
+	CAxis(Axis, Option<bool>),
+}
+
+impl Binding {
+	/// Get the binding for an input that was just pressed (or an axis that
+	/// was pushed past halfway), for rebinding to whatever the user
+	/// presses next.
+	pub fn from_event(event: Event) -> Option<Binding> {
+		use Event::*;
+
+		let pushed = |axis, value: f32| if value.abs() > 0.5 {
+			Some(Binding::CAxis(axis, Some(value > 0.0)))
+		} else {
+			None
+		};
+
+		Some(match event {
+			Event::Key { key, state: KeyState::Pressed } => {
+				Binding::Key(key)
+			}
+			LeftButton(Some(true), _) => Binding::LeftButton,
+			MiddleButton(Some(true), _) => Binding::MiddleButton,
+			RightButton(Some(true), _) => Binding::RightButton,
+			CAccept(_, Some(true)) => Binding::CAccept,
+			CCancel(_, Some(true)) => Binding::CCancel,
+			CExecute(_, Some(true)) => Binding::CExecute,
+			CAction(_, Some(true)) => Binding::CAction,
+			CL(_, b, Some(true)) => Binding::CL(b),
+			CR(_, b, Some(true)) => Binding::CR(b),
+			CMenu(_, Some(true)) => Binding::CMenu,
+			CUp(_, Some(true)) => Binding::CUp,
+			CDown(_, Some(true)) => Binding::CDown,
+			CLeft(_, Some(true)) => Binding::CLeft,
+			CRight(_, Some(true)) => Binding::CRight,
+			CMoveStick(_, Some(true)) => Binding::CMoveStick,
+			CCamStick(_, Some(true)) => Binding::CCamStick,
+			CMove(_, x, y) => return pushed(Axis::MoveX, x)
+				.or_else(|| pushed(Axis::MoveY, y)),
+			CCamera(_, x, y) => return pushed(Axis::CameraX, x)
+				.or_else(|| pushed(Axis::CameraY, y)),
+			CThrottleL(_, x) => return pushed(Axis::ThrottleL, x),
+			CThrottleR(_, x) => return pushed(Axis::ThrottleR, x),
+			_ => return None,
+		})
+	}
+
+	/// Get the value (0 to 1, or -1 to 1 for a whole axis).
+	fn value(self, keyboard: &Keyboard, mouse: MouseButtons,
+		controllers: &[Controller]) -> f32
+	{
+		let button = |f: &dyn Fn(&Controller) -> bool| {
+			let held = controllers.iter()
+				.any(|c| c.connected && f(c));
+
+			if held { 1.0 } else { 0.0 }
+		};
+		let on = |b| if b { 1.0 } else { 0.0 };
+
+		match self {
+			Binding::Key(key) => on(keyboard.get(key)),
+			Binding::LeftButton => on(mouse.left),
+			Binding::MiddleButton => on(mouse.middle),
+			Binding::RightButton => on(mouse.right),
+			Binding::CAccept => button(&|c| c.accept),
+			Binding::CCancel => button(&|c| c.cancel),
+			Binding::CExecute => button(&|c| c.execute),
+			Binding::CAction => button(&|c| c.action),
+			Binding::CL(b) => button(&|c| {
+				c.l.get(b as usize).cloned().unwrap_or(false)
+			}),
+			Binding::CR(b) => button(&|c| {
+				c.r.get(b as usize).cloned().unwrap_or(false)
+			}),
+			Binding::CMenu => button(&|c| c.menu),
+			Binding::CUp => button(&|c| c.up),
+			Binding::CDown => button(&|c| c.down),
+			Binding::CLeft => button(&|c| c.left),
+			Binding::CRight => button(&|c| c.right),
+			Binding::CMoveStick => button(&|c| c.move_stick),
+			Binding::CCamStick => button(&|c| c.cam_stick),
+			Binding::CAxis(axis, half) => {
+				// The controller pushed the furthest.
+				let mut value = 0.0f32;
+
+				let connected = controllers.iter()
+					.filter(|c| c.connected);
+
+				for c in connected {
+					let v = match axis {
+						Axis::MoveX => c.movement.0,
+						Axis::MoveY => c.movement.1,
+						Axis::CameraX => c.camera.0,
+						Axis::CameraY => c.camera.1,
+						Axis::ThrottleL => c.throttle_l,
+						Axis::ThrottleR => c.throttle_r,
+					};
+					let v = match half {
+						None => v,
+						Some(true) => v.max(0.0),
+						Some(false) => (-v).max(0.0),
+					};
+
+					if v.abs() > value.abs() {
+						value = v;
+					}
+				}
+
+				value
+			}
+		}
+	}
+}
+
+impl fmt::Display for Binding {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Binding::Key(key) => write!(f, "{}", key),
+			Binding::CL(b) => write!(f, "CL{}", b),
+			Binding::CR(b) => write!(f, "CR{}", b),
+			Binding::CAxis(axis, half) => {
+				let name = AXES.iter().find(|a| a.0 == axis)
+					.map_or("", |a| a.1);
+
+				match half {
+					None => write!(f, "{}", name),
+					Some(true) => write!(f, "{}+", name),
+					Some(false) => write!(f, "{}-", name),
+				}
+			}
+			binding => {
+				let name = BUTTONS.iter()
+					.find(|b| b.0 == binding)
+					.map_or("", |b| b.1);
+
+				f.write_str(name)
+			}
+		}
+	}
+}
+
+impl FromStr for Binding {
+	type Err = io::Error;
+
+	fn from_str(s: &str) -> io::Result<Binding> {
+		let number = |n: &str| n.parse().map_err(|_| invalid(s));
+
+		if let Some(button) = BUTTONS.iter().find(|b| b.1 == s) {
+			return Ok(button.0);
+		}
+
+		Ok(match AXES.iter().find(|a| s.starts_with(a.1)) {
+			Some(&(axis, name)) => Binding::CAxis(axis,
+				match &s[name.len()..] {
+					"" => None,
+					"+" => Some(true),
+					"-" => Some(false),
+					_ => return Err(invalid(s)),
+				}),
+			None => if let Some(b) = s.strip_prefix("CL") {
+				Binding::CL(number(b)?)
+			} else if let Some(b) = s.strip_prefix("CR") {
+				Binding::CR(number(b)?)
+			} else {
+				Binding::Key(s.parse().map_err(|_| invalid(s))?)
+			},
+		})
+	}
+}
+
+/// Named actions, and what they're bound to.  Get the state of an action
+/// with `Screen::action()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Actions {
+	// Each action, and it's bindings (and whether they're negative).
+	actions: Vec<(String, Vec<(Binding, bool)>)>,
+}
+
+impl Actions {
+	/// Create actions, with no bindings.
+	pub fn new() -> Actions {
+		Actions::default()
+	}
+
+	/// Load bindings from the file at `path` (see the module docs).
+	pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Actions> {
+		fs::read_to_string(path)?.parse()
+	}
+
+	/// Save the bindings to the file at `path`.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+		fs::write(path, self.to_string())
+	}
+
+	/// Bind `action` to `binding`, which pushes the action positive.
+	pub fn bind(&mut self, action: &str, binding: Binding) {
+		self.add(action, binding, false)
+	}
+
+	/// Bind `action` to `binding`, which pushes the action negative, such
+	/// as the "A" key for a "MoveX" action.
+	pub fn bind_negative(&mut self, action: &str, binding: Binding) {
+		self.add(action, binding, true)
+	}
+
+	/// Remove `binding` from `action`.
+	pub fn unbind(&mut self, action: &str, binding: Binding) {
+		if let Some(bindings) = self.get_mut(action) {
+			bindings.retain(|b| b.0 != binding);
+		}
+	}
+
+	/// Remove every binding from `action`.
+	pub fn clear(&mut self, action: &str) {
+		if let Some(bindings) = self.get_mut(action) {
+			bindings.clear();
+		}
+	}
+
+	/// Get the bindings of `action`, and whether each is negative.
+	pub fn bindings(&self, action: &str) -> &[(Binding, bool)] {
+		self.actions.iter().find(|a| a.0 == action)
+			.map_or(&[], |a| &a.1[..])
+	}
+
+	/// Get the names of every action, in the order they were added.
+	pub fn names(&self) -> Vec<&str> {
+		self.actions.iter().map(|a| a.0.as_str()).collect()
+	}
+
+	/// Get the value of `action`: the sum of it's bindings, -1 to 1.
+	pub(crate) fn value(&self, action: &str, keyboard: &Keyboard,
+		mouse: MouseButtons, controllers: &[Controller]) -> f32
+	{
+		let mut value = 0.0;
+
+		for &(binding, negative) in self.bindings(action) {
+			let v = binding.value(keyboard, mouse, controllers);
+
+			value += if negative { -v } else { v };
+		}
+
+		value.clamp(-1.0, 1.0)
+	}
+
+	fn add(&mut self, action: &str, binding: Binding, negative: bool) {
+		if self.get_mut(action).is_none() {
+			self.actions.push((action.to_string(), Vec::new()));
+		}
+
+		let bindings = self.get_mut(action).unwrap();
+
+		bindings.retain(|b| b.0 != binding);
+		bindings.push((binding, negative));
+	}
+
+	fn get_mut(&mut self, action: &str)
+		-> Option<&mut Vec<(Binding, bool)>>
+	{
+		self.actions.iter_mut().find(|a| a.0 == action)
+			.map(|a| &mut a.1)
+	}
+}
+
+impl fmt::Display for Actions {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (action, bindings) in self.actions.iter() {
+			write!(f, "{}:", action)?;
+
+			for &(binding, negative) in bindings.iter() {
+				write!(f, " {}{}", binding, if negative {
+					"~"
+				} else {
+					""
+				})?;
+			}
+
+			writeln!(f)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl FromStr for Actions {
+	type Err = io::Error;
+
+	fn from_str(s: &str) -> io::Result<Actions> {
+		let mut actions = Actions::new();
+
+		for line in s.lines().map(|l| l.trim()) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let mut line = line.splitn(2, ':');
+			let action = line.next().unwrap_or("").trim();
+			let bindings = line.next()
+				.ok_or_else(|| invalid(action))?;
+
+			// Actions with no bindings are kept.
+			if actions.get_mut(action).is_none() {
+				actions.actions.push((action.to_string(),
+					Vec::new()));
+			}
+
+			for binding in bindings.split_whitespace() {
+				let (binding, negative) = match binding
+					.strip_suffix('~')
+				{
+					Some(binding) => (binding, true),
+					None => (binding, false),
+				};
+
+				actions.add(action, binding.parse()?, negative);
+			}
+		}
+
+		Ok(actions)
+	}
+}
+
+fn invalid(binding: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData,
+		format!("bad binding: {}", binding))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Every kind of binding.
+	fn bindings() -> Vec<Binding> {
+		let mut bindings: Vec<Binding> = BUTTONS.iter().map(|b| b.0)
+			.collect();
+
+		bindings.extend(Key::ALL.iter().map(|k| Binding::Key(*k)));
+		bindings.extend(&[Binding::CL(0), Binding::CL(255),
+			Binding::CR(0), Binding::CR(3)]);
+		for &(axis, _) in AXES.iter() {
+			for half in &[None, Some(true), Some(false)] {
+				bindings.push(Binding::CAxis(axis, *half));
+			}
+		}
+
+		bindings
+	}
+
+	#[test]
+	fn binding_display_parses() {
+		for binding in bindings() {
+			let name = binding.to_string();
+
+			assert_eq!(name.parse::<Binding>().ok(), Some(binding),
+				"{:?}", name);
+		}
+	}
+
+	#[test]
+	fn parse() {
+		let actions: Actions = "# Comment\n\
+			Jump: Space CAccept\n\
+			\n\
+			MoveX: D A~ CMoveX\n\
+			Idle:\n".parse().unwrap();
+
+		assert_eq!(actions.names(), ["Jump", "MoveX", "Idle"]);
+		assert_eq!(actions.bindings("Jump"),
+			&[(Binding::Key(Key::Space), false),
+				(Binding::CAccept, false)]);
+		assert_eq!(actions.bindings("MoveX"),
+			&[(Binding::Key(Key::D), false),
+				(Binding::Key(Key::A), true),
+				(Binding::CAxis(Axis::MoveX, None), false)]);
+		assert!(actions.bindings("Idle").is_empty());
+		assert!("Jump Space".parse::<Actions>().is_err());
+		assert!("Jump: Spacebar".parse::<Actions>().is_err());
+		assert!("Jump: CL".parse::<Actions>().is_err());
+		assert!("Jump: CMoveX*".parse::<Actions>().is_err());
+	}
+
+	#[test]
+	fn load_save() {
+		let path = ::std::env::temp_dir()
+			.join(format!("awi-test-{}.txt", ::std::process::id()));
+		let mut actions = Actions::new();
+
+		for (i, binding) in bindings().into_iter().enumerate() {
+			let action = format!("Action{}", i % 5);
+
+			if i % 2 == 0 {
+				actions.bind(&action, binding);
+			} else {
+				actions.bind_negative(&action, binding);
+			}
+		}
+		actions.clear("Action4");
+
+		actions.save(&path).unwrap();
+		let loaded = Actions::load(&path).unwrap();
+		loaded.save(&path).unwrap();
+		let reloaded = Actions::load(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(loaded, actions);
+		assert_eq!(reloaded, actions);
+	}
+}
@@ -0,0 +1,131 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Controller state, for polling instead of handling every controller event.
+
+use Event;
+
+/// Snapshot of a controller's state, from `Screen::controller()`.  Buttons
+/// are `true` while held down.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Controller {
+	/// Whether the controller is plugged in.
+	pub connected: bool,
+	/// The name of the device (or it's vendor and product id if unknown).
+	pub name: String,
+	/// The device id from `Event::CPluggedIn`.
+	pub id: i32,
+	/// Main joystick (x, y).
+	pub movement: (f32, f32),
+	/// Camera / C joystick (x, y).
+	pub camera: (f32, f32),
+	/// Left Throttle.
+	pub throttle_l: f32,
+	/// Right Throttle.
+	pub throttle_r: f32,
+	/// Accept (A Button / Left Top Button - Missle / Circle)
+	pub accept: bool,
+	/// Cancel (B Button / Side Button / Cross)
+	pub cancel: bool,
+	/// Execute (X Button / Trigger / Triangle)
+	pub execute: bool,
+	/// Action (Y Button / Right Top Button / Square)
+	pub action: bool,
+	/// Left Buttons, see `Event::CL`.
+	pub l: [bool; 32],
+	/// Right Buttons, see `Event::CR`.
+	pub r: [bool; 32],
+	/// Pause Menu (Start Button)
+	pub menu: bool,
+	/// HAT/DPAD Up Button
+	pub up: bool,
+	/// HAT/DPAD Down Button
+	pub down: bool,
+	/// HAT/DPAD Left Button
+	pub left: bool,
+	/// HAT/DPAD Right Button
+	pub right: bool,
+	/// Movement stick Push
+	pub move_stick: bool,
+	/// Camera stick Push
+	pub cam_stick: bool,
+	/// Gyroscope (x, y, z), in radians per second.
+	pub gyro: (f32, f32, f32),
+	/// Accelerometer (x, y, z), in meters per second squared.
+	pub accel: (f32, f32, f32),
+}
+
+impl Controller {
+	/// Update the state of controller `js` in `controllers` from `event`.
+	/// `name` is the name of the device, for `CPluggedIn`.
+	pub(crate) fn update(controllers: &mut Vec<Controller>, event: Event,
+		name: Option<String>)
+	{
+		use Event::*;
+
+		let js = match event {
+			CMove(js, _, _) | CCamera(js, _, _) | CThrottleL(js, _)
+				| CThrottleR(js, _) | CAccept(js, _)
+				| CCancel(js, _) | CExecute(js, _)
+				| CAction(js, _) | CL(js, _, _) | CR(js, _, _)
+				| CMenu(js, _) | CUp(js, _) | CDown(js, _)
+				| CLeft(js, _) | CRight(js, _)
+				| CMoveStick(js, _) | CCamStick(js, _)
+				| CPluggedIn(js, _) | CUnPlugged(js, _)
+				| CGyro(js, _, _, _) | CAccel(js, _, _, _) => js,
+			_ => return,
+		};
+
+		if controllers.len() <= js {
+			controllers.resize(js + 1, Controller::default());
+		}
+
+		let c = &mut controllers[js];
+
+		// stick: `None` is released, `Some(_)` is pressed or held.
+		match event {
+			CMove(_, x, y) => c.movement = (x, y),
+			CCamera(_, x, y) => c.camera = (x, y),
+			CThrottleL(_, x) => c.throttle_l = x,
+			CThrottleR(_, x) => c.throttle_r = x,
+			CAccept(_, s) => c.accept = s.is_some(),
+			CCancel(_, s) => c.cancel = s.is_some(),
+			CExecute(_, s) => c.execute = s.is_some(),
+			CAction(_, s) => c.action = s.is_some(),
+			CL(_, b, s) => if let Some(l) = c.l.get_mut(b as usize) {
+				*l = s.is_some();
+			},
+			CR(_, b, s) => if let Some(r) = c.r.get_mut(b as usize) {
+				*r = s.is_some();
+			},
+			CMenu(_, s) => c.menu = s.is_some(),
+			CUp(_, s) => c.up = s.is_some(),
+			CDown(_, s) => c.down = s.is_some(),
+			CLeft(_, s) => c.left = s.is_some(),
+			CRight(_, s) => c.right = s.is_some(),
+			CMoveStick(_, s) => c.move_stick = s.is_some(),
+			CCamStick(_, s) => c.cam_stick = s.is_some(),
+			CPluggedIn(_, id) => {
+				*c = Controller {
+					connected: true, id,
+					name: name.unwrap_or_else(|| {
+						id_name(id)
+					}),
+					..Controller::default()
+				};
+			}
+			CUnPlugged(_, _) => c.connected = false,
+			CGyro(_, x, y, z) => c.gyro = (x, y, z),
+			CAccel(_, x, y, z) => c.accel = (x, y, z),
+			_ => {}
+		}
+	}
+}
+
+// "vendor:product", like `lsusb`.
+fn id_name(id: i32) -> String {
+	format!("{:04x}:{:04x}", (id >> 16) & 0xFFFF, id & 0xFFFF)
+}
@@ -0,0 +1,737 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Controllers opened with evdev, for force feedback and motion sensors, and
+//! to read them directly when they have a mapping (stick only knows a few
+//! controllers' layouts).
+
+use std::f32::consts::PI;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use libc;
+
+use super::{Axis, Button, Effect};
+use super::mapping::{Mapping, Source, Target};
+
+const EVIOCGID: libc::c_ulong = 0x80084502;
+const EVIOCGBIT_KEY: libc::c_ulong = 0x80604521; // 96 bytes
+const EVIOCGBIT_ABS: libc::c_ulong = 0x80084523; // 8 bytes
+const EVIOCGBIT_FF: libc::c_ulong = 0x80104535; // 16 bytes
+const EVIOCSFF: libc::c_ulong = 0x40004580; // + size << 16
+const EVIOCGNAME: libc::c_ulong = 0x80404506; // 64 bytes
+const EVIOCGPHYS: libc::c_ulong = 0x80404507; // 64 bytes
+const EVIOCGUNIQ: libc::c_ulong = 0x80404508; // 64 bytes
+const EVIOCGPROP: libc::c_ulong = 0x80044509; // 4 bytes
+const EVIOCGABS: libc::c_ulong = 0x80184540; // + axis
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const EV_FF: u16 = 0x15;
+const FF_RUMBLE: u16 = 0x50;
+const FF_PERIODIC: u16 = 0x51;
+const FF_CONSTANT: u16 = 0x52;
+const FF_SINE: u16 = 0x5a;
+const FF_CNT: usize = 0x80;
+const KEY_CNT: usize = 0x300;
+const ABS_CNT: usize = 0x40;
+const BTN_JOYSTICK: usize = 0x120;
+const BTN_SOUTH: usize = 0x130;
+const ABS_HAT0X: usize = 0x10;
+const ABS_HAT3Y: usize = 0x17;
+const INPUT_PROP_ACCELEROMETER: usize = 0x06;
+
+/// An evdev controller, opened separately from stick.  stick keeps it's
+/// file descriptor private, and opens it read-only, but playing force
+/// feedback effects needs to `write()`.
+pub(crate) struct Device {
+	fd: i32,
+	path: PathBuf,
+	name: String,
+	// Whether `fd` was opened for writing, for force feedback.
+	writable: bool,
+	// Whether there's a mapping, to read it directly.
+	mapped: bool,
+	binds: Vec<(Target, Source)>,
+	// Which force feedback effects it can do.
+	ff: [u8; FF_CNT / 8],
+	// Id of the uploaded force feedback effect, or -1.
+	effect: i16,
+	// The controller's motion sensors, if it has them.
+	motion: Option<Motion>,
+	// Whether each key code is held.
+	keys: Vec<bool>,
+	// Value and (minimum, maximum) of each absolute axis.
+	abs: [i32; ABS_CNT],
+	range: [(i32, i32); ABS_CNT],
+}
+
+impl Device {
+	/// Open the controller with stick's `id`, that isn't already open
+	/// (`open`).
+	pub(crate) fn open(id: i32, mappings: &[Mapping], open: &[&Path])
+		-> Option<Device>
+	{
+		// Same devices as stick, in the same order.
+		let paths = fs::read_dir("/dev/input/by-id/").ok()?
+			.filter_map(|e| e.ok())
+			.map(|e| e.path())
+			.filter(|p| {
+				p.to_string_lossy().ends_with("-event-joystick")
+			})
+			.filter(|p| !open.contains(&p.as_path()));
+
+		for path in paths {
+			let name = path.as_os_str().as_bytes();
+			let name = match CString::new(name) {
+				Ok(name) => name,
+				Err(_) => continue,
+			};
+			// Force feedback needs to write.
+			let flags = libc::O_NONBLOCK | libc::O_CLOEXEC;
+			let (fd, writable) = match unsafe {
+				libc::open(name.as_ptr(), libc::O_RDWR | flags)
+			} {
+				-1 => (unsafe {
+					libc::open(name.as_ptr(),
+						libc::O_RDONLY | flags)
+				}, false),
+				fd => (fd, true),
+			};
+
+			if fd < 0 {
+				continue;
+			}
+
+			// bustype, vendor, product, version
+			let mut ids = [0u16; 4];
+			unsafe { libc::ioctl(fd, EVIOCGID, ids.as_mut_ptr()) };
+
+			// stick's id is made from signed values.
+			if (ids[1] as i16 as i32) << 16 | ids[2] as i16 as i32
+				!= id
+			{
+				unsafe { libc::close(fd) };
+				continue;
+			}
+
+			let mut device = Device {
+				fd, path, name: string(fd, EVIOCGNAME),
+				writable, mapped: false,
+				binds: Vec::new(),
+				ff: [0; FF_CNT / 8], effect: -1,
+				motion: Motion::open(ids, fd),
+				keys: vec![false; KEY_CNT], abs: [0; ABS_CNT],
+				range: [(-1, 1); ABS_CNT],
+			};
+
+			device.mapped = device.map(ids, mappings);
+			unsafe {
+				libc::ioctl(fd, EVIOCGBIT_FF,
+					device.ff.as_mut_ptr())
+			};
+
+			return Some(device);
+		}
+
+		None
+	}
+
+	/// The path of the device.
+	pub(crate) fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// The name of the device.
+	pub(crate) fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Read the motion sensors, and get the accelerometer (m/s²) and
+	/// gyroscope (radians/s) if they changed.
+	pub(crate) fn motion(&mut self) -> Option<([f32; 3], [f32; 3])> {
+		self.motion.as_mut().and_then(|motion| motion.poll())
+	}
+
+	/// Whether the device has a mapping, to read it directly.
+	pub(crate) fn mapped(&self) -> bool {
+		self.mapped
+	}
+
+	/// Find the mapping (falling back on the kernel's gamepad layout), and
+	/// resolve it's button and axis numbers to evdev codes.  Returns
+	/// `false` if there isn't one.
+	fn map(&mut self, ids: [u16; 4], mappings: &[Mapping]) -> bool {
+		let mut key_bits = [0u8; KEY_CNT / 8];
+		let mut abs_bits = [0u8; ABS_CNT / 8];
+
+		unsafe {
+			libc::ioctl(self.fd, EVIOCGBIT_KEY,
+				key_bits.as_mut_ptr());
+			libc::ioctl(self.fd, EVIOCGBIT_ABS,
+				abs_bits.as_mut_ptr());
+		}
+
+		let bit = |bits: &[u8], i: usize| {
+			bits[i / 8] & (1 << (i % 8)) != 0
+		};
+
+		for code in (0..ABS_CNT).filter(|&code| bit(&abs_bits, code)) {
+			let mut info: libc::input_absinfo = unsafe {
+				mem::zeroed()
+			};
+
+			unsafe {
+				libc::ioctl(self.fd,
+					EVIOCGABS + code as libc::c_ulong,
+					&mut info)
+			};
+
+			self.abs[code] = info.value;
+			self.range[code] = (info.minimum, info.maximum);
+		}
+
+		// Later mappings replace earlier ones, and ones for this
+		// version are better than ones for any version.
+		let guid = |m: &&Mapping, version: bool| {
+			let g = m.guid;
+			let g = |i: usize| g[i] as u16 | (g[i + 1] as u16) << 8;
+
+			g(0) == ids[0] && g(4) == ids[1] && g(8) == ids[2]
+				&& (!version || g(12) == ids[3])
+				&& g(6) == 0 && g(10) == 0
+		};
+		let mapping = mappings.iter().rev().find(|m| guid(m, true))
+			.or_else(|| {
+				mappings.iter().rev().find(|m| guid(m, false))
+			});
+
+		let mapping = match mapping {
+			Some(mapping) => mapping,
+			None if bit(&key_bits, BTN_SOUTH) => {
+				// Only the controls it has.
+				let has = |source| match source {
+					Source::Button(code) => {
+						bit(&key_bits, code as usize)
+					}
+					Source::Axis(code, _, _) => {
+						bit(&abs_bits, code as usize)
+					}
+					Source::Hat(_, _) => false,
+				};
+
+				self.binds = standard().into_iter()
+					.filter(|&(_, source)| has(source))
+					.collect();
+				return true;
+			}
+			None => return false,
+		};
+
+		// Numbered like SDL does: buttons from `BTN_JOYSTICK`, then
+		// the ones before it; axes skipping hats; and then hats.
+		let buttons: Vec<usize> = (BTN_JOYSTICK..KEY_CNT)
+			.chain(0..BTN_JOYSTICK)
+			.filter(|&code| bit(&key_bits, code))
+			.collect();
+		let axes: Vec<usize> = (0..ABS_CNT)
+			.filter(|code| !(ABS_HAT0X..=ABS_HAT3Y).contains(code))
+			.filter(|&code| bit(&abs_bits, code))
+			.collect();
+		let hats: Vec<usize> = (ABS_HAT0X..ABS_HAT3Y).step_by(2)
+			.filter(|&code| {
+				bit(&abs_bits, code) || bit(&abs_bits, code + 1)
+			})
+			.collect();
+
+		let resolve = |source| Some(match source {
+			Source::Button(i) => {
+				Source::Button(*buttons.get(i as usize)? as u16)
+			}
+			Source::Axis(i, half, invert) => {
+				let code = *axes.get(i as usize)? as u16;
+
+				Source::Axis(code, half, invert)
+			}
+			Source::Hat(i, direction) => {
+				// X, and Y after it.
+				let x = *hats.get(i as usize)? as u16;
+				let (code, half) = match direction {
+					1 => (x + 1, false),
+					2 => (x, true),
+					4 => (x + 1, true),
+					8 => (x, false),
+					_ => return None,
+				};
+
+				Source::Axis(code, Some(half), false)
+			}
+		});
+
+		self.binds = mapping.binds.iter()
+			.filter_map(|&(target, source)| {
+				Some((target, resolve(source)?))
+			})
+			.collect();
+
+		true
+	}
+
+	/// Read the events sent since the last poll.
+	pub(crate) fn poll(&mut self) {
+		let mut event: libc::input_event = unsafe { mem::zeroed() };
+		let size = mem::size_of::<libc::input_event>();
+
+		while unsafe {
+			libc::read(self.fd, &mut event as *mut _ as *mut _,
+				size)
+		} == size as isize {
+			let code = event.code as usize;
+
+			match event.type_ {
+				EV_KEY if code < KEY_CNT => {
+					// 2 is key repeat.
+					self.keys[code] = event.value != 0;
+				}
+				EV_ABS if code < ABS_CNT => {
+					self.abs[code] = event.value;
+				}
+				_ => {}
+			}
+		}
+	}
+
+	/// Get the controls, and their values (0 to 1, or -1 to 1 for whole
+	/// axes other than throttles).
+	pub(crate) fn controls<'a>(&'a self)
+		-> impl Iterator<Item = (Target, f32)> + 'a
+	{
+		self.binds.iter().map(move |&(target, source)| {
+			let value = self.value(source);
+
+			(target, match (target, source) {
+				// Throttles go from 0 (resting) to 1.
+				(Target::Axis(Axis::ThrottleL, None),
+					Source::Axis(_, None, _))
+				| (Target::Axis(Axis::ThrottleR, None),
+					Source::Axis(_, None, _)) => {
+					(value + 1.0) / 2.0
+				}
+				_ => value,
+			})
+		})
+	}
+
+	/// Get the value of a button or axis.
+	fn value(&self, source: Source) -> f32 {
+		match source {
+			Source::Button(code) => {
+				if self.keys[code as usize] { 1.0 } else { 0.0 }
+			}
+			Source::Axis(code, half, invert) => {
+				let (min, max) = self.range[code as usize];
+				let value = self.abs[code as usize];
+				let value = if max > min {
+					let range = (max - min) as f32;
+
+					(value - min) as f32 / range * 2.0 - 1.0
+				} else {
+					0.0
+				};
+				let value = if invert { -value } else { value };
+
+				match half {
+					None => value,
+					Some(true) => value.max(0.0),
+					Some(false) => (-value).max(0.0),
+				}
+			}
+			Source::Hat(_, _) => 0.0,
+		}
+	}
+
+	/// Play a force feedback effect, replacing the last one.  Returns
+	/// why, if the device can't do it.
+	pub(crate) fn effect(&mut self, effect: Effect, duration: Duration)
+		-> Result<(), String>
+	{
+		if !self.writable {
+			return Err(format!("no write access to {}, needed for \
+				force feedback", self.path.display()));
+		}
+
+		let has = |ff: u16| {
+			self.ff[ff as usize / 8] & (1 << (ff % 8)) != 0
+		};
+		let level = |value: f32| {
+			(value.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+		};
+		let magnitude = |value: f32| {
+			(value.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+		};
+		// Radians clockwise from up, to the kernel's direction (0 is
+		// down, 0x4000 is left, and a full turn is 0x10000).
+		let direction = |angle: f32| {
+			let turns = angle / (2.0 * PI) + 0.5;
+
+			((turns - turns.floor()) * 65536.0) as u32 as u16
+		};
+
+		let mut ff: libc::ff_effect = unsafe { mem::zeroed() };
+
+		// Effect data is a union after the header.
+		let data = &mut ff.u as *mut _;
+
+		match effect {
+			Effect::Rumble(strong, weak) if has(FF_RUMBLE) => {
+				let rumble: &mut libc::ff_rumble_effect =
+					unsafe { &mut *(data as *mut _) };
+
+				ff.type_ = FF_RUMBLE;
+				rumble.strong_magnitude = magnitude(strong);
+				rumble.weak_magnitude = magnitude(weak);
+			}
+			// Controllers without rumble motors.
+			Effect::Rumble(strong, weak) => {
+				return self.effect(Effect::Sine(
+					strong.max(weak),
+					Duration::from_millis(50), 0.0),
+					duration);
+			}
+			Effect::Constant(value, angle) if has(FF_CONSTANT) => {
+				let constant: &mut libc::ff_constant_effect =
+					unsafe { &mut *(data as *mut _) };
+
+				ff.type_ = FF_CONSTANT;
+				ff.direction = direction(angle);
+				constant.level = level(value);
+			}
+			Effect::Sine(value, period, angle)
+				if has(FF_PERIODIC) && has(FF_SINE) =>
+			{
+				let period = period.as_millis();
+				let periodic: &mut libc::ff_periodic_effect =
+					unsafe { &mut *(data as *mut _) };
+
+				ff.type_ = FF_PERIODIC;
+				ff.direction = direction(angle);
+				periodic.waveform = FF_SINE;
+				periodic.period = period.min(0xFFFF) as u16;
+				periodic.magnitude = level(value);
+			}
+			_ => return Err("the controller can't play this effect"
+				.to_string()),
+		}
+
+		let length = duration.as_millis();
+
+		ff.id = self.effect;
+		ff.replay.length = length.min(0xFFFF) as u16;
+
+		// Upload it (replacing the last one), then play it.
+		let size = mem::size_of::<libc::ff_effect>() as libc::c_ulong;
+		if unsafe {
+			libc::ioctl(self.fd, EVIOCSFF | size << 16, &mut ff)
+		} < 0 {
+			return Err(format!("couldn't upload the effect: {}",
+				io::Error::last_os_error()));
+		}
+		self.effect = ff.id;
+
+		let mut event: libc::input_event = unsafe { mem::zeroed() };
+		event.type_ = EV_FF;
+		event.code = self.effect as u16;
+		event.value = if length == 0 { 0 } else { 1 };
+
+		let size = mem::size_of::<libc::input_event>();
+
+		if unsafe {
+			libc::write(self.fd, &event as *const _ as *const _,
+				size)
+		} != size as isize {
+			return Err(format!("couldn't play the effect: {}",
+				io::Error::last_os_error()));
+		}
+
+		Ok(())
+	}
+}
+
+impl Drop for Device {
+	fn drop(&mut self) {
+		unsafe { libc::close(self.fd) };
+	}
+}
+
+/// A controller's motion sensors, which are a separate evdev device.
+struct Motion {
+	fd: i32,
+	// Value of each axis (accelerometer x, y, z, then gyroscope x, y, z),
+	// and how much of it is 1 g or 1 degree per second.
+	abs: [i32; 6],
+	resolution: [i32; 6],
+	// Whether there's new values.
+	changed: bool,
+}
+
+impl Motion {
+	/// Find the motion sensors of the controller with `ids` (from
+	/// `EVIOCGID`) opened as `controller`.
+	fn open(ids: [u16; 4], controller: i32) -> Option<Motion> {
+		let uniq = string(controller, EVIOCGUNIQ);
+		// Without the `/inputN` at the end.
+		let phys = string(controller, EVIOCGPHYS);
+		let phys = phys.rsplit_once('/').map_or(&phys[..], |p| p.0);
+
+		for path in fs::read_dir("/dev/input/").ok()? {
+			let path = match path {
+				Ok(path) => path.path(),
+				Err(_) => continue,
+			};
+
+			if !path.to_string_lossy().contains("/event") {
+				continue;
+			}
+
+			let name = path.as_os_str().as_bytes();
+			let name = match CString::new(name) {
+				Ok(name) => name,
+				Err(_) => continue,
+			};
+			let fd = unsafe {
+				libc::open(name.as_ptr(), libc::O_RDONLY
+					| libc::O_NONBLOCK | libc::O_CLOEXEC)
+			};
+
+			if fd < 0 {
+				continue;
+			}
+
+			let mut props = [0u8; 4];
+			let mut motion_ids = [0u16; 4];
+
+			unsafe {
+				libc::ioctl(fd, EVIOCGPROP, props.as_mut_ptr());
+				libc::ioctl(fd, EVIOCGID,
+					motion_ids.as_mut_ptr());
+			}
+
+			let accelerometer = props[INPUT_PROP_ACCELEROMETER / 8]
+				& (1 << (INPUT_PROP_ACCELEROMETER % 8)) != 0;
+			// Same serial number (or connection, if there isn't
+			// one).
+			let same = if uniq.is_empty() {
+				string(fd, EVIOCGPHYS).starts_with(phys)
+			} else {
+				string(fd, EVIOCGUNIQ) == uniq
+			};
+
+			// Same vendor and product.
+			if !accelerometer || motion_ids[1..3] != ids[1..3]
+				|| !same
+			{
+				unsafe { libc::close(fd) };
+				continue;
+			}
+
+			let mut info: [libc::input_absinfo; 6] = unsafe {
+				mem::zeroed()
+			};
+
+			for (code, info) in info.iter_mut().enumerate() {
+				let code = code as libc::c_ulong;
+
+				unsafe {
+					libc::ioctl(fd, EVIOCGABS + code, info)
+				};
+			}
+
+			return Some(Motion {
+				fd, abs: info.map(|info| info.value),
+				resolution: info.map(|info| {
+					info.resolution.max(1)
+				}),
+				changed: false,
+			});
+		}
+
+		None
+	}
+
+	/// Read the events sent since the last poll, and get the
+	/// accelerometer (m/s²) and gyroscope (radians/s) if they changed.
+	fn poll(&mut self) -> Option<([f32; 3], [f32; 3])> {
+		let mut event: libc::input_event = unsafe { mem::zeroed() };
+		let size = mem::size_of::<libc::input_event>();
+
+		while unsafe {
+			libc::read(self.fd, &mut event as *mut _ as *mut _,
+				size)
+		} == size as isize {
+			let code = event.code as usize;
+
+			match event.type_ {
+				EV_ABS if code < 6 => {
+					self.abs[code] = event.value
+				}
+				EV_SYN => self.changed = true,
+				_ => {}
+			}
+		}
+
+		if !self.changed {
+			return None;
+		}
+		self.changed = false;
+
+		let value = |i: usize| {
+			self.abs[i] as f32 / self.resolution[i] as f32
+		};
+		let g = 9.80665;
+		let radians = PI / 180.0;
+
+		Some(([value(0) * g, value(1) * g, value(2) * g],
+			[value(3) * radians, value(4) * radians,
+				value(5) * radians]))
+	}
+}
+
+impl Drop for Motion {
+	fn drop(&mut self) {
+		unsafe { libc::close(self.fd) };
+	}
+}
+
+/// Get a string from an `ioctl()` (64 bytes).
+fn string(fd: i32, request: libc::c_ulong) -> String {
+	let mut string = [0u8; 64];
+
+	if unsafe { libc::ioctl(fd, request, string.as_mut_ptr()) } < 0 {
+		return String::new();
+	}
+
+	let len = string.iter().position(|&c| c == 0).unwrap_or(64);
+
+	String::from_utf8_lossy(&string[..len]).into_owned()
+}
+
+/// The kernel's gamepad layout (`Documentation/input/gamepad.rst`), for
+/// controllers without a mapping that use it.
+fn standard() -> Vec<(Target, Source)> {
+	use self::Button::*;
+
+	let button = |b, code| (Target::Button(b), Source::Button(code));
+	let axis = |a, code| (Target::Axis(a, None), Source::Axis(code, None,
+		false));
+	let hat = |b, code, half| {
+		(Target::Button(b), Source::Axis(code, Some(half), false))
+	};
+
+	vec![
+		button(Accept, 0x130), // BTN_SOUTH
+		button(Cancel, 0x131), // BTN_EAST
+		button(Execute, 0x133), // BTN_NORTH (X)
+		button(Action, 0x134), // BTN_WEST (Y)
+		button(L(1), 0x136), // BTN_TL
+		button(R(1), 0x137), // BTN_TR
+		button(L(0), 0x138), // BTN_TL2
+		button(R(0), 0x139), // BTN_TR2
+		button(Exit, 0x13a), // BTN_SELECT
+		button(Menu, 0x13b), // BTN_START
+		button(Controls, 0x13c), // BTN_MODE
+		button(MoveStick, 0x13d), // BTN_THUMBL
+		button(CamStick, 0x13e), // BTN_THUMBR
+		button(Up, 0x220), // BTN_DPAD_UP
+		button(Down, 0x221), // BTN_DPAD_DOWN
+		button(Left, 0x222), // BTN_DPAD_LEFT
+		button(Right, 0x223), // BTN_DPAD_RIGHT
+		axis(Axis::MoveX, 0x00), // ABS_X
+		axis(Axis::MoveY, 0x01), // ABS_Y
+		axis(Axis::CameraX, 0x03), // ABS_RX
+		axis(Axis::CameraY, 0x04), // ABS_RY
+		axis(Axis::ThrottleL, 0x02), // ABS_Z
+		axis(Axis::ThrottleR, 0x05), // ABS_RZ
+		hat(Up, 0x11, false), // ABS_HAT0Y
+		hat(Down, 0x11, true),
+		hat(Left, 0x10, false), // ABS_HAT0X
+		hat(Right, 0x10, true),
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Type, code and value.
+	type Sent = [(u16, u16, i32)];
+
+	// Bytes of `input_event`s, as read from the device.
+	fn events(events: &Sent) -> Vec<u8> {
+		let mut bytes = vec![];
+
+		for &(type_, code, value) in events {
+			let time = vec![0; mem::size_of::<libc::timeval>()];
+
+			bytes.extend(time);
+			bytes.extend(&type_.to_ne_bytes());
+			bytes.extend(&code.to_ne_bytes());
+			bytes.extend(&value.to_ne_bytes());
+		}
+		assert_eq!(bytes.len(), events.len()
+			* mem::size_of::<libc::input_event>());
+
+		bytes
+	}
+
+	#[test]
+	fn motion_units() {
+		let mut fds = [0; 2];
+
+		assert_eq!(unsafe {
+			libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK)
+		}, 0);
+
+		// 8192 per g and 1024 per degree per second (DualShock 4).
+		let mut motion = Motion {
+			fd: fds[0], abs: [0; 6],
+			resolution: [8192, 8192, 8192, 1024, 1024, 1024],
+			changed: false,
+		};
+		let g = 9.80665;
+		let table: &[(&Sent, _)] = &[
+			// Nothing sent.
+			(&[], None),
+			// Values without a report aren't used yet.
+			(&[(EV_ABS, 0, 8192), (EV_ABS, 3, 90 * 1024)], None),
+			(&[(EV_ABS, 1, -4096), (EV_SYN, 0, 0)],
+				Some(([g, -g / 2.0, 0.0],
+					[PI / 2.0, 0.0, 0.0]))),
+			// Only what changed is replaced.
+			(&[(EV_ABS, 2, 16384), (EV_ABS, 5, -180 * 1024),
+				(EV_ABS, 6, 1), (EV_KEY, 0, 1), (EV_SYN, 0, 0)],
+				Some(([g, -g / 2.0, 2.0 * g],
+					[PI / 2.0, 0.0, -PI]))),
+			(&[], None),
+		];
+
+		for (i, &(sent, expected)) in table.iter().enumerate() {
+			let bytes = events(sent);
+
+			assert_eq!(unsafe {
+				libc::write(fds[1], bytes.as_ptr() as *const _,
+					bytes.len())
+			}, bytes.len() as isize);
+			assert_eq!(motion.poll(), expected, "{}", i);
+		}
+
+		unsafe { libc::close(fds[1]) };
+	}
+}
@@ -0,0 +1,266 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Controller mappings in SDL's `gamecontrollerdb.txt` format.  Each line is
+//! `GUID,NAME,CONTROL:SOURCE,...,platform:PLATFORM,`, where `SOURCE` is a
+//! button (`b2`), an axis (`a1`, `+a1`, `-a1`, `a1~`) or a hat direction
+//! (`h0.4`).  Lines starting with `#` are comments.
+
+use super::{Axis, Button};
+
+/// Mappings are only used with evdev, so only Linux ones are loaded.
+const PLATFORM: &str = "Linux";
+
+/// Where the value of a control comes from.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Source {
+	/// Button number (evdev key code, once resolved).
+	Button(u16),
+	/// Axis number (evdev absolute axis code, once resolved), which half
+	/// (`Some(true)` for positive, `None` for the whole axis), and whether
+	/// it's inverted.
+	Axis(u16, Option<bool>, bool),
+	/// Hat number, and direction (1: up, 2: right, 4: down, 8: left).
+	/// Resolved to half of an axis.
+	Hat(u16, u8),
+}
+
+/// What a control is.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Target {
+	/// A button.
+	Button(Button),
+	/// An axis, and which half (`None` for the whole axis).
+	Axis(Axis, Option<bool>),
+}
+
+/// A mapping for one type of controller.
+pub(crate) struct Mapping {
+	/// SDL's GUID: bus type, CRC, vendor, product and version, as 16-bit
+	/// little endian values with 0s between.
+	pub(crate) guid: [u8; 16],
+	pub(crate) binds: Vec<(Target, Source)>,
+}
+
+impl Mapping {
+	/// Parse every mapping in `text`, skipping comments, mappings for
+	/// other platforms and lines that can't be parsed.
+	pub(crate) fn parse_all(text: &str) -> Vec<Mapping> {
+		text.lines().filter_map(Mapping::parse).collect()
+	}
+
+	/// Parse one line of a `gamecontrollerdb.txt`.
+	fn parse(line: &str) -> Option<Mapping> {
+		let line = line.trim();
+
+		if line.is_empty() || line.starts_with('#') {
+			return None;
+		}
+
+		let mut fields = line.split(',');
+		let guid = guid(fields.next()?)?;
+		let _name = fields.next()?;
+		let mut binds = Vec::new();
+
+		for field in fields.filter(|f| !f.is_empty()) {
+			// Skip stray tokens, rather than the whole controller.
+			let (target, source) = match field.find(':') {
+				Some(i) => (&field[..i], &field[i + 1..]),
+				None => continue,
+			};
+
+			if target == "platform" {
+				if source != PLATFORM {
+					return None;
+				}
+				continue;
+			}
+
+			// Unknown controls (`crc`, `hint`, `touchpad`...)
+			if let (Some(target), Some(source))
+				= (self::target(target), self::source(source))
+			{
+				binds.push((target, source));
+			}
+		}
+
+		Some(Mapping { guid, binds })
+	}
+}
+
+/// Parse a GUID, 32 hexadecimal digits.
+fn guid(text: &str) -> Option<[u8; 16]> {
+	let mut guid = [0; 16];
+
+	if text.len() != 32 || !text.is_ascii() {
+		return None;
+	}
+
+	for (i, byte) in guid.iter_mut().enumerate() {
+		*byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+	}
+
+	Some(guid)
+}
+
+/// Parse the name of a control.
+fn target(text: &str) -> Option<Target> {
+	use self::Button::*;
+
+	// Half axes: `+leftx`
+	let (half, text) = match text.as_bytes().first() {
+		Some(b'+') => (Some(true), &text[1..]),
+		Some(b'-') => (Some(false), &text[1..]),
+		_ => (None, text),
+	};
+
+	Some(match text {
+		"leftx" => Target::Axis(Axis::MoveX, half),
+		"lefty" => Target::Axis(Axis::MoveY, half),
+		"rightx" => Target::Axis(Axis::CameraX, half),
+		"righty" => Target::Axis(Axis::CameraY, half),
+		"lefttrigger" => Target::Axis(Axis::ThrottleL, half),
+		"righttrigger" => Target::Axis(Axis::ThrottleR, half),
+		"a" => Target::Button(Accept),
+		"b" => Target::Button(Cancel),
+		"x" => Target::Button(Execute),
+		"y" => Target::Button(Action),
+		"leftshoulder" => Target::Button(L(1)),
+		"rightshoulder" => Target::Button(R(1)),
+		"paddle2" => Target::Button(L(2)),
+		"paddle1" => Target::Button(R(2)),
+		"paddle4" => Target::Button(L(3)),
+		"paddle3" => Target::Button(R(3)),
+		"start" => Target::Button(Menu),
+		"guide" => Target::Button(Controls),
+		"back" => Target::Button(Exit),
+		"dpup" => Target::Button(Up),
+		"dpdown" => Target::Button(Down),
+		"dpleft" => Target::Button(Left),
+		"dpright" => Target::Button(Right),
+		"leftstick" => Target::Button(MoveStick),
+		"rightstick" => Target::Button(CamStick),
+		_ => return None,
+	})
+}
+
+/// Parse where the value of a control comes from.
+fn source(text: &str) -> Option<Source> {
+	// Half axes: `+a2`
+	let (half, text) = match text.as_bytes().first() {
+		Some(b'+') => (Some(true), &text[1..]),
+		Some(b'-') => (Some(false), &text[1..]),
+		_ => (None, text),
+	};
+	// Inverted axes: `a2~`
+	let (invert, text) = match text.strip_suffix('~') {
+		Some(text) => (true, text),
+		None => (false, text),
+	};
+
+	match text.as_bytes().first() {
+		Some(b'b') => Some(Source::Button(text[1..].parse().ok()?)),
+		Some(b'a') => {
+			let number = text[1..].parse().ok()?;
+
+			Some(Source::Axis(number, half, invert))
+		}
+		Some(b'h') => {
+			let mut hat = text[1..].splitn(2, '.');
+			let number = hat.next()?.parse().ok()?;
+			let direction = hat.next()?.parse().ok()?;
+
+			Some(Source::Hat(number, direction))
+		}
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GUID: &str = "030000005e0400008e02000014010000";
+
+	fn line(binds: &str) -> String {
+		format!("{},Test Pad,{},", GUID, binds)
+	}
+
+	#[test]
+	fn parse_guid() {
+		let guid = guid(GUID).unwrap();
+
+		assert_eq!(guid[0], 0x03);
+		assert_eq!(&guid[4..6], &[0x5e, 0x04]);
+		assert_eq!(&guid[8..10], &[0x8e, 0x02]);
+		assert_eq!(super::guid(""), None);
+		assert_eq!(super::guid(&GUID[1..]), None);
+		assert_eq!(super::guid(&format!("{}0", GUID)), None);
+		assert_eq!(super::guid("0300000g5e0400008e02000014010000"),
+			None);
+		assert_eq!(super::guid("03000000é5e0400008e020000140100"),
+			None);
+		assert_eq!(super::guid("030000005é400008e0200001401000"),
+			None);
+		assert!(Mapping::parse(&line("a:b0").replacen('3', "x", 1))
+			.is_none());
+	}
+
+	#[test]
+	fn parse_sources() {
+		let binds = Mapping::parse(&line("a:b0,leftx:a0,lefty:a1~,\
+			+rightx:+a3,-righty:-a4~,lefttrigger:a2,dpup:h0.1,\
+			dpleft:h0.8")).unwrap().binds;
+
+		assert_eq!(binds, [
+			(Target::Button(Button::Accept), Source::Button(0)),
+			(Target::Axis(Axis::MoveX, None),
+				Source::Axis(0, None, false)),
+			(Target::Axis(Axis::MoveY, None),
+				Source::Axis(1, None, true)),
+			(Target::Axis(Axis::CameraX, Some(true)),
+				Source::Axis(3, Some(true), false)),
+			(Target::Axis(Axis::CameraY, Some(false)),
+				Source::Axis(4, Some(false), true)),
+			(Target::Axis(Axis::ThrottleL, None),
+				Source::Axis(2, None, false)),
+			(Target::Button(Button::Up), Source::Hat(0, 1)),
+			(Target::Button(Button::Left), Source::Hat(0, 8)),
+		]);
+	}
+
+	#[test]
+	fn skip_unknown() {
+		let binds = Mapping::parse(&line("crc:1234,touchpad:b20,\
+			misc1:b15,a:b0,b:q1,x:h0,y:b3,stray,start:b7")).unwrap()
+			.binds;
+
+		assert_eq!(binds, [
+			(Target::Button(Button::Accept), Source::Button(0)),
+			(Target::Button(Button::Action), Source::Button(3)),
+			(Target::Button(Button::Menu), Source::Button(7)),
+		]);
+	}
+
+	#[test]
+	fn platforms() {
+		let text = format!("# Comment\n\
+			\n\
+			{}\n\
+			{}\n\
+			{}\n\
+			{}\n\
+			not a mapping\n",
+			line("a:b0,platform:Linux"),
+			line("a:b1,platform:Windows"),
+			line("a:b2,platform:Mac OS X"),
+			line("a:b3"));
+		let mappings = Mapping::parse_all(&text);
+		let binds: Vec<_> = mappings.iter().map(|m| m.binds[0].1)
+			.collect();
+
+		assert_eq!(binds, [Source::Button(0), Source::Button(3)]);
+	}
+}
@@ -0,0 +1,620 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Controller input from stick, or read directly with a mapping, with
+//! calibration, deadzones and response curves applied to the axes.  Also
+//! force feedback.
+
+use std::env;
+use std::time::Duration;
+
+use Event;
+use super::InputQueue;
+
+mod mapping;
+#[cfg(target_os = "linux")] mod linux;
+
+use self::mapping::{Mapping, Target};
+
+/// Mappings included with awi, in SDL's `gamecontrollerdb.txt` format.
+const MAPPINGS: &str = include_str!("gamecontrollerdb.txt");
+
+/// A controller axis.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Axis {
+	/// Main joystick, left (-1) to right (1).
+	MoveX = 0,
+	/// Main joystick, up (-1) to down (1).
+	MoveY = 1,
+	/// Camera / C joystick, left (-1) to right (1).
+	CameraX = 2,
+	/// Camera / C joystick, up (-1) to down (1).
+	CameraY = 3,
+	/// Left Throttle.
+	ThrottleL = 4,
+	/// Right Throttle.
+	ThrottleR = 5,
+}
+
+/// How the position of an axis becomes it's value in events.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AxisConfig {
+	/// Positions closer to the center than this (0 to 1) are 0, to hide
+	/// stick drift.  The rest of the range is scaled to fill 0 to 1.
+	/// Default is 0.1.
+	pub deadzone: f32,
+	/// Exponent of the response curve.  1 is linear (default), and higher
+	/// values give more precision near the center.
+	pub curve: f32,
+}
+
+impl Default for AxisConfig {
+	fn default() -> Self {
+		AxisConfig { deadzone: 0.1, curve: 1.0 }
+	}
+}
+
+impl AxisConfig {
+	fn apply(&self, value: f32) -> f32 {
+		let distance = value.abs();
+
+		if distance <= self.deadzone {
+			return 0.0;
+		}
+
+		let distance = ((distance - self.deadzone)
+			/ (1.0 - self.deadzone)).min(1.0);
+
+		distance.powf(self.curve) * value.signum()
+	}
+}
+
+/// Where an axis rests and how far it goes, from `Screen::calibrate()`.
+/// Positions are before calibration, from -1 to 1.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Calibration {
+	/// Position when resting, which becomes 0.
+	pub center: f32,
+	/// Lowest position, which becomes -1.
+	pub min: f32,
+	/// Highest position, which becomes 1.
+	pub max: f32,
+}
+
+impl Default for Calibration {
+	fn default() -> Self {
+		Calibration { center: 0.0, min: -1.0, max: 1.0 }
+	}
+}
+
+impl Calibration {
+	fn apply(&self, value: f32) -> f32 {
+		let range = if value >= self.center {
+			self.max - self.center
+		} else {
+			self.center - self.min
+		};
+
+		if range <= 0.0 {
+			return 0.0;
+		}
+
+		((value - self.center) / range).clamp(-1.0, 1.0)
+	}
+}
+
+/// A force feedback effect, for `Screen::force_feedback()`.  Directions are
+/// in radians, clockwise from up.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Effect {
+	/// Vibrate the strong (low frequency) and weak (high frequency) rumble
+	/// motors (0 to 1).
+	Rumble(f32, f32),
+	/// Push with a constant force (-1 to 1), in a direction.
+	Constant(f32, f32),
+	/// Push back and forth with a sine wave of a magnitude (0 to 1) and
+	/// period, in a direction.
+	Sine(f32, Duration, f32),
+}
+
+/// A controller button.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub(crate) enum Button {
+	Accept,
+	Cancel,
+	Execute,
+	Action,
+	L(u8),
+	R(u8),
+	Menu,
+	Controls,
+	Exit,
+	Up,
+	Down,
+	Left,
+	Right,
+	MoveStick,
+	CamStick,
+}
+
+impl Button {
+	/// Get the event for a button on controller `js`.  `state` is the same
+	/// as stick's (`None` when released).
+	fn event(self, js: usize, state: Option<bool>) -> Option<Event> {
+		use self::Button::*;
+
+		Some(match self {
+			Accept => Event::CAccept(js, state),
+			Cancel => Event::CCancel(js, state),
+			Execute => Event::CExecute(js, state),
+			Action => Event::CAction(js, state),
+			L(b) => Event::CL(js, b, state),
+			R(b) => Event::CR(js, b, state),
+			Menu => Event::CMenu(js, state),
+			// Only when pressed.
+			Controls if state == Some(true) => Event::CControls(js),
+			Exit if state == Some(true) => Event::CExit(js),
+			Controls | Exit => return None,
+			Up => Event::CUp(js, state),
+			Down => Event::CDown(js, state),
+			Left => Event::CLeft(js, state),
+			Right => Event::CRight(js, state),
+			MoveStick => Event::CMoveStick(js, state),
+			CamStick => Event::CCamStick(js, state),
+		})
+	}
+}
+
+/// A controller slot (stick's controller id).
+#[derive(Default)]
+struct Pad {
+	// stick's id for the device, while plugged in.
+	id: Option<i32>,
+	// The name of the device, found when it was plugged in.
+	name: Option<String>,
+	// The device, when read directly.
+	#[cfg(target_os = "linux")]
+	device: Option<linux::Device>,
+	// Position of each axis, before calibration.
+	raw: [f32; 6],
+	// Value of each axis sent last.
+	sent: [f32; 6],
+	// Buttons held last update, when read directly.
+	held: Vec<Button>,
+	calibration: [Calibration; 6],
+	// Calibration being recorded by `calibrate()`.
+	calibrating: Option<[Calibration; 6]>,
+}
+
+impl Pad {
+	/// Whether the device is read directly, rather than with stick.
+	#[cfg(target_os = "linux")]
+	fn mapped(&self) -> bool {
+		self.device.as_ref().is_some_and(|device| device.mapped())
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	fn mapped(&self) -> bool {
+		false
+	}
+
+	/// Read the motion sensors, and the device directly if it's mapped.
+	#[cfg(target_os = "linux")]
+	fn poll(&mut self, js: usize, queue: &mut InputQueue) {
+		let device = match self.device {
+			Some(ref mut device) => device,
+			None => return,
+		};
+
+		// Motion sensors are a separate device, even with stick.
+		if let Some((accel, gyro)) = device.motion() {
+			queue.controller(Event::CAccel(js, accel[0], accel[1],
+				accel[2]));
+			queue.controller(Event::CGyro(js, gyro[0], gyro[1],
+				gyro[2]));
+		}
+
+		if !device.mapped() {
+			return;
+		}
+
+		device.poll();
+
+		let mut raw = [0.0; 6];
+		let mut held = Vec::new();
+
+		for (target, value) in device.controls() {
+			match target {
+				Target::Button(button) => if value > 0.5
+					&& !held.contains(&button)
+				{
+					held.push(button);
+				},
+				Target::Axis(axis, None) => {
+					raw[axis as usize] += value
+				}
+				Target::Axis(axis, Some(true)) => {
+					raw[axis as usize] += value
+				}
+				Target::Axis(axis, Some(false)) => {
+					raw[axis as usize] -= value
+				}
+			}
+		}
+
+		for raw in raw.iter_mut() {
+			*raw = raw.clamp(-1.0, 1.0);
+		}
+
+		// Throttles pressed most of the way are also the trigger
+		// buttons, like stick.
+		for &(button, axis) in &[(Button::L(0), Axis::ThrottleL),
+			(Button::R(0), Axis::ThrottleR)]
+		{
+			if raw[axis as usize] > 0.5 && !held.contains(&button) {
+				held.push(button);
+			}
+		}
+
+		for &button in &held {
+			let state = Some(!self.held.contains(&button));
+
+			if let Some(event) = button.event(js, state) {
+				queue.controller(event);
+			}
+		}
+		for &button in &self.held {
+			if !held.contains(&button) {
+				if let Some(event) = button.event(js, None) {
+					queue.controller(event);
+				}
+			}
+		}
+
+		self.raw = raw;
+		self.held = held;
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	fn poll(&mut self, _js: usize, _queue: &mut InputQueue) {}
+
+	/// Send axis events, like stick: sticks every update while they're
+	/// off-center (and once when they're let go), throttles on change.
+	fn axes(&mut self, js: usize, config: &[AxisConfig; 6],
+		queue: &mut InputQueue)
+	{
+		let mut value = [0.0; 6];
+
+		for (i, value) in value.iter_mut().enumerate() {
+			if let Some(ref mut calibrating) = self.calibrating {
+				calibrating[i].min = calibrating[i].min
+					.min(self.raw[i]);
+				calibrating[i].max = calibrating[i].max
+					.max(self.raw[i]);
+			}
+
+			*value = config[i].apply(self.calibration[i]
+				.apply(self.raw[i]));
+		}
+
+		let sent = self.sent;
+		let stick = |a: usize| {
+			value[a] != 0.0 || value[a + 1] != 0.0
+				|| sent[a] != 0.0 || sent[a + 1] != 0.0
+		};
+
+		if stick(Axis::MoveX as usize) {
+			queue.controller(Event::CMove(js, value[0], value[1]));
+		}
+		if stick(Axis::CameraX as usize) {
+			queue.controller(Event::CCamera(js, value[2],
+				value[3]));
+		}
+		if value[4] != sent[4] {
+			queue.controller(Event::CThrottleL(js, value[4]));
+		}
+		if value[5] != sent[5] {
+			queue.controller(Event::CThrottleR(js, value[5]));
+		}
+
+		self.sent = value;
+	}
+}
+
+/// Every controller.
+pub(crate) struct Gamepads {
+	cm: ::stick::ControllerManager,
+	mappings: Vec<Mapping>,
+	config: [AxisConfig; 6],
+	pads: Vec<Pad>,
+}
+
+impl Gamepads {
+	/// Start looking for controllers, with the included mappings and ones
+	/// in `SDL_GAMECONTROLLERCONFIG` (same as SDL).
+	pub(crate) fn new() -> Self {
+		let mut mappings = Mapping::parse_all(MAPPINGS);
+
+		if let Ok(config) = env::var("SDL_GAMECONTROLLERCONFIG") {
+			mappings.extend(Mapping::parse_all(&config));
+		}
+
+		Gamepads {
+			cm: ::stick::ControllerManager::new(vec![]),
+			mappings, config: [AxisConfig::default(); 6],
+			pads: Vec::new(),
+		}
+	}
+
+	/// Add mappings in SDL's `gamecontrollerdb.txt` format, replacing
+	/// existing ones for the same controllers.  Returns how many were
+	/// added.
+	pub(crate) fn add_mappings(&mut self, mappings: &str) -> usize {
+		let mappings = Mapping::parse_all(mappings);
+		let count = mappings.len();
+
+		self.mappings.extend(mappings);
+
+		// Controllers already plugged in may have a mapping now.
+		for js in 0..self.pads.len() {
+			if let Some(id) = self.pads[js].id {
+				self.open(js, id);
+			}
+		}
+
+		count
+	}
+
+	/// Set how the position of `axis` becomes it's value.
+	pub(crate) fn axis_config(&mut self, axis: Axis, config: AxisConfig) {
+		self.config[axis as usize] = config;
+	}
+
+	/// Start (`true`) or finish (`false`) calibrating controller `js`.
+	pub(crate) fn calibrate(&mut self, js: usize, calibrate: bool) {
+		let pad = self.pad(js);
+
+		pad.calibrating = if calibrate {
+			let mut calibrating = [Calibration::default(); 6];
+
+			for (c, &raw) in calibrating.iter_mut().zip(&pad.raw) {
+				*c = Calibration {
+					center: raw, min: raw, max: raw
+				};
+			}
+
+			Some(calibrating)
+		} else {
+			if let Some(mut calibrating) = pad.calibrating.take() {
+				// Sides that weren't moved keep the full range.
+				for c in calibrating.iter_mut() {
+					if c.max - c.center < 0.25 {
+						c.max = 1.0;
+					}
+					if c.center - c.min < 0.25 {
+						c.min = -1.0;
+					}
+				}
+				pad.calibration = calibrating;
+			}
+
+			None
+		};
+	}
+
+	/// Get the name of controller `js`, if it's known.
+	pub(crate) fn name(&self, js: usize) -> Option<String> {
+		self.pads.get(js).and_then(|pad| pad.name.clone())
+	}
+
+	/// Get the calibration of `axis` on controller `js`.
+	pub(crate) fn calibration(&self, js: usize, axis: Axis) -> Calibration {
+		self.pads.get(js).map(|pad| pad.calibration[axis as usize])
+			.unwrap_or_default()
+	}
+
+	/// Set the calibration of `axis` on controller `js`.
+	pub(crate) fn set_calibration(&mut self, js: usize, axis: Axis,
+		calibration: Calibration)
+	{
+		self.pad(js).calibration[axis as usize] = calibration;
+	}
+
+	/// Play a force feedback effect on controller `js`, replacing the
+	/// last one.  Returns why, if it can't.
+	#[cfg(target_os = "linux")]
+	pub(crate) fn force_feedback(&mut self, js: usize, effect: Effect,
+		duration: Duration) -> Result<(), String>
+	{
+		let pad = self.pads.get_mut(js);
+
+		match pad.and_then(|pad| pad.device.as_mut()) {
+			Some(device) => device.effect(effect, duration),
+			None => Err(format!("controller {} isn't open", js)),
+		}
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	pub(crate) fn force_feedback(&mut self, _js: usize, _effect: Effect,
+		_duration: Duration) -> Result<(), String>
+	{
+		Err("force feedback is only supported on Linux".to_string())
+	}
+
+	/// Generate controller events.
+	pub(crate) fn update(&mut self, queue: &mut InputQueue) {
+		// stick sends sticks every update while they're off-center.
+		for pad in self.pads.iter_mut().filter(|pad| !pad.mapped()) {
+			for raw in &mut pad.raw[..4] {
+				*raw = 0.0;
+			}
+		}
+
+		while let Some((js, input)) = self.cm.update() {
+			use stick::Input::*;
+
+			let event = match input {
+				PluggedIn(id) => {
+					self.pad(js).id = Some(id);
+					self.open(js, id);
+					Event::CPluggedIn(js, id)
+				}
+				UnPlugged(id) => {
+					let pad = self.pad(js);
+
+					*pad = Pad {
+						calibration: pad.calibration,
+						..Pad::default()
+					};
+					Event::CUnPlugged(js, id)
+				}
+				// Read directly instead.
+				_ if self.pad(js).mapped() => continue,
+				Move(x, y) => {
+					self.pad(js).raw[0] = x;
+					self.pad(js).raw[1] = y;
+					continue;
+				}
+				Camera(x, y) => {
+					self.pad(js).raw[2] = x;
+					self.pad(js).raw[3] = y;
+					continue;
+				}
+				ThrottleL(x) => {
+					self.pad(js).raw[4] = x;
+					continue;
+				}
+				ThrottleR(x) => {
+					self.pad(js).raw[5] = x;
+					continue;
+				}
+				Accept(s) => Event::CAccept(js, s),
+				Cancel(s) => Event::CCancel(js, s),
+				Execute(s) => Event::CExecute(js, s),
+				Action(s) => Event::CAction(js, s),
+				L(b, s) => Event::CL(js, b, s),
+				R(b, s) => Event::CR(js, b, s),
+				Menu(s) => Event::CMenu(js, s),
+				Controls => Event::CControls(js),
+				Exit => Event::CExit(js),
+				Up(s) => Event::CUp(js, s),
+				Down(s) => Event::CDown(js, s),
+				Left(s) => Event::CLeft(js, s),
+				Right(s) => Event::CRight(js, s),
+				MoveStick(s) => Event::CMoveStick(js, s),
+				CamStick(s) => Event::CCamStick(js, s),
+			};
+
+			queue.controller(event);
+		}
+
+		for (js, pad) in self.pads.iter_mut().enumerate() {
+			if pad.id.is_some() {
+				pad.poll(js, queue);
+				pad.axes(js, &self.config, queue);
+			}
+		}
+	}
+
+	/// Get controller slot `js`, adding it if it doesn't exist yet.
+	fn pad(&mut self, js: usize) -> &mut Pad {
+		while self.pads.len() <= js {
+			self.pads.push(Pad::default());
+		}
+
+		&mut self.pads[js]
+	}
+
+	/// Open controller `js`, for force feedback and to read directly if
+	/// there's a mapping for it.
+	#[cfg(target_os = "linux")]
+	fn open(&mut self, js: usize, id: i32) {
+		use std::path::Path;
+
+		self.pad(js).device = None;
+
+		let device = {
+			let open: Vec<&Path> = self.pads.iter()
+				.filter_map(|pad| pad.device.as_ref())
+				.map(|device| device.path())
+				.collect();
+
+			linux::Device::open(id, &self.mappings, &open)
+		};
+
+		self.pads[js].name = device.as_ref()
+			.map(|device| device.name().to_string())
+			.filter(|name| !name.is_empty());
+		self.pads[js].device = device;
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	fn open(&mut self, _js: usize, _id: i32) {}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn near(a: f32, b: f32) -> bool {
+		(a - b).abs() < 1e-6
+	}
+
+	#[test]
+	fn axis_config() {
+		for &(deadzone, curve, value, expected) in &[
+			// Default: no curve, 0.1 deadzone.
+			(0.1, 1.0, 0.0, 0.0),
+			(0.1, 1.0, 0.1, 0.0),
+			(0.1, 1.0, -0.05, 0.0),
+			(0.1, 1.0, 0.55, 0.5),
+			(0.1, 1.0, -0.55, -0.5),
+			(0.1, 1.0, 1.0, 1.0),
+			(0.1, 1.0, -1.5, -1.0),
+			// Curves keep the sign.
+			(0.0, 2.0, 0.5, 0.25),
+			(0.0, 2.0, -0.5, -0.25),
+			(0.5, 2.0, 0.75, 0.25),
+			(0.0, 0.5, 0.25, 0.5),
+		] {
+			let config = AxisConfig { deadzone, curve };
+			let value = config.apply(value);
+
+			assert!(near(value, expected), "{:?} {}", config, value);
+		}
+	}
+
+	#[test]
+	fn calibration() {
+		for &(center, min, max, value, expected) in &[
+			(0.0, -1.0, 1.0, 0.5, 0.5),
+			// Each side is scaled separately.
+			(0.1, -0.8, 0.9, 0.1, 0.0),
+			(0.1, -0.8, 0.9, 0.5, 0.5),
+			(0.1, -0.8, 0.9, 0.9, 1.0),
+			(0.1, -0.8, 0.9, -0.35, -0.5),
+			(0.1, -0.8, 0.9, -0.8, -1.0),
+			// Past the calibrated range.
+			(0.1, -0.8, 0.9, 1.0, 1.0),
+			(0.1, -0.8, 0.9, -1.0, -1.0),
+			// Triggers rest at one end.
+			(-1.0, -1.0, 1.0, 0.0, 0.5),
+			(-1.0, -1.0, 1.0, -1.0, 0.0),
+			// Nothing measured on a side.
+			(1.0, -1.0, 1.0, 1.0, 0.0),
+			(0.0, 0.0, 0.0, -0.5, 0.0),
+		] {
+			let calibration = Calibration { center, min, max };
+			let value = calibration.apply(value);
+
+			assert!(near(value, expected), "{:?} {}", calibration,
+				value);
+		}
+	}
+}
@@ -0,0 +1,406 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Gestures (tap, pinch, etc.), recognized from touches.
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use Event;
+
+// Longest touch that's a tap, and longest wait between double taps (seconds).
+const TAP_TIME: f32 = 0.3;
+// Shortest touch that's a long press (seconds).
+const LONG_PRESS_TIME: f32 = 0.5;
+// How far a finger can move and still be a tap or long press (the window is
+// 2 high).
+const SLOP: f32 = 0.04;
+
+/// Recognizes gestures from `Event::Touch`.  Touchpad gestures are sent by
+/// the OS as `Pan`, `Pinch` and `Rotate` already.
+pub(crate) struct Gestures {
+	// Width / height of the window, to measure distances and angles.
+	aspect: f32,
+	// Fingers touching, and where.
+	fingers: Vec<(usize, (f32, f32))>,
+	// How long the only finger has been down, or `None` if it's not a tap
+	// or long press.
+	held: Option<f32>,
+	// Where the first finger touched.
+	start: (f32, f32),
+	// Time since the last tap, and where, for double taps.
+	tap: Option<(f32, (f32, f32))>,
+	// Whether the fingers moved far enough to pan.
+	panning: bool,
+	// Centroid, spread and angle of the fingers at the last gesture.
+	centroid: (f32, f32),
+	spread: f32,
+	angle: f32,
+	// Gestures recognized, but not sent yet.
+	queue: VecDeque<Event>,
+}
+
+impl Gestures {
+	/// Recognize gestures in a window of size `wh`.
+	pub(crate) fn new(wh: (u16, u16)) -> Gestures {
+		Gestures {
+			aspect: aspect(wh.0, wh.1),
+			fingers: Vec::new(),
+			held: None,
+			start: (0.0, 0.0),
+			tap: None,
+			panning: false,
+			centroid: (0.0, 0.0),
+			spread: 0.0,
+			angle: 0.0,
+			queue: VecDeque::new(),
+		}
+	}
+
+	/// Update the fingers from `event`.  Long presses and double taps are
+	/// timed with `Timestep`'s `dt`.
+	pub(crate) fn update(&mut self, event: Event, dt: f32) {
+		match event {
+			Event::Resize(w, h) => self.aspect = aspect(w, h),
+			// The releases won't be sent.
+			Event::Pause => {
+				self.fingers.clear();
+				self.held = None;
+			}
+			Event::Timestep => self.timestep(dt),
+			Event::Touch(i, Some(true), Some(xy)) => {
+				self.fingers.retain(|f| f.0 != i);
+				self.fingers.push((i, xy));
+				if self.fingers.len() == 1 {
+					self.held = Some(0.0);
+					self.start = xy;
+					self.panning = false;
+				} else {
+					self.held = None;
+					self.panning = true;
+				}
+				self.measure();
+			}
+			Event::Touch(i, Some(false), Some(xy)) => {
+				let finger = self.fingers.iter_mut()
+					.find(|f| f.0 == i);
+
+				match finger {
+					Some(finger) => finger.1 = xy,
+					None => return,
+				}
+				if self.distance(xy, self.start) > SLOP {
+					self.held = None;
+					self.panning = true;
+				}
+				if self.panning {
+					self.moved();
+				}
+			}
+			Event::Touch(i, None, _) => {
+				let len = self.fingers.len();
+
+				self.fingers.retain(|f| f.0 != i);
+				if self.fingers.len() == len {
+					return;
+				}
+				if self.fingers.is_empty() {
+					self.lifted();
+				} else {
+					self.measure();
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Get the next gesture recognized.
+	pub(crate) fn pop(&mut self) -> Option<Event> {
+		self.queue.pop_front()
+	}
+
+	fn timestep(&mut self, dt: f32) {
+		if let Some((ref mut time, _)) = self.tap {
+			*time += dt;
+		}
+		if self.tap.is_some_and(|tap| tap.0 > TAP_TIME) {
+			self.tap = None;
+		}
+
+		if let Some(held) = self.held {
+			if held + dt >= LONG_PRESS_TIME {
+				self.held = None;
+				self.send(Event::LongPress(self.start));
+			} else {
+				self.held = Some(held + dt);
+			}
+		}
+	}
+
+	/// The last finger was lifted.
+	fn lifted(&mut self) {
+		match self.held.take() {
+			Some(held) if held <= TAP_TIME => {}
+			_ => return,
+		}
+
+		let start = self.start;
+
+		match self.tap.take() {
+			Some((_, xy)) if self.distance(xy, start) <= SLOP => {
+				self.send(Event::DoubleTap(start));
+			}
+			_ => {
+				self.tap = Some((0.0, start));
+				self.send(Event::Tap(start));
+			}
+		}
+	}
+
+	/// The fingers moved: pan, and pinch and rotate with 2 or more.
+	fn moved(&mut self) {
+		let (centroid, spread, angle) = self.shape();
+		let movement = (centroid.0 - self.centroid.0,
+			centroid.1 - self.centroid.1);
+
+		if movement != (0.0, 0.0) {
+			self.send(Event::Pan(centroid, movement));
+		}
+
+		if self.fingers.len() >= 2 {
+			if self.spread > 0.0 && spread != self.spread {
+				let scale = spread / self.spread;
+
+				self.send(Event::Pinch(centroid, scale));
+			}
+
+			// Take the short way around.
+			let mut turn = angle - self.angle;
+			if turn > PI {
+				turn -= 2.0 * PI;
+			} else if turn < -PI {
+				turn += 2.0 * PI;
+			}
+			if turn != 0.0 {
+				self.send(Event::Rotate(centroid, turn));
+			}
+		}
+
+		self.centroid = centroid;
+		self.spread = spread;
+		self.angle = angle;
+	}
+
+	/// Start measuring from where the fingers are now (a finger was added
+	/// or removed), so the gestures don't jump.
+	fn measure(&mut self) {
+		let (centroid, spread, angle) = self.shape();
+
+		self.centroid = centroid;
+		self.spread = spread;
+		self.angle = angle;
+	}
+
+	/// Get the centroid of the fingers, their average distance from it,
+	/// and the angle from the first finger to the second.
+	fn shape(&self) -> ((f32, f32), f32, f32) {
+		let n = self.fingers.len().max(1) as f32;
+		let (x, y) = self.fingers.iter().fold((0.0, 0.0), |a, f| {
+			(a.0 + (f.1).0, a.1 + (f.1).1)
+		});
+		let centroid = (x / n, y / n);
+		let spread = self.fingers.iter()
+			.map(|f| self.distance(f.1, centroid))
+			.sum::<f32>() / n;
+		let angle = match (self.fingers.first(), self.fingers.get(1)) {
+			(Some(a), Some(b)) => {
+				let dx = (b.1).0 - (a.1).0;
+				let dy = (b.1).1 - (a.1).1;
+
+				dy.atan2(dx * self.aspect)
+			}
+			_ => 0.0,
+		};
+
+		(centroid, spread, angle)
+	}
+
+	/// Send a gesture.
+	fn send(&mut self, gesture: Event) {
+		self.queue.push_back(gesture);
+	}
+
+	/// Distance between two points (the window is 2 high).
+	fn distance(&self, a: (f32, f32), b: (f32, f32)) -> f32 {
+		((a.0 - b.0) * self.aspect).hypot(a.1 - b.1)
+	}
+}
+
+fn aspect(w: u16, h: u16) -> f32 {
+	if h == 0 {
+		1.0
+	} else {
+		w as f32 / h as f32
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn press(i: usize, xy: (f32, f32)) -> Event {
+		Event::Touch(i, Some(true), Some(xy))
+	}
+
+	fn drag(i: usize, xy: (f32, f32)) -> Event {
+		Event::Touch(i, Some(false), Some(xy))
+	}
+
+	fn lift(i: usize) -> Event {
+		Event::Touch(i, None, None)
+	}
+
+	/// Send `events` with their timestep, and get the gestures.
+	fn run(gestures: &mut Gestures, events: &[(Event, f32)]) -> Vec<Event> {
+		let mut sent = vec![];
+
+		for &(event, dt) in events {
+			gestures.update(event, dt);
+			while let Some(gesture) = gestures.pop() {
+				sent.push(gesture);
+			}
+		}
+
+		sent
+	}
+
+	fn names(events: &[Event]) -> Vec<String> {
+		events.iter().map(Event::to_string).collect()
+	}
+
+	#[test]
+	fn tap() {
+		let mut gestures = Gestures::new((100, 100));
+		let sent = run(&mut gestures, &[(press(0, (0.5, 0.5)), 0.0),
+			(Event::Timestep, 0.1), (Event::Timestep, 0.1),
+			(lift(0), 0.0)]);
+
+		assert_eq!(names(&sent), names(&[Event::Tap((0.5, 0.5))]));
+	}
+
+	#[test]
+	fn slow_tap() {
+		let mut gestures = Gestures::new((100, 100));
+		let sent = run(&mut gestures, &[(press(0, (0.5, 0.5)), 0.0),
+			(Event::Timestep, 0.2), (Event::Timestep, 0.2),
+			(lift(0), 0.0)]);
+
+		assert!(sent.is_empty(), "{:?}", names(&sent));
+	}
+
+	#[test]
+	fn long_press() {
+		let mut gestures = Gestures::new((100, 100));
+		let sent = run(&mut gestures, &[(press(0, (0.5, 0.5)), 0.0),
+			(Event::Timestep, 0.3), (drag(0, (0.51, 0.5)), 0.0),
+			(Event::Timestep, 0.3), (Event::Timestep, 0.3),
+			(lift(0), 0.0)]);
+
+		assert_eq!(names(&sent),
+			names(&[Event::LongPress((0.5, 0.5))]));
+	}
+
+	#[test]
+	fn moved_isnt_tap() {
+		let mut gestures = Gestures::new((100, 100));
+		let sent = run(&mut gestures, &[(press(0, (0.0, 0.0)), 0.0),
+			(drag(0, (0.1, 0.0)), 0.0), (Event::Timestep, 0.6),
+			(lift(0), 0.0)]);
+
+		assert_eq!(names(&sent),
+			names(&[Event::Pan((0.1, 0.0), (0.1, 0.0))]));
+	}
+
+	#[test]
+	fn double_tap() {
+		let tap = |xy| [(press(0, xy), 0.0), (Event::Timestep, 0.1),
+			(lift(0), 0.0)];
+		let mut gestures = Gestures::new((100, 100));
+		let mut events = tap((0.0, 0.0)).to_vec();
+		events.extend(&tap((0.02, 0.0)));
+
+		assert_eq!(names(&run(&mut gestures, &events)), names(&[
+			Event::Tap((0.0, 0.0)),
+			Event::DoubleTap((0.02, 0.0)),
+		]));
+
+		// Too far apart.
+		let mut events = tap((0.0, 0.0)).to_vec();
+		events.extend(&tap((0.5, 0.0)));
+
+		assert_eq!(names(&run(&mut gestures, &events)), names(&[
+			Event::Tap((0.0, 0.0)),
+			Event::Tap((0.5, 0.0)),
+		]));
+
+		// Too slow.
+		let mut gestures = Gestures::new((100, 100));
+		let mut events = tap((0.0, 0.0)).to_vec();
+		events.push((Event::Timestep, 0.3));
+		events.extend(&tap((0.0, 0.0)));
+
+		assert_eq!(names(&run(&mut gestures, &events)), names(&[
+			Event::Tap((0.0, 0.0)),
+			Event::Tap((0.0, 0.0)),
+		]));
+	}
+
+	#[test]
+	fn pinch() {
+		let mut gestures = Gestures::new((100, 100));
+		let sent = run(&mut gestures, &[(press(0, (-0.1, 0.0)), 0.0),
+			(press(1, (0.1, 0.0)), 0.0), (drag(1, (0.3, 0.0)), 0.0),
+			(drag(0, (-0.3, 0.0)), 0.0)]);
+		let scales: Vec<f32> = sent.iter().filter_map(|e| match *e {
+			Event::Pinch(_, scale) => Some(scale),
+			_ => None,
+		}).collect();
+
+		assert_eq!(scales.len(), 2);
+		assert!((scales[0] * scales[1] - 3.0).abs() < 1e-5);
+		assert!(!sent.iter().any(|e| match *e {
+			Event::Tap(_) | Event::LongPress(_) => true,
+			Event::Rotate(_, a) => a.abs() > 1e-5,
+			_ => false,
+		}), "{:?}", names(&sent));
+	}
+
+	#[test]
+	fn rotate_across_pi() {
+		let mut gestures = Gestures::new((100, 100));
+		// The angle from the first finger to the second goes from just
+		// under π to just over -π.
+		let sent = run(&mut gestures, &[(press(0, (0.0, 0.0)), 0.0),
+			(press(1, (-0.5, 0.01)), 0.0),
+			(drag(1, (-0.5, -0.01)), 0.0)]);
+		let turns: Vec<f32> = sent.iter().filter_map(|e| match *e {
+			Event::Rotate(_, turn) => Some(turn),
+			_ => None,
+		}).collect();
+
+		assert_eq!(turns.len(), 1);
+		assert!(turns[0] > 0.0 && turns[0] < 0.1, "{}", turns[0]);
+	}
+
+	#[test]
+	fn pause_cancels() {
+		let mut gestures = Gestures::new((100, 100));
+		let sent = run(&mut gestures, &[(press(0, (0.5, 0.5)), 0.0),
+			(Event::Pause, 0.0), (Event::Timestep, 1.0),
+			(lift(0), 0.0)]);
+
+		assert!(sent.is_empty(), "{:?}", names(&sent));
+	}
+}
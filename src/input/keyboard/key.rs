@@ -0,0 +1,390 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Keys, their states, and key combinations ("Ctrl+Shift+S").
+
+use std::fmt;
+use std::str::FromStr;
+
+// Calls `$m!` with every key, and it's name (for `Display` and `FromStr`).
+macro_rules! keys {
+	($m:ident) => {
+		$m! {
+			/// 1
+			Num1 = "1",
+			/// 2
+			Num2 = "2",
+			/// 3
+			Num3 = "3",
+			/// 4
+			Num4 = "4",
+			/// 5
+			Num5 = "5",
+			/// 6
+			Num6 = "6",
+			/// 7
+			Num7 = "7",
+			/// 8
+			Num8 = "8",
+			/// 9
+			Num9 = "9",
+			/// 0
+			Num0 = "0",
+			/// \-
+			Minus = "-",
+			/// \=
+			EqualSign = "=",
+			/// Backspace
+			Backspace = "Backspace",
+			/// Tab
+			Tab = "Tab",
+			/// Q
+			Q = "Q",
+			/// W
+			W = "W",
+			/// E
+			E = "E",
+			/// R
+			R = "R",
+			/// T
+			T = "T",
+			/// Y
+			Y = "Y",
+			/// U
+			U = "U",
+			/// I
+			I = "I",
+			/// O
+			O = "O",
+			/// P
+			P = "P",
+			/// [
+			BracketOpen = "[",
+			/// ]
+			BracketClose = "]",
+			/// Backslash
+			Backslash = "\\",
+			/// Compose (CAPS LOCK)
+			Compose = "Compose",
+			/// A
+			A = "A",
+			/// S
+			S = "S",
+			/// D
+			D = "D",
+			/// F
+			F = "F",
+			/// G
+			G = "G",
+			/// H
+			H = "H",
+			/// J
+			J = "J",
+			/// K
+			K = "K",
+			/// L
+			L = "L",
+			/// ;
+			Semicolon = ";",
+			/// '
+			Apostrophe = "'",
+			/// Enter
+			Enter = "Enter",
+			/// Left Shift
+			LShift = "LShift",
+			/// Z
+			Z = "Z",
+			/// X
+			X = "X",
+			/// C
+			C = "C",
+			/// V
+			V = "V",
+			/// B
+			B = "B",
+			/// N
+			N = "N",
+			/// M
+			M = "M",
+			/// ,
+			Comma = ",",
+			/// .
+			Period = ".",
+			/// /
+			Slash = "/",
+			/// Right Shift
+			RShift = "RShift",
+			/// Left CTRL
+			LCtrl = "LCtrl",
+			/// Alt (Left)
+			Alt = "Alt",
+			/// Space
+			Space = "Space",
+			/// Right Control
+			RCtrl = "RCtrl",
+			/// Up Arrow Key
+			Up = "Up",
+			/// Down Arrow Key
+			Down = "Down",
+			/// Left Arrow Key
+			Left = "Left",
+			/// Right Arrow Key
+			Right = "Right",
+			// May require 2 keys to be pressed on some platforms.
+			/// `
+			Backtick = "`",
+			/// Delete
+			Delete = "Delete",
+			/// Insert
+			Insert = "Insert",
+			/// NumLock
+			NumLock = "NumLock",
+			/// Page Up
+			PageUp = "PageUp",
+			/// Page Down
+			PageDown = "PageDown",
+			/// Home
+			Home = "Home",
+			/// End
+			End = "End",
+			/// \*
+			Asterisk = "*",
+			/// \+
+			Plus = "+",
+			/// AltGr (Right Alt)
+			AltGr = "AltGr",
+		}
+	}
+}
+
+macro_rules! key_enum {
+	($($(#[$attr:meta])* $key:ident = $name:expr,)*) => {
+		/// A key on the keyboard.  Keys are named after their place on a
+		/// US QWERTY keyboard (the physical key, not the character).
+		#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+		#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+		pub enum Key {
+			$($(#[$attr])* $key,)*
+		}
+
+		impl Key {
+			/// Every key, in the order they're declared.
+			pub(crate) const ALL: &'static [Key] = &[$(Key::$key,)*];
+
+			/// The name of the key, like "PageUp" or "S".
+			pub fn name(self) -> &'static str {
+				match self {
+					$(Key::$key => $name,)*
+				}
+			}
+		}
+	}
+}
+
+keys!(key_enum);
+
+impl fmt::Display for Key {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl FromStr for Key {
+	type Err = ParseKeyError;
+
+	/// Parse the name of a key (ignoring case), like "PageUp" or "s".
+	/// "Plus" is also the `+` key.
+	fn from_str(s: &str) -> Result<Key, ParseKeyError> {
+		let s = s.trim();
+
+		if s.eq_ignore_ascii_case("plus") {
+			return Ok(Key::Plus);
+		}
+
+		Key::ALL.iter().cloned()
+			.find(|key| key.name().eq_ignore_ascii_case(s))
+			.ok_or(ParseKeyError)
+	}
+}
+
+/// The state of a key in `Event::Key`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyState {
+	/// The key was just pressed.
+	Pressed,
+	/// The key is still held down (sent every frame).
+	Held,
+	/// The key was just released.
+	Released,
+	/// The key is held down, and the OS sent an auto-repeat.
+	Repeat,
+}
+
+impl KeyState {
+	/// Whether the key is down (not `Released`).
+	pub fn is_down(self) -> bool {
+		self != KeyState::Released
+	}
+}
+
+/// A key, with modifiers held down, such as "Ctrl+Shift+S".  Parse one from
+/// a string to load key bindings.  Either Ctrl, either Shift, or Alt count
+/// as the modifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyCombo {
+	/// Whether Ctrl is held.
+	pub ctrl: bool,
+	/// Whether Shift is held.
+	pub shift: bool,
+	/// Whether Alt is held.
+	pub alt: bool,
+	/// The key pressed.
+	pub key: Key,
+}
+
+impl KeyCombo {
+	/// A key without modifiers.
+	pub fn new(key: Key) -> KeyCombo {
+		KeyCombo { ctrl: false, shift: false, alt: false, key }
+	}
+}
+
+impl From<Key> for KeyCombo {
+	fn from(key: Key) -> KeyCombo {
+		KeyCombo::new(key)
+	}
+}
+
+impl fmt::Display for KeyCombo {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.ctrl {
+			f.write_str("Ctrl+")?;
+		}
+		if self.shift {
+			f.write_str("Shift+")?;
+		}
+		if self.alt {
+			f.write_str("Alt+")?;
+		}
+		write!(f, "{}", self.key)
+	}
+}
+
+impl FromStr for KeyCombo {
+	type Err = ParseKeyError;
+
+	/// Parse modifiers (Ctrl, Shift, Alt) and a key, separated by `+`,
+	/// ignoring case: "Ctrl+Shift+S".  Each modifier may only be listed
+	/// once.
+	fn from_str(s: &str) -> Result<KeyCombo, ParseKeyError> {
+		let s = s.trim();
+		// The `+` key is the last `+`: "Ctrl++".
+		let (mods, key) = match s.strip_suffix("++") {
+			Some(mods) => (mods, "+"),
+			None if s == "+" => ("", "+"),
+			None => match s.rfind('+') {
+				Some(i) => (&s[..i], &s[i + 1..]),
+				None => ("", s),
+			},
+		};
+		let mut combo = KeyCombo::new(key.parse()?);
+
+		for modifier in mods.split('+').filter(|m| !m.is_empty()) {
+			let modifier = modifier.trim().to_ascii_lowercase();
+
+			let held = match modifier.as_str() {
+				"ctrl" | "control" => &mut combo.ctrl,
+				"shift" => &mut combo.shift,
+				"alt" => &mut combo.alt,
+				_ => return Err(ParseKeyError),
+			};
+
+			if *held {
+				return Err(ParseKeyError);
+			}
+			*held = true;
+		}
+
+		Ok(combo)
+	}
+}
+
+/// The error from parsing a `Key` or `KeyCombo` that isn't one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParseKeyError;
+
+impl fmt::Display for ParseKeyError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("unknown key")
+	}
+}
+
+impl ::std::error::Error for ParseKeyError {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn combo(ctrl: bool, shift: bool, alt: bool, key: Key) -> KeyCombo {
+		KeyCombo { ctrl, shift, alt, key }
+	}
+
+	#[test]
+	fn parse_key() {
+		assert_eq!("PageUp".parse(), Ok(Key::PageUp));
+		assert_eq!(" s ".parse(), Ok(Key::S));
+		assert_eq!("+".parse(), Ok(Key::Plus));
+		assert_eq!("Plus".parse(), Ok(Key::Plus));
+		assert_eq!("plus".parse(), Ok(Key::Plus));
+		assert_eq!("".parse::<Key>(), Err(ParseKeyError));
+		assert_eq!("Hyper".parse::<Key>(), Err(ParseKeyError));
+	}
+
+	#[test]
+	fn parse_combo() {
+		let parse = |s: &str| s.parse::<KeyCombo>();
+
+		assert_eq!(parse("S"), Ok(combo(false, false, false, Key::S)));
+		assert_eq!(parse("Ctrl+Shift+S"),
+			Ok(combo(true, true, false, Key::S)));
+		assert_eq!(parse("ctrl+s"),
+			Ok(combo(true, false, false, Key::S)));
+		assert_eq!(parse("Alt + Control + Delete"),
+			Ok(combo(true, false, true, Key::Delete)));
+		assert_eq!(parse("+"),
+			Ok(combo(false, false, false, Key::Plus)));
+		assert_eq!(parse("Ctrl++"),
+			Ok(combo(true, false, false, Key::Plus)));
+		assert_eq!(parse("Ctrl+Plus"),
+			Ok(combo(true, false, false, Key::Plus)));
+		assert_eq!(parse("Ctrl+Shift++"),
+			Ok(combo(true, true, false, Key::Plus)));
+	}
+
+	#[test]
+	fn parse_combo_errors() {
+		for s in &["", "Ctrl+", "Ctrl+Shift+", "Hyper+S", "Ctrl+Hyper",
+			"Ctrl+Ctrl+S", "Shift+shift+S", "S+Ctrl"]
+		{
+			assert_eq!(s.parse::<KeyCombo>(), Err(ParseKeyError),
+				"{:?}", s);
+		}
+	}
+
+	#[test]
+	fn display_parses() {
+		for key in Key::ALL {
+			for &(ctrl, shift, alt) in &[(false, false, false),
+				(true, true, true), (true, false, false)]
+			{
+				let combo = combo(ctrl, shift, alt, *key);
+
+				assert_eq!(combo.to_string().parse(), Ok(combo));
+			}
+		}
+	}
+}
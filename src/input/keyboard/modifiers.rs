@@ -4,6 +4,7 @@
 // https://www.boost.org/LICENSE_1_0.txt)
 
 use Event;
+use super::{Key, KeyState};
 
 const NONE : u8 = 0b0000_0000;
 const SHIFT : u8 = 0b0000_0001;
@@ -28,81 +29,78 @@ impl Modifiers {
 				NONE | SHIFT => {},
 				_ => return, // Ctrl,Shift,Alt shouldn't print.
 			},
-			Event::LCtrl(state) | Event::RCtrl(state) => {
-				if state.is_some() {
-					self.held |= CTRL
-				} else {
-					self.held &= !CTRL
-				}
-			},
-			Event::LShift(state) | Event::RShift(state) => {
-				if state.is_some() {
-					self.held |= SHIFT
-				} else {
-					self.held &= !SHIFT
-				}
-			},
-			Event::Alt(state) => {
-				if state.is_some() {
-					self.held |= ALT
-				} else {
-					self.held &= !ALT
-				}
+			Event::Key { key, state } => self.key(queue, key, state),
+			_ => {},
+		}
+		queue.push(input)
+	}
+
+	fn key(&mut self, queue: &mut Vec<Event>, key: Key, state: KeyState) {
+		match key {
+			Key::LCtrl | Key::RCtrl => self.hold(CTRL, state),
+			Key::LShift | Key::RShift => self.hold(SHIFT, state),
+			Key::Alt => self.hold(ALT, state),
+			// Toggle compose state.
+			Key::Compose => if state == KeyState::Pressed {
+				self.held ^= COMPOSE
 			},
-			Event::Compose(state) => {
-				// Toggle compose state.
-				if state.is_some() {
-					if self.held & COMPOSE == 0 {
-						self.held |= COMPOSE
-					} else {
-						self.held &= !COMPOSE
-					}
-				}
+			// Shortcuts on press, and auto-repeat.
+			_ if state == KeyState::Pressed
+				|| state == KeyState::Repeat => match key
+			{
+				Key::A => self.a(queue),
+				Key::B => self.b(queue),
+				Key::C => self.c(queue),
+				Key::D => self.d(queue),
+				Key::E => self.e(queue),
+				Key::F => self.f(queue),
+				Key::G => self.g(queue),
+				Key::H => self.h(queue),
+				Key::I => self.i(queue),
+				Key::J => self.j(queue),
+				Key::K => self.k(queue),
+				Key::L => self.l(queue),
+				Key::M => self.m(queue),
+				Key::N => self.n(queue),
+				Key::O => self.o(queue),
+				Key::P => self.p(queue),
+				Key::Q => self.q(queue),
+				Key::R => self.r(queue),
+				Key::S => self.s(queue),
+				Key::T => self.t(queue),
+				Key::U => self.u(queue),
+				Key::V => self.v(queue),
+				Key::W => self.w(queue),
+				Key::X => self.x(queue),
+				Key::Y => self.y(queue),
+				Key::Z => self.z(queue),
+				Key::Enter => self.enter(queue),
+				Key::Apostrophe => self.apostrophe(queue),
+				Key::Semicolon => self.semicolon(queue),
+				Key::EqualSign => self.equalsign(queue),
+				Key::Minus => self.minus(queue),
+				Key::Num1 => self.num1(queue),
+				Key::Num2 => self.num2(queue),
+				Key::Num3 => self.num3(queue),
+				Key::Num4 => self.num4(queue),
+				Key::Num5 => self.num5(queue),
+				Key::Num6 => self.num6(queue),
+				Key::Num7 => self.num7(queue),
+				Key::Num8 => self.num8(queue),
+				Key::Num9 => self.num9(queue),
+				Key::Num0 => self.num0(queue),
+				_ => {},
 			},
-			Event::A(state) => if state.is_some() { self.a(queue) },
-			Event::B(state) => if state.is_some() { self.b(queue) },
-			Event::C(state) => if state.is_some() { self.c(queue) },
-			Event::D(state) => if state.is_some() { self.d(queue) },
-			Event::E(state) => if state.is_some() { self.e(queue) },
-			Event::F(state) => if state.is_some() { self.f(queue) },
-			Event::G(state) => if state.is_some() { self.g(queue) },
-			Event::H(state) => if state.is_some() { self.h(queue) },
-			Event::I(state) => if state.is_some() { self.i(queue) },
-			Event::J(state) => if state.is_some() { self.j(queue) },
-			Event::K(state) => if state.is_some() { self.k(queue) },
-			Event::L(state) => if state.is_some() { self.l(queue) },
-			Event::M(state) => if state.is_some() { self.m(queue) },
-			Event::N(state) => if state.is_some() { self.n(queue) },
-			Event::O(state) => if state.is_some() { self.o(queue) },
-			Event::P(state) => if state.is_some() { self.p(queue) },
-			Event::Q(state) => if state.is_some() { self.q(queue) },
-			Event::R(state) => if state.is_some() { self.r(queue) },
-			Event::S(state) => if state.is_some() { self.s(queue) },
-			Event::T(state) => if state.is_some() { self.t(queue) },
-			Event::U(state) => if state.is_some() { self.u(queue) },
-			Event::V(state) => if state.is_some() { self.v(queue) },
-			Event::W(state) => if state.is_some() { self.w(queue) },
-			Event::X(state) => if state.is_some() { self.x(queue) },
-			Event::Y(state) => if state.is_some() { self.y(queue) },
-			Event::Z(state) => if state.is_some() { self.z(queue) },
-			Event::Enter(state) => if state.is_some() { self.enter(queue) },
-			Event::Apostrophe(state) => if state.is_some() { self.apostrophe(queue) },
-			Event::Semicolon(state) => if state.is_some() { self.semicolon(queue) },
-			Event::EqualSign(state) => if state.is_some() { self.equalsign(queue) },
-			Event::Minus(state) => if state.is_some() { self.minus(queue) },
-			Event::Num1(state) => if state.is_some() { self.num1(queue) },
-			Event::Num2(state) => if state.is_some() { self.num2(queue) },
-			Event::Num3(state) => if state.is_some() { self.num3(queue) },
-			Event::Num4(state) => if state.is_some() { self.num4(queue) },
-			Event::Num5(state) => if state.is_some() { self.num5(queue) },
-			Event::Num6(state) => if state.is_some() { self.num6(queue) },
-			Event::Num7(state) => if state.is_some() { self.num7(queue) },
-			Event::Num8(state) => if state.is_some() { self.num8(queue) },
-			Event::Num9(state) => if state.is_some() { self.num9(queue) },
-			Event::Num0(state) => if state.is_some() { self.num0(queue) },
 			_ => {},
 		}
-		queue.push(input)
+	}
+
+	fn hold(&mut self, modifier: u8, state: KeyState) {
+		if state.is_down() {
+			self.held |= modifier
+		} else {
+			self.held &= !modifier
+		}
 	}
 
 	fn a(&self, queue: &mut Vec<Event>) -> () {
@@ -0,0 +1,248 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Names of keys, buttons and shortcuts in the user's language.
+
+use std::collections::HashMap;
+use std::env;
+
+use {Binding, Key, KeyCombo};
+
+// Translations included with awi, for each language.
+const BUNDLED: [(&str, &str); 4] = [
+	("en", include_str!("en.txt")),
+	("de", include_str!("de.txt")),
+	("es", include_str!("es.txt")),
+	("fr", include_str!("fr.txt")),
+];
+
+/// Human-readable names for keys, buttons and shortcuts, in a language
+/// ("Strg+S" in German).  Names not translated to the language fall back
+/// to English, and then to the name from `Display` ("PageUp").
+#[derive(Clone, Debug)]
+pub struct Locale {
+	// The language, like "de" or "pt_BR".
+	lang: String,
+	// Translations for each language, from the name to the translation.
+	tables: HashMap<String, HashMap<String, String>>,
+}
+
+impl Locale {
+	/// Names in `lang` ("de", or "pt_BR" falling back to "pt"), with
+	/// the translations included with awi (en, de, es and fr).
+	pub fn new(lang: &str) -> Locale {
+		let mut locale = Locale {
+			lang: String::new(),
+			tables: HashMap::new(),
+		};
+
+		locale.set_lang(lang);
+		for &(lang, text) in BUNDLED.iter() {
+			locale.add(lang, text);
+		}
+
+		locale
+	}
+
+	/// Names in the system's language, from the `LC_ALL`, `LC_MESSAGES`
+	/// or `LANG` environment variable (English if none are set).
+	pub fn system() -> Locale {
+		let lang = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+			.filter_map(|var| env::var(var).ok())
+			.find(|lang| !lang.is_empty())
+			.unwrap_or_default();
+
+		Locale::new(&lang)
+	}
+
+	/// Get the language, like "de" or "pt_BR".
+	pub fn lang(&self) -> &str {
+		&self.lang
+	}
+
+	/// Switch to names in `lang`.  Accepts a locale name like
+	/// "de_DE.UTF-8" ("C" and "POSIX" are English).
+	pub fn set_lang(&mut self, lang: &str) {
+		// Remove the encoding and modifier: "de_DE.UTF-8@euro".
+		let lang = lang.split(['.', '@']).next().unwrap_or("");
+
+		self.lang = match lang {
+			"" | "C" | "POSIX" => "en".to_string(),
+			lang => lang.to_string(),
+		};
+	}
+
+	/// Add translations to `lang`, as lines of `Name = Translation`, such
+	/// as "PageUp = Bild auf".  Names are from `Display` for `Key` and
+	/// `Binding`, "Ctrl", "Shift" and "Alt" for modifiers, "CL" and "CR"
+	/// for controller bumpers/triggers, and "CMoveX"-style names for
+	/// controller axes.  Lines starting with `#` are comments.  Replaces
+	/// earlier translations of the same name, including the ones included
+	/// with awi.  Returns how many were added.
+	pub fn add(&mut self, lang: &str, text: &str) -> usize {
+		let table = self.tables.entry(lang.to_string())
+			.or_default();
+		let mut count = 0;
+
+		for line in text.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			if let Some(i) = line.find('=') {
+				let name = line[..i].trim();
+				let translation = line[i + 1..].trim();
+
+				table.insert(name.to_string(),
+					translation.to_string());
+				count += 1;
+			}
+		}
+
+		count
+	}
+
+	/// Get the translation of `name` (see `add()`), or `name` if there
+	/// isn't one.
+	pub fn get<'a>(&'a self, name: &'a str) -> &'a str {
+		// "pt_BR", then "pt", then English.
+		let base = self.lang.split('_').next().unwrap_or("");
+
+		[self.lang.as_str(), base, "en"].iter()
+			.filter_map(|lang| self.tables.get(*lang))
+			.filter_map(|table| table.get(name))
+			.next()
+			.map_or(name, |translation| translation.as_str())
+	}
+
+	/// Get the name of a key, like "Bild auf" for `Key::PageUp`.  Keys
+	/// that type a character are named after the key on a US QWERTY
+	/// keyboard: use `Screen::key_name()` for the keyboard layout's.
+	pub fn key(&self, key: Key) -> &str {
+		self.get(key.name())
+	}
+
+	/// Get the name of a key combination, like "Strg+Umschalt+S".
+	pub fn combo(&self, combo: KeyCombo) -> String {
+		self.combo_named(combo, self.key(combo.key))
+	}
+
+	/// Get the name of a binding, like "Linke Maustaste".
+	pub fn binding(&self, binding: Binding) -> String {
+		match binding {
+			Binding::Key(key) => self.key(key).to_string(),
+			Binding::CL(b) => format!("{}{}", self.get("CL"), b),
+			Binding::CR(b) => format!("{}{}", self.get("CR"), b),
+			Binding::CAxis(..) => {
+				// "CMoveX+": translate the axis, keep the sign.
+				let name = binding.to_string();
+				let axis = name.trim_end_matches(['+', '-']);
+				let sign = &name[axis.len()..];
+
+				format!("{}{}", self.get(axis), sign)
+			}
+			binding => self.get(&binding.to_string()).to_string(),
+		}
+	}
+
+	/// Name a key combination, with the key already named.
+	pub(crate) fn combo_named(&self, combo: KeyCombo, key: &str) -> String {
+		let mut name = String::new();
+
+		if combo.ctrl {
+			name.push_str(self.get("Ctrl"));
+			name.push('+');
+		}
+		if combo.shift {
+			name.push_str(self.get("Shift"));
+			name.push('+');
+		}
+		if combo.alt {
+			name.push_str(self.get("Alt"));
+			name.push('+');
+		}
+		name.push_str(key);
+
+		name
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use Axis;
+
+	#[test]
+	fn set_lang() {
+		for &(name, lang) in &[("de_DE.UTF-8", "de_DE"),
+			("de_DE.UTF-8@euro", "de_DE"), ("fr@euro", "fr"),
+			("pt_BR", "pt_BR"), ("", "en"), ("C", "en"),
+			("POSIX", "en"), ("C.UTF-8", "en")]
+		{
+			assert_eq!(Locale::new(name).lang(), lang, "{:?}", name);
+		}
+	}
+
+	#[test]
+	fn add() {
+		let mut locale = Locale::new("xx");
+		let text = "# Comment\n\
+			\n\
+			  PageUp =  Page Up Key  \n\
+			No equals sign\n\
+			Equals = a = b\n\
+			Empty =\n";
+
+		assert_eq!(locale.add("xx", text), 3);
+		assert_eq!(locale.get("PageUp"), "Page Up Key");
+		assert_eq!(locale.get("Equals"), "a = b");
+		assert_eq!(locale.get("Empty"), "");
+		assert_eq!(locale.get("# Comment"), "# Comment");
+		assert_eq!(locale.get("No equals sign"), "No equals sign");
+	}
+
+	#[test]
+	fn fallback() {
+		let mut locale = Locale::new("de_AT.UTF-8");
+
+		locale.add("de_AT", "PageDown = Bild runter");
+
+		// Region, then language, then English, then the name.
+		assert_eq!(locale.get("PageDown"), "Bild runter");
+		assert_eq!(locale.get("PageUp"), "Bild auf");
+		assert_eq!(locale.get("CL"), "L");
+		assert_eq!(locale.get("Missing"), "Missing");
+
+		// Added translations replace the bundled ones.
+		locale.add("de", "PageUp = Seite hoch");
+		assert_eq!(locale.get("PageUp"), "Seite hoch");
+	}
+
+	#[test]
+	fn names() {
+		let de = Locale::new("de");
+		let ctrl_s = KeyCombo {
+			ctrl: true, shift: true, alt: false, key: Key::S,
+		};
+
+		assert_eq!(de.key(Key::PageUp), "Bild auf");
+		assert_eq!(de.key(Key::S), "S");
+		assert_eq!(de.combo(ctrl_s), "Strg+Umschalt+S");
+
+		for &(binding, name) in &[
+			(Binding::Key(Key::Home), "Pos1"),
+			(Binding::LeftButton, "Linke Maustaste"),
+			(Binding::CAccept, "Bestätigen"),
+			(Binding::CL(1), "L1"),
+			(Binding::CR(0), "R0"),
+			(Binding::CAxis(Axis::MoveX, None), "Bewegungsstick X"),
+			(Binding::CAxis(Axis::MoveY, Some(true)),
+				"Bewegungsstick Y+"),
+			(Binding::CAxis(Axis::ThrottleL, Some(false)),
+				"Linker Trigger-"),
+		] {
+			assert_eq!(de.binding(binding), name);
+		}
+	}
+}
@@ -3,17 +3,41 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
+pub(crate) mod action;
+pub(crate) mod controller;
+pub(crate) mod gamepad;
+pub(crate) mod gesture;
 pub(crate) mod keyboard;
+pub(crate) mod locale;
+pub(crate) mod mouse;
+pub(crate) mod pointer;
+pub(crate) mod record;
+
+use std::collections::VecDeque;
+
+use self::keyboard::{Key, KeyState};
 
 /// Window Input Event, put on queue when an event has occurred.
 #[derive(PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Event {
 	/// Timestep event.
 	Timestep,
 	/// Exit event (Back key / Esc / 'X' button on app's window / Ctrl-Q).
 	Exit,
-	/// The window has just been resized.
-	Resize,
+	/// The window has just been resized (width, height in pixels).
+	Resize(u16, u16),
+	/// The window has just been moved (x, y of the top-left corner, in
+	/// pixels from the top-left of the screen).
+	Moved(i32, i32),
+	/// The window has been minimized (iconified).
+	Minimized,
+	/// The window is covered by other windows, so nothing drawn is seen.
+	Hidden,
+	/// The window is visible again, after `Minimized` or `Hidden`.
+	Shown,
+	/// Part of the window was damaged, and needs to be redrawn.
+	Redraw,
 	/// The user has switched to this window (in focus).
 	Resume,
 	/// The user has switched to a different window (out of focus).
@@ -63,7 +87,8 @@ pub enum Event {
 	/// Keyboard Shortcut - Close (Ctrl-W)
 	Close,
 	/// Keyboard Shortcut - Open (Ctrl-O)
-	Open(Option<&'static str>),
+	Open(#[cfg_attr(feature = "serde", serde(skip))]
+		Option<&'static str>),
 	/// Keyboard Shortcut - Share (Ctrl-S)
 	Share,
 	/// Keyboard Shortcut - Save Copy (Ctrl-Shift-S)
@@ -78,7 +103,9 @@ pub enum Event {
 	Paste,
 	/// Keyboard Shortcut - Print (Ctrl-P)
 	Print,
-	/// Cursor moved
+	/// Cursor moved.  While a mouse button is held, the pointer is captured:
+	/// the cursor (and button releases) can be outside of -1 to 1, instead
+	/// of `None`.
 	Cursor(Option<(f32,f32)>),
 	/// Left Click (Some(Just Clicked) = Pressed, Cursor XY)
 	LeftButton(Option<bool>, Option<(f32, f32)>),
@@ -86,160 +113,55 @@ pub enum Event {
 	MiddleButton(Option<bool>, Option<(f32, f32)>),
 	/// Right Click (or CTRL-Click) (Some(Just Clicked) = Pressed, Cursor XY)
 	RightButton(Option<bool>, Option<(f32, f32)>),
-	/// Touch (on a touchscreen) (Some(Just Clicked) = Pressed, Cursor XY)
-	Touch(Option<bool>, Option<(f32, f32)>),
+	/// Touch (on a touchscreen) (Finger, Some(Just Touched) = Touching,
+	/// Touch XY).  `Some(false)` is sent when a finger moves.  Fingers are
+	/// numbered from 0, and a lifted finger's number is reused.
+	Touch(usize, Option<bool>, Option<(f32, f32)>),
 	/// Touchpad / Mousewheel scroll (x, y) - (-1, -1) is up / left, (1, 1)
 	/// is down / right (Scroll XY, Cursor XY)
 	Scroll((f32, f32), Option<(f32, f32)>),
-	// Note: These rows are not necessarily the rows these keys are found.
-	// Row1
-	/// 1
-	Num1(Option<bool>),
-	/// 2
-	Num2(Option<bool>),
-	/// 3
-	Num3(Option<bool>),
-	/// 4
-	Num4(Option<bool>),
-	/// 5
-	Num5(Option<bool>),
-	/// 6
-	Num6(Option<bool>),
-	/// 7
-	Num7(Option<bool>),
-	/// 8
-	Num8(Option<bool>),
-	/// 9
-	Num9(Option<bool>),
-	/// 0
-	Num0(Option<bool>),
-	/// \-
-	Minus(Option<bool>),
-	/// \=
-	EqualSign(Option<bool>),
-	/// Backspace
-	Backspace(Option<bool>),
-	// Row2
-	/// Tab
-	Tab(Option<bool>),
-	/// Q
-	Q(Option<bool>), // = 14,
-	/// W
-	W(Option<bool>), // = 15,
-	/// E
-	E(Option<bool>), // = 16,
-	/// R
-	R(Option<bool>), // = 17,
-	/// T
-	T(Option<bool>), // = 18,
-	/// Y
-	Y(Option<bool>), // = 19,
-	/// U
-	U(Option<bool>), // = 20,
-	/// I
-	I(Option<bool>), // = 21,
-	/// O
-	O(Option<bool>), // = 22,
-	/// P
-	P(Option<bool>), // = 23,
-	/// {
-	BracketOpen(Option<bool>), // = 24,
-	/// }
-	BracketClose(Option<bool>), // = 25,
-	/// Backslash
-	Backslash(Option<bool>), // = 26,
-	// Row3
-	/// Compose (CAPS LOCK)
-	Compose(Option<bool>), // = 27,
-	/// A
-	A(Option<bool>), // = 28,
-	/// S
-	S(Option<bool>), // = 29,
-	/// D
-	D(Option<bool>), // = 30,
-	/// F
-	F(Option<bool>), // = 31,
-	/// G
-	G(Option<bool>), // = 32,
-	/// H
-	H(Option<bool>), // = 33,
-	/// J
-	J(Option<bool>), // = 34,
-	/// K
-	K(Option<bool>), // = 35,
-	/// L
-	L(Option<bool>), // = 36,
-	/// ;
-	Semicolon(Option<bool>), // = 37,
-	/// '
-	Apostrophe(Option<bool>), // = 38,
-	/// Enter
-	Enter(Option<bool>), // = 39,
-	// Row4
-	/// Left Shift
-	LShift(Option<bool>), // = 40,
-	/// Z
-	Z(Option<bool>), // = 41,
-	/// X
-	X(Option<bool>), // = 42,
-	/// C
-	C(Option<bool>), // = 43,
-	/// V
-	V(Option<bool>), // = 44,
-	/// B
-	B(Option<bool>), // = 45,
-	/// N
-	N(Option<bool>), // = 46,
-	/// M
-	M(Option<bool>), // = 47,
-	/// ,
-	Comma(Option<bool>), // = 48,
-	/// .
-	Period(Option<bool>), // = 49,
-	/// /
-	Slash(Option<bool>), // = 50,
-	/// Right Shift
-	RShift(Option<bool>), // = 51,
-	// Row5
-	/// Left CTRL
-	LCtrl(Option<bool>), // = 52,
-	/// Alt (Left)
-	Alt(Option<bool>), // = 53,
-	/// Space
-	Space(Option<bool>), // = 54,
-	/// Right Control
-	RCtrl(Option<bool>), // = 55,
-	/// Up Arrow Key
-	Up(Option<bool>), // = 56,
-	/// Down Arrow Key
-	Down(Option<bool>), // = 57,
-	/// Left Arrow Key
-	Left(Option<bool>), // = 58,
-	/// Right Arrow Key
-	Right(Option<bool>), // = 59,
-	// Ext ( May require 2 keys to be pressed on some platforms )
-	/// `
-	ExtBacktick(Option<bool>), // = 64,
-	/// Delete
-	ExtDelete(Option<bool>), // = 65,
-	/// Insert
-	ExtInsert(Option<bool>), // = 66,
-	/// NumLock
-	ExtNumLock(Option<bool>), // = 67,
-	/// Page Up
-	ExtPageUp(Option<bool>), // = 68,
-	/// Page Down
-	ExtPageDown(Option<bool>), // = 69,
-	/// Home
-	ExtHome(Option<bool>), // = 70,
-	/// End
-	ExtEnd(Option<bool>), // = 71,
-	/// \*
-	ExtAsterisk(Option<bool>), // = 72,
-	/// \+
-	ExtPlus(Option<bool>), // = 73,
-	/// AltGr (Right Alt)
-	ExtAltGr(Option<bool>), // = 74
+	/// Gesture: a quick touch and release (Touch XY).
+	Tap((f32, f32)),
+	/// Gesture: a second tap in the same place, right after a `Tap` (Touch
+	/// XY).  Sent instead of the second `Tap`.
+	DoubleTap((f32, f32)),
+	/// Gesture: a touch held without moving (Touch XY).
+	LongPress((f32, f32)),
+	/// Gesture: fingers dragged, on a touchscreen or touchpad (Centroid XY,
+	/// Movement XY).
+	Pan((f32, f32), (f32, f32)),
+	/// Gesture: fingers pinched together or spread apart, on a touchscreen
+	/// or touchpad (Centroid XY, Scale - more than 1 is spread apart / zoom
+	/// in).  The scale is since the last `Pinch`.
+	Pinch((f32, f32), f32),
+	/// Gesture: fingers twisted, on a touchscreen or touchpad (Centroid XY,
+	/// Radians clockwise).  The angle is since the last `Rotate`.
+	Rotate((f32, f32), f32),
+	/// Left button pressed twice, quickly and in the same place (Cursor
+	/// XY).  Sent after the second `LeftButton` press.
+	DoubleClick((f32, f32)),
+	/// Left button pressed three times, quickly and in the same place
+	/// (Cursor XY).  Sent after the third `LeftButton` press.
+	TripleClick((f32, f32)),
+	/// The cursor moved farther than the drag threshold with the left
+	/// button held (Cursor XY where the button was pressed).
+	DragStart((f32, f32)),
+	/// The cursor moved while dragging (Cursor XY, Movement XY).
+	DragMove((f32, f32), (f32, f32)),
+	/// The left button was released (or the window lost focus) while
+	/// dragging (Cursor XY).
+	DragEnd((f32, f32)),
+	/// The cursor entered the window (Cursor XY).
+	HoverEnter((f32, f32)),
+	/// The cursor left the window.
+	HoverLeave,
+	/// A key was pressed, held, released or auto-repeated.
+	Key {
+		/// Which key.
+		key: Key,
+		/// What happened to it.
+		state: KeyState,
+	},
 	/// Controller: Main joystick movement.
 	CMove(usize, f32, f32),
 	/// Controller: Camera / C joystick movement.
@@ -286,21 +208,51 @@ pub enum Event {
 	CPluggedIn(usize, i32),
 	/// Controller: Device Un-Plugged
 	CUnPlugged(usize, i32),
+	/// Controller: Gyroscope (x, y, z), in radians per second.
+	CGyro(usize, f32, f32, f32),
+	/// Controller: Accelerometer (x, y, z), in meters per second squared
+	/// (including gravity).
+	CAccel(usize, f32, f32, f32),
+	/// Value posted from another thread with `Waker::post()`.
+	User(u64),
 }
 
 use self::Event::*;
 
 impl ::std::fmt::Display for Event {
 	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result<> {
-		// TODO: Write in language of the user.
-		match self {
+		// In English: `Locale` names keys and buttons in the user's
+		// language.
+		match *self {
 			Timestep => write!(f, "Timestep"),
 			Exit => write!(f, "Exit"),
-			Resize => write!(f, "Resize"),
+			Resize(w, h) => write!(f, "Resize {}x{}", w, h),
+			Moved(x, y) => write!(f, "Moved ({}, {})", x, y),
+			Minimized => write!(f, "Minimized"),
+			Hidden => write!(f, "Hidden"),
+			Shown => write!(f, "Shown"),
+			Redraw => write!(f, "Redraw"),
 			Resume => write!(f, "Resume"),
 			Pause => write!(f, "Pause"),
-			Text(chr) => write!(f, "Text {}", chr),
-			Select => write!(f, "Select"),
+			Text(chr) => write!(f, "Text {:?}", chr),
+			AlignLeft => write!(f, "Align Left"),
+			AlignCenter => write!(f, "Align Center"),
+			AlignRight => write!(f, "Align Right"),
+			AlignJustified => write!(f, "Align Justified"),
+			EmphasisBrokenUnderline => {
+				write!(f, "Emphasis Broken Underline")
+			}
+			EmphasisOverline => write!(f, "Emphasis Overline"),
+			EmphasisBold => write!(f, "Emphasis Bold"),
+			EmphasisInvertColor => write!(f, "Emphasis Invert Color"),
+			EmphasisNone => write!(f, "Emphasis None"),
+			EmphasisStrikeOut => write!(f, "Emphasis Strike Out"),
+			EmphasisDoubleUnderline => {
+				write!(f, "Emphasis Double Underline")
+			}
+			EmphasisUnderline => write!(f, "Emphasis Underline"),
+			EmphasisItalic => write!(f, "Emphasis Italic"),
+			Select => write!(f, "Select All"),
 			Copy => write!(f, "Copy"),
 			Cancel => write!(f, "Cancel"),
 			Delete => write!(f, "Delete"),
@@ -308,7 +260,8 @@ impl ::std::fmt::Display for Event {
 			Help => write!(f, "Help"),
 			Info => write!(f, "Info"),
 			Close => write!(f, "Close"),
-			Open(_) => write!(f, "Open..."),
+			Open(Some(file)) => write!(f, "Open {}", file),
+			Open(None) => write!(f, "Open..."),
 			Share => write!(f, "Share..."),
 			SaveCopy => write!(f, "Save A Copy..."),
 			Undo => write!(f, "Undo"),
@@ -316,105 +269,107 @@ impl ::std::fmt::Display for Event {
 			Cut => write!(f, "Cut"),
 			Paste => write!(f, "Paste"),
 			Print => write!(f, "Print"),
-			Cursor(xy) => write!(f, "Cursor {:?}", xy),
-			LeftButton(state, xy) => write!(f, "Left Click {:?} {:?}", state, xy),
-			MiddleButton(state, xy) => write!(f, "Middle Click {:?} {:?}", state, xy),
-			RightButton(state, xy) => write!(f, "Right Click {:?} {:?}", state, xy),
-			Touch(state, xy) => write!(f, "Touch {:?} {:?}", state, xy),
-			Scroll(sxy, xy) => write!(f, "Scroll {:?} {:?}",sxy,xy),
-			Num1(state) => write!(f, "1 {:?}", state),
-			Num2(state) => write!(f, "2 {:?}", state),
-			Num3(state) => write!(f, "3 {:?}", state),
-			Num4(state) => write!(f, "4 {:?}", state),
-			Num5(state) => write!(f, "5 {:?}", state),
-			Num6(state) => write!(f, "6 {:?}", state),
-			Num7(state) => write!(f, "7 {:?}", state),
-			Num8(state) => write!(f, "8 {:?}", state),
-			Num9(state) => write!(f, "9 {:?}", state),
-			Num0(state) => write!(f, "0 {:?}", state),
-			Minus(state) => write!(f, "- {:?}", state),
-			EqualSign(state) => write!(f, "= {:?}", state),
-			Backspace(state) => write!(f, "Backspace {:?}", state),
-			Tab(state) => write!(f, "tab {:?}", state),
-			Q(state) => write!(f, "Q {:?}", state),
-			W(state) => write!(f, "W {:?}", state),
-			E(state) => write!(f, "E {:?}", state),
-			R(state) => write!(f, "R {:?}", state),
-			T(state) => write!(f, "T {:?}", state),
-			Y(state) => write!(f, "Y {:?}", state),
-			U(state) => write!(f, "U {:?}", state),
-			I(state) => write!(f, "I {:?}", state),
-			O(state) => write!(f, "O {:?}", state),
-			P(state) => write!(f, "P {:?}", state),
-			BracketOpen(state) => write!(f, "[ {:?}", state),
-			BracketClose(state) => write!(f, "] {:?}", state),
-			Backslash(state) => write!(f, "\\ {:?}", state),
-			Compose(state) => write!(f, "Compose {:?}", state),
-			A(state) => write!(f, "A {:?}", state),
-			S(state) => write!(f, "S {:?}", state),
-			D(state) => write!(f, "D {:?}", state),
-			F(state) => write!(f, "F {:?}", state),
-			G(state) => write!(f, "G {:?}", state),
-			H(state) => write!(f, "H {:?}", state),
-			J(state) => write!(f, "J {:?}", state),
-			K(state) => write!(f, "K {:?}", state),
-			L(state) => write!(f, "L {:?}", state),
-			Semicolon(state) => write!(f, "; {:?}", state),
-			Apostrophe(state) => write!(f, "' {:?}", state),
-			Enter(state) => write!(f, "enter {:?}", state),
-			LShift(state) => write!(f, "Left Shift {:?}", state),
-			RShift(state) => write!(f, "Right Shift {:?}", state),
-			Z(state) => write!(f, "Z {:?}", state),
-			X(state) => write!(f, "X {:?}", state),
-			C(state) => write!(f, "C {:?}", state),
-			V(state) => write!(f, "V {:?}", state),
-			B(state) => write!(f, "B {:?}", state),
-			N(state) => write!(f, "N {:?}", state),
-			M(state) => write!(f, "M {:?}", state),
-			Comma(state) => write!(f, ", {:?}", state),
-			Period(state) => write!(f, ". {:?}", state),
-			Slash(state) => write!(f, "/ {:?}", state),
-			LCtrl(state) => write!(f, "Left Ctrl {:?}", state),
-			RCtrl(state) => write!(f, "Right Ctrl {:?}", state),
-			Alt(state) => write!(f, "Alt {:?}", state),
-			ExtAltGr(state) => write!(f, "AltGr {:?}", state),
-			Space(state) => write!(f, "space {:?}", state),
-			Up(state) => write!(f, "Up {:?}", state),
-			Down(state) => write!(f, "Down {:?}", state),
-			Left(state) => write!(f, "Left {:?}", state),
-			Right(state) => write!(f, "Right {:?}", state),
-			ExtBacktick(state) => write!(f, "` {:?}", state),
-			ExtDelete(state) => write!(f, "Delete {:?}", state),
-			ExtInsert(state) => write!(f, "Insert {:?}", state),
-			ExtNumLock(state) => write!(f, "NumLock {:?}", state),
-			ExtPageUp(state) => write!(f, "PageUp {:?}", state),
-			ExtPageDown(state) => write!(f, "PageDown {:?}", state),
-			ExtHome(state) => write!(f, "Home {:?}", state),
-			ExtEnd(state) => write!(f, "End {:?}", state),
-			ExtAsterisk(state) => write!(f, "* {:?}", state),
-			ExtPlus(state) => write!(f, "+ {:?}", state),
+			Cursor(c) => write!(f, "Cursor {}", Xy(c)),
+			LeftButton(s, c) => {
+				write!(f, "Left Click {} {}", State(s), Xy(c))
+			}
+			MiddleButton(s, c) => {
+				write!(f, "Middle Click {} {}", State(s), Xy(c))
+			}
+			RightButton(s, c) => {
+				write!(f, "Right Click {} {}", State(s), Xy(c))
+			}
+			Touch(i, s, c) => {
+				write!(f, "Touch {} {} {}", i, State(s), Xy(c))
+			}
+			Scroll(s, c) => {
+				write!(f, "Scroll {} {}", Xy(Some(s)), Xy(c))
+			}
+			Tap(c) => write!(f, "Tap {}", Xy(Some(c))),
+			DoubleTap(c) => write!(f, "DoubleTap {}", Xy(Some(c))),
+			LongPress(c) => write!(f, "LongPress {}", Xy(Some(c))),
+			Pan(c, m) => {
+				write!(f, "Pan {} {}", Xy(Some(c)), Xy(Some(m)))
+			}
+			Pinch(c, s) => write!(f, "Pinch {} {}", Xy(Some(c)), s),
+			Rotate(c, a) => write!(f, "Rotate {} {}", Xy(Some(c)), a),
+			DoubleClick(c) => write!(f, "DoubleClick {}", Xy(Some(c))),
+			TripleClick(c) => write!(f, "TripleClick {}", Xy(Some(c))),
+			DragStart(c) => write!(f, "DragStart {}", Xy(Some(c))),
+			DragMove(c, m) => {
+				write!(f, "DragMove {} {}", Xy(Some(c)),
+					Xy(Some(m)))
+			}
+			DragEnd(c) => write!(f, "DragEnd {}", Xy(Some(c))),
+			HoverEnter(c) => write!(f, "HoverEnter {}", Xy(Some(c))),
+			HoverLeave => write!(f, "HoverLeave"),
+			Event::Key { key, state } => {
+				write!(f, "{} {:?}", key, state)
+			}
 			CMove(i, x, y) => write!(f, "C{} Move ({}, {})", i, x, y),
-			CCamera(i, x, y) => write!(f, "C{} Camera ({}, {})", i, x, y),
+			CCamera(i, x, y) => {
+				write!(f, "C{} Camera ({}, {})", i, x, y)
+			}
 			CThrottleL(i, x) => write!(f, "C{} ThrottleL ({})", i, x),
 			CThrottleR(i, x) => write!(f, "C{} ThrottleR ({})", i, x),
-			CAccept(i, s) => write!(f, "C{} Accept {:?}", i, s),
-			CCancel(i, s) => write!(f, "C{} Cancel {:?}", i, s),
-			CExecute(i, s) => write!(f, "C{} Execute {:?}", i, s),
-			CAction(i, s) => write!(f, "C{} Action {:?}", i, s),
-			CL(i, a, s) => write!(f, "C{} L-{} {:?}", i, a, s),
-			CR(i, a, s) => write!(f, "C{} R-{} {:?}", i, a, s),
-			CMenu(i, s) => write!(f, "C{} Menu {:?}", i, s),
+			CAccept(i, s) => write!(f, "C{} Accept {}", i, State(s)),
+			CCancel(i, s) => write!(f, "C{} Cancel {}", i, State(s)),
+			CExecute(i, s) => write!(f, "C{} Execute {}", i, State(s)),
+			CAction(i, s) => write!(f, "C{} Action {}", i, State(s)),
+			CL(i, a, s) => write!(f, "C{} L-{} {}", i, a, State(s)),
+			CR(i, a, s) => write!(f, "C{} R-{} {}", i, a, State(s)),
+			CMenu(i, s) => write!(f, "C{} Menu {}", i, State(s)),
 			CControls(i) => write!(f, "C{} Controls", i),
 			CExit(i) => write!(f, "C{} Exit", i),
-			CUp(i, s) => write!(f, "C{} Up {:?}", i, s),
-			CDown(i, s) => write!(f, "C{} Down {:?}", i, s),
-			CLeft(i, s) => write!(f, "C{} Left {:?}", i, s),
-			CRight(i, s) => write!(f, "C{} Right {:?}", i, s),
-			CMoveStick(i, s) => write!(f, "C{} Movement Stick Push {:?}", i, s),
-			CCamStick(i, s) => write!(f, "C{} Camera Stick Push {:?}", i, s),
-			CPluggedIn(i, x) => write!(f, "C{} Device Plugged-In {:x}", i, x),
-			CUnPlugged(i, x) =>  write!(f, "C{} Device Un-Plugged {:x}", i, x),
-			_ => write!(f, "FIXME: Unknown") // FIXME
+			CUp(i, s) => write!(f, "C{} Up {}", i, State(s)),
+			CDown(i, s) => write!(f, "C{} Down {}", i, State(s)),
+			CLeft(i, s) => write!(f, "C{} Left {}", i, State(s)),
+			CRight(i, s) => write!(f, "C{} Right {}", i, State(s)),
+			CMoveStick(i, s) => {
+				write!(f, "C{} Movement Stick Push {}", i, State(s))
+			}
+			CCamStick(i, s) => {
+				write!(f, "C{} Camera Stick Push {}", i, State(s))
+			}
+			CPluggedIn(i, x) => {
+				write!(f, "C{} Device Plugged-In {:x}", i, x)
+			}
+			CUnPlugged(i, x) => {
+				write!(f, "C{} Device Un-Plugged {:x}", i, x)
+			}
+			CGyro(i, x, y, z) => {
+				write!(f, "C{} Gyro ({}, {}, {})", i, x, y, z)
+			}
+			CAccel(i, x, y, z) => {
+				write!(f, "C{} Accel ({}, {}, {})", i, x, y, z)
+			}
+			User(x) => write!(f, "User {}", x),
+		}
+	}
+}
+
+// A button state (`Some(true)` just pressed, `Some(false)` held, `None`
+// released), for `Display`.
+struct State(Option<bool>);
+
+impl ::std::fmt::Display for State {
+	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result<> {
+		f.write_str(match self.0 {
+			Some(true) => "Pressed",
+			Some(false) => "Held",
+			None => "Released",
+		})
+	}
+}
+
+// Coordinates, or `None` (outside of the window), for `Display`.
+struct Xy(Option<(f32, f32)>);
+
+impl ::std::fmt::Display for Xy {
+	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result<> {
+		match self.0 {
+			Some((x, y)) => write!(f, "({}, {})", x, y),
+			None => write!(f, "(Outside)"),
 		}
 	}
 }
@@ -447,19 +402,69 @@ fn cursor_coordinates<T, U>(wh: (T, T), xy: (U, U)) -> Option<(f32, f32)>
 	}
 }
 
+/// What the input queue does with a new event when it's full.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Overflow {
+	/// Throw away the oldest event in the queue to make room.
+	DropOldest,
+	/// Throw away the new event.
+	DropNewest,
+}
+
 pub struct InputQueue {
-	queue: Vec<Event>,
+	queue: VecDeque<Event>,
+	// Events generated by the modifiers, before going on the queue.
+	pending: Vec<Event>,
 	mods: keyboard::modifiers::Modifiers,
+	// Maximum number of events on the queue, `None` for no limit.
+	capacity: Option<usize>,
+	overflow: Overflow,
+	// Whether to merge consecutive events (see `set_coalesce()`).
+	coalesce: bool,
+	// Whether the left, middle and right mouse buttons are held,
+	// capturing the pointer.
+	held: [bool; 3],
 }
 
 impl InputQueue {
 	/// Get an empty InputQueue.
 	#[inline(always)]
 	pub fn new() -> InputQueue {
-		let queue = Vec::new();
+		let queue = VecDeque::new();
+		let pending = Vec::new();
 		let mods = keyboard::modifiers::Modifiers::create();
+		let capacity = None;
+		let overflow = Overflow::DropOldest;
+		let coalesce = false;
+
+		InputQueue {
+			queue, pending, mods, capacity, overflow, coalesce,
+			held: [false; 3],
+		}
+	}
 
-		InputQueue { queue, mods }
+	/// Limit the number of events on the queue, `None` for no limit.
+	pub fn set_capacity(&mut self, capacity: Option<usize>,
+		overflow: Overflow)
+	{
+		self.capacity = capacity;
+		self.overflow = overflow;
+
+		// Shrink the queue if it's already over the new limit.
+		if let Some(capacity) = capacity {
+			while self.queue.len() > capacity {
+				match overflow {
+					Overflow::DropOldest => self.queue.pop_front(),
+					Overflow::DropNewest => self.queue.pop_back(),
+				};
+			}
+		}
+	}
+
+	/// Set whether consecutive `Cursor`, `Scroll`, `Touch` move and gesture
+	/// events are merged.
+	pub fn set_coalesce(&mut self, coalesce: bool) {
+		self.coalesce = coalesce;
 	}
 
 	#[inline(always)]
@@ -469,12 +474,13 @@ impl InputQueue {
 
 	#[inline(always)]
 	pub fn is_empty(&self) -> bool {
-		self.queue.len() == 0
+		self.queue.is_empty()
 	}
 
+	/// Get the oldest event on the queue (first in, first out).
 	#[inline(always)]
 	pub fn pop(&mut self) -> Option<Event> {
-		self.queue.pop()
+		self.queue.pop_front()
 	}
 
 	#[inline(always)]
@@ -487,85 +493,22 @@ impl InputQueue {
 		// Only if new dimensions differ from old.
 		if *wh != d {
 			*wh = d;
-			self.input(Event::Resize);
+			self.input(Event::Resize(d.0, d.1));
 		}
 	}
 
-	pub fn key(&mut self, key: u8, state: Option<bool>) {
-		self.input(match key {
-			keyboard::NUM1 => Event::Num1(state),
-			keyboard::NUM2 => Event::Num2(state),
-			keyboard::NUM3 => Event::Num3(state),
-			keyboard::NUM4 => Event::Num4(state),
-			keyboard::NUM5 => Event::Num5(state),
-			keyboard::NUM6 => Event::Num6(state),
-			keyboard::NUM7 => Event::Num7(state),
-			keyboard::NUM8 => Event::Num8(state),
-			keyboard::NUM9 => Event::Num9(state),
-			keyboard::NUM0 => Event::Num0(state),
-			keyboard::MINUS => Event::Minus(state),
-			keyboard::EQUAL_SIGN => Event::EqualSign(state),
-			keyboard::BACKSPACE => Event::Backspace(state),
-			keyboard::TAB => Event::Tab(state),
-			keyboard::Q => Event::Q(state),
-			keyboard::W => Event::W(state),
-			keyboard::E => Event::E(state),
-			keyboard::R => Event::R(state),
-			keyboard::T => Event::T(state),
-			keyboard::Y => Event::Y(state),
-			keyboard::U => Event::U(state),
-			keyboard::I => Event::I(state),
-			keyboard::O => Event::O(state),
-			keyboard::P => Event::P(state),
-			keyboard::BRACKET_OPEN => Event::BracketOpen(state),
-			keyboard::BRACKET_CLOSE => Event::BracketClose(state),
-			keyboard::BACKSLASH => Event::Backslash(state),
-			keyboard::COMPOSE => Event::Compose(state),
-			keyboard::A => Event::A(state),
-			keyboard::S => Event::S(state),
-			keyboard::D => Event::D(state),
-			keyboard::F => Event::F(state),
-			keyboard::G => Event::G(state),
-			keyboard::H => Event::H(state),
-			keyboard::J => Event::J(state),
-			keyboard::K => Event::K(state),
-			keyboard::L => Event::L(state),
-			keyboard::SEMICOLON => Event::Semicolon(state),
-			keyboard::APOSTROPHE => Event::Apostrophe(state),
-			keyboard::ENTER => Event::Enter(state),
-			keyboard::LSHIFT => Event::LShift(state),
-			keyboard::Z => Event::Z(state),
-			keyboard::X => Event::X(state),
-			keyboard::C => Event::C(state),
-			keyboard::V => Event::V(state),
-			keyboard::B => Event::B(state),
-			keyboard::N => Event::N(state),
-			keyboard::M => Event::M(state),
-			keyboard::COMMA => Event::Comma(state),
-			keyboard::PERIOD => Event::Period(state),
-			keyboard::SLASH => Event::Slash(state),
-			keyboard::RSHIFT => Event::RShift(state),
-			keyboard::LCTRL => Event::LCtrl(state),
-			keyboard::ALT => Event::Alt(state),
-			keyboard::SPACE => Event::Space(state),
-			keyboard::RCTRL => Event::RCtrl(state),
-			keyboard::UP => Event::Up(state),
-			keyboard::DOWN => Event::Down(state),
-			keyboard::LEFT => Event::Left(state),
-			keyboard::RIGHT => Event::Right(state),
-			keyboard::EXT_BACKTICK => Event::ExtBacktick(state),
-			keyboard::EXT_DELETE => Event::ExtDelete(state),
-			keyboard::EXT_INSERT => Event::ExtInsert(state),
-			keyboard::EXT_NUM_LOCK => Event::ExtNumLock(state),
-			keyboard::EXT_PAGE_UP => Event::ExtPageUp(state),
-			keyboard::EXT_PAGE_DOWN => Event::ExtPageDown(state),
-			keyboard::EXT_HOME => Event::ExtHome(state),
-			keyboard::EXT_END => Event::ExtEnd(state),
-			keyboard::EXT_ASTERISK => Event::ExtAsterisk(state),
-			keyboard::EXT_PLUS => Event::ExtPlus(state),
-			keyboard::EXT_ALT_GR => Event::ExtAltGr(state),
-			_ => return,
-		})
+	#[inline(always)]
+	pub fn moved(&mut self, xy: &mut (i32, i32), d: (i32, i32)) {
+		// Only if new position differs from old.
+		if *xy != d {
+			*xy = d;
+			self.input(Event::Moved(d.0, d.1));
+		}
+	}
+
+	#[inline(always)]
+	pub fn key(&mut self, key: Key, state: KeyState) {
+		self.input(Event::Key { key, state })
 	}
 
 	#[inline(always)]
@@ -579,74 +522,189 @@ impl InputQueue {
 
 	#[inline(always)]
 	pub fn left_button_release(&mut self, wh: (u16, u16), c: (i16, i16)) {
-		let xy = cursor_coordinates(wh, c);
+		let xy = self.pointer_coordinates(wh, c);
 
 		self.input(Event::LeftButton(None, xy));
+		self.release(0, xy);
 	}
 
 	#[inline(always)]
 	pub fn middle_button_release(&mut self, wh: (u16, u16), c: (i16, i16)) {
-		let xy = cursor_coordinates(wh, c);
+		let xy = self.pointer_coordinates(wh, c);
 
 		self.input(Event::MiddleButton(None, xy));
+		self.release(1, xy);
 	}
 
 	#[inline(always)]
 	pub fn right_button_release(&mut self, wh: (u16, u16), c: (i16, i16)) {
-		let xy = cursor_coordinates(wh, c);
+		let xy = self.pointer_coordinates(wh, c);
 
 		self.input(Event::RightButton(None, xy));
+		self.release(2, xy);
 	}
 
 	#[inline(always)]
-	pub fn touch_release(&mut self, wh: (u16, u16), c: (i16, i16)) {
+	pub fn touch_release(&mut self, finger: usize, wh: (u16, u16),
+		c: (i16, i16))
+	{
 		let xy = cursor_coordinates(wh, c);
 
-		self.input(Event::Touch(None, xy));
+		self.input(Event::Touch(finger, None, xy));
 	}
 
 	#[inline(always)]
 	pub fn left_button_press(&mut self, wh: (u16, u16), c: (i16, i16)) {
-		let xy = cursor_coordinates(wh, c);
+		let xy = self.pointer_coordinates(wh, c);
 
+		self.held[0] = true;
 		self.input(Event::LeftButton(Some(true), xy));
 	}
 
 	#[inline(always)]
 	pub fn middle_button_press(&mut self, wh: (u16, u16), c: (i16, i16)) {
-		let xy = cursor_coordinates(wh, c);
+		let xy = self.pointer_coordinates(wh, c);
 
+		self.held[1] = true;
 		self.input(Event::MiddleButton(Some(true), xy));
 	}
 
 	#[inline(always)]
 	pub fn right_button_press(&mut self, wh: (u16, u16), c: (i16, i16)) {
-		let xy = cursor_coordinates(wh, c);
+		let xy = self.pointer_coordinates(wh, c);
 
+		self.held[2] = true;
 		self.input(Event::RightButton(Some(true), xy));
 	}
 
 	#[inline(always)]
-	pub fn touch_press(&mut self, wh: (u16, u16), c: (i16, i16)) {
+	pub fn touch_press(&mut self, finger: usize, wh: (u16, u16),
+		c: (i16, i16))
+	{
 		let xy = cursor_coordinates(wh, c);
 
-		self.input(Event::Touch(Some(true), xy));
+		self.input(Event::Touch(finger, Some(true), xy));
 	}
 
 	#[inline(always)]
-	pub fn cursor_move(&mut self, wh: (u16, u16), c: (i16,i16)) {
+	pub fn touch_move(&mut self, finger: usize, wh: (u16, u16),
+		c: (i16, i16))
+	{
 		let xy = cursor_coordinates(wh, c);
 
+		self.input(Event::Touch(finger, Some(false), xy));
+	}
+
+	/// A touchpad gesture moved `movement` pixels.
+	#[inline(always)]
+	pub fn pan(&mut self, wh: (u16, u16), c: (i16, i16),
+		movement: (f32, f32))
+	{
+		let w = wh.0.max(1) as f32;
+		let h = wh.1.max(1) as f32;
+
+		if let Some(xy) = cursor_coordinates(wh, c) {
+			let movement = (movement.0 * 2.0 / w,
+				movement.1 * 2.0 / h);
+
+			self.input(Event::Pan(xy, movement));
+		}
+	}
+
+	#[inline(always)]
+	pub fn pinch(&mut self, wh: (u16, u16), c: (i16, i16), scale: f32) {
+		if let Some(xy) = cursor_coordinates(wh, c) {
+			self.input(Event::Pinch(xy, scale));
+		}
+	}
+
+	#[inline(always)]
+	pub fn rotate(&mut self, wh: (u16, u16), c: (i16, i16), angle: f32) {
+		if let Some(xy) = cursor_coordinates(wh, c) {
+			self.input(Event::Rotate(xy, angle));
+		}
+	}
+
+	#[inline(always)]
+	pub fn cursor_move(&mut self, wh: (u16, u16), c: (i16,i16)) {
+		let xy = self.pointer_coordinates(wh, c);
+
 		self.input(Event::Cursor(xy));
 	}
 
 	#[inline(always)]
 	pub fn cursor_leave(&mut self) {
-		self.input(Event::Cursor(None));
+		// Captured: the cursor is still reported outside the window.
+		if !self.captured() {
+			self.input(Event::Cursor(None));
+		}
+	}
+
+	/// Whether a mouse button is held, so the OS should keep sending the
+	/// cursor while it's outside of the window.
+	pub fn captured(&self) -> bool {
+		self.held.contains(&true)
+	}
+
+	/// Cursor coordinates, which may be outside of the window (instead of
+	/// `None`) while the pointer is captured.
+	fn pointer_coordinates(&self, wh: (u16, u16), c: (i16, i16))
+		-> Option<(f32, f32)>
+	{
+		// Minimized windows can be 0 pixels wide.
+		let wh = (wh.0.max(1), wh.1.max(1));
+
+		if !self.captured() {
+			return cursor_coordinates(wh, c);
+		}
+
+		let (w, h) = (wh.0 as f32, wh.1 as f32);
+
+		Some((c.0 as f32 * 2.0 / w - 1.0, c.1 as f32 * 2.0 / h - 1.0))
+	}
+
+	/// A mouse button was released: stop capturing the pointer after the
+	/// last one, and the cursor left if it's outside of the window.
+	fn release(&mut self, button: usize, xy: Option<(f32, f32)>) {
+		if !self.captured() {
+			return;
+		}
+		self.held[button] = false;
+
+		let outside = match xy {
+			Some((x, y)) => !(-1.0..=1.0).contains(&x)
+				|| !(-1.0..=1.0).contains(&y),
+			None => true,
+		};
+		if !self.captured() && outside {
+			self.input(Event::Cursor(None));
+		}
+	}
+
+	#[inline(always)]
+	pub fn minimized(&mut self) {
+		self.input(Event::Minimized);
+	}
+
+	#[inline(always)]
+	pub fn hidden(&mut self) {
+		self.input(Event::Hidden);
+	}
+
+	#[inline(always)]
+	pub fn shown(&mut self) {
+		self.input(Event::Shown);
+	}
+
+	#[inline(always)]
+	pub fn redraw(&mut self) {
+		self.input(Event::Redraw);
 	}
 
 	#[inline(always)]
 	pub fn pause(&mut self) {
+		// The releases won't be sent.
+		self.held = [false; 3];
 		self.input(Event::Pause);
 	}
 
@@ -669,38 +727,224 @@ impl InputQueue {
 		}
 	}
 
+	/// Add an event that didn't come from the OS (injected).
+	#[cfg(feature = "headless")]
+	pub fn event(&mut self, event: Event) {
+		self.input(event);
+	}
+
 	#[inline(always)]
 	fn input(&mut self, input: Event) -> () {
-		self.mods.update(&mut self.queue, input)
-	}
-
-	#[inline(always)]
-	pub fn stick(&mut self, cm: &mut ::stick::ControllerManager) {
-		while let Some((js, i)) = cm.update() {
-			use ::stick::Input::*;
-
-			match i {
-				Move(x, y) => self.input(Event::CMove(js, x, y)),
-				Camera(x, y) => self.input(Event::CCamera(js, x, y)),
-				ThrottleL(x) => self.input(Event::CThrottleL(js, x)),
-				ThrottleR(x) => self.input(Event::CThrottleR(js, x)),
-				Accept(s) => self.input(Event::CAccept(js, s)),
-				Cancel(s) => self.input(Event::CCancel(js, s)),
-				Execute(s) => self.input(Event::CExecute(js, s)),
-				Action(s) => self.input(Event::CAction(js, s)),
-				L(b, s) => self.input(Event::CL(js, b, s)),
-				R(b, s) => self.input(Event::CR(js, b, s)),
-				Menu(s) => self.input(Event::CMenu(js, s)),
-				Controls => self.input(Event::CControls(js)),
-				Exit => self.input(Event::CExit(js)),
-				Up(s) => self.input(Event::CUp(js, s)),
-				Down(s) => self.input(Event::CDown(js, s)),
-				Left(s) => self.input(Event::CLeft(js, s)),
-				Right(s) => self.input(Event::CRight(js, s)),
-				MoveStick(s) => self.input(Event::CMoveStick(js, s)),
-				CamStick(s) => self.input(Event::CCamStick(js, s)),
-				PluggedIn(i) => self.input(Event::CPluggedIn(js, i)),
-				UnPlugged(i) => self.input(Event::CUnPlugged(js, i)),
+		self.mods.update(&mut self.pending, input);
+		for i in 0..self.pending.len() {
+			let event = self.pending[i];
+			self.push(event);
+		}
+		self.pending.clear();
+	}
+
+	/// Put an event on the back of the queue, following the coalescing
+	/// and overflow settings.
+	fn push(&mut self, event: Event) {
+		if self.coalesce {
+			if let Some(last) = self.queue.back_mut() {
+				match (*last, event) {
+					(Event::Cursor(_), Event::Cursor(_)) => {
+						*last = event;
+						return;
+					}
+					(Event::Scroll(a, _), Event::Scroll(b, xy)) => {
+						*last = Event::Scroll(
+							(a.0 + b.0, a.1 + b.1), xy);
+						return;
+					}
+					(Event::Touch(a, Some(false), _),
+						Event::Touch(b, Some(false), _))
+						if a == b =>
+					{
+						*last = event;
+						return;
+					}
+					(Event::Pan(_, a), Event::Pan(c, b)) => {
+						*last = Event::Pan(c,
+							(a.0 + b.0, a.1 + b.1));
+						return;
+					}
+					(Event::Pinch(_, a), Event::Pinch(c, b)) => {
+						*last = Event::Pinch(c, a * b);
+						return;
+					}
+					(Event::Rotate(_, a), Event::Rotate(c, b)) => {
+						*last = Event::Rotate(c, a + b);
+						return;
+					}
+					_ => {}
+				}
+			}
+		}
+
+		if let Some(capacity) = self.capacity {
+			if self.queue.len() >= capacity {
+				match self.overflow {
+					Overflow::DropOldest => {
+						if self.queue.pop_front()
+							.is_none()
+						{
+							return;
+						}
+					}
+					Overflow::DropNewest => return,
+				}
+			}
+		}
+
+		self.queue.push_back(event);
+	}
+
+	#[inline(always)]
+	pub fn user(&mut self, value: u64) {
+		self.input(Event::User(value));
+	}
+
+	/// Add a controller event.
+	#[inline(always)]
+	pub fn controller(&mut self, event: Event) {
+		self.input(event);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Events aren't `Debug`, so compare their `Display`.
+	fn drain(queue: &mut InputQueue) -> Vec<String> {
+		let mut events = vec![];
+
+		while let Some(event) = queue.pop() {
+			events.push(event.to_string());
+		}
+
+		events
+	}
+
+	fn names(events: &[Event]) -> Vec<String> {
+		events.iter().map(Event::to_string).collect()
+	}
+
+	#[test]
+	fn first_in_first_out() {
+		let mut queue = InputQueue::new();
+
+		queue.user(1);
+		queue.cursor_move((100, 100), (50, 50));
+		queue.user(2);
+
+		assert_eq!(drain(&mut queue), names(&[Event::User(1),
+			Event::Cursor(Some((0.0, 0.0))), Event::User(2)]));
+	}
+
+	#[test]
+	fn capacity_drop_oldest() {
+		let mut queue = InputQueue::new();
+
+		queue.set_capacity(Some(2), Overflow::DropOldest);
+		for i in 0..4 {
+			queue.user(i);
+		}
+
+		assert_eq!(drain(&mut queue),
+			names(&[Event::User(2), Event::User(3)]));
+	}
+
+	#[test]
+	fn capacity_drop_newest() {
+		let mut queue = InputQueue::new();
+
+		queue.set_capacity(Some(2), Overflow::DropNewest);
+		for i in 0..4 {
+			queue.user(i);
+		}
+
+		assert_eq!(drain(&mut queue),
+			names(&[Event::User(0), Event::User(1)]));
+	}
+
+	#[test]
+	fn capacity_shrinks_queue() {
+		let mut queue = InputQueue::new();
+
+		for i in 0..4 {
+			queue.user(i);
+		}
+		queue.set_capacity(Some(1), Overflow::DropNewest);
+
+		assert_eq!(drain(&mut queue), names(&[Event::User(0)]));
+	}
+
+	#[test]
+	fn coalesce() {
+		let mut queue = InputQueue::new();
+
+		queue.set_coalesce(true);
+		queue.cursor_move((100, 100), (0, 0));
+		queue.cursor_move((100, 100), (50, 50));
+		queue.scroll((100, 100), (50, 50), (1.0, 0.0));
+		queue.scroll((100, 100), (50, 50), (1.0, -1.0));
+		queue.user(0);
+		queue.cursor_move((100, 100), (100, 100));
+
+		assert_eq!(drain(&mut queue), names(&[
+			Event::Cursor(Some((0.0, 0.0))),
+			Event::Scroll((2.0, -1.0), Some((0.0, 0.0))),
+			Event::User(0),
+			Event::Cursor(Some((1.0, 1.0))),
+		]));
+	}
+
+	#[test]
+	fn no_coalesce() {
+		let mut queue = InputQueue::new();
+
+		queue.cursor_move((100, 100), (0, 0));
+		queue.cursor_move((100, 100), (50, 50));
+
+		assert_eq!(queue.len(), 2);
+	}
+
+	#[test]
+	fn capture() {
+		let mut queue = InputQueue::new();
+
+		queue.left_button_press((100, 100), (50, 50));
+		queue.cursor_move((100, 100), (150, 50));
+		queue.cursor_leave();
+		queue.left_button_release((100, 100), (150, 50));
+
+		assert_eq!(drain(&mut queue), names(&[
+			Event::LeftButton(Some(true), Some((0.0, 0.0))),
+			Event::Cursor(Some((2.0, 0.0))),
+			Event::LeftButton(None, Some((2.0, 0.0))),
+			Event::Cursor(None),
+		]));
+		assert!(!queue.captured());
+	}
+
+	#[test]
+	fn zero_size() {
+		let mut queue = InputQueue::new();
+
+		queue.cursor_move((0, 0), (0, 0));
+		queue.left_button_press((0, 0), (0, 0));
+		queue.cursor_move((0, 0), (1, 1));
+
+		while let Some(event) = queue.pop() {
+			match event {
+				Event::Cursor(Some((x, y)))
+				| Event::LeftButton(_, Some((x, y))) => {
+					assert!(x.is_finite() && y.is_finite())
+				}
+				_ => {}
 			}
 		}
 	}
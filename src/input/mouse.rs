@@ -0,0 +1,53 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Mouse state, for polling instead of handling every mouse event.
+
+use Event;
+
+/// Which mouse buttons are held down, from `Screen::mouse_buttons()`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MouseButtons {
+	/// Left Button
+	pub left: bool,
+	/// Middle Button
+	pub middle: bool,
+	/// Right Button
+	pub right: bool,
+}
+
+/// The cursor and mouse buttons.
+#[derive(Default)]
+pub(crate) struct Mouse {
+	pub(crate) cursor: Option<(f32, f32)>,
+	pub(crate) buttons: MouseButtons,
+}
+
+impl Mouse {
+	/// Update the cursor and buttons from `event`.  Buttons are released
+	/// when the window loses focus (the releases won't be sent).
+	pub(crate) fn update(&mut self, event: Event) {
+		match event {
+			Event::Cursor(xy) | Event::Scroll(_, xy) => {
+				self.cursor = xy
+			}
+			Event::LeftButton(s, xy) => {
+				self.buttons.left = s.is_some();
+				self.cursor = xy;
+			}
+			Event::MiddleButton(s, xy) => {
+				self.buttons.middle = s.is_some();
+				self.cursor = xy;
+			}
+			Event::RightButton(s, xy) => {
+				self.buttons.right = s.is_some();
+				self.cursor = xy;
+			}
+			Event::Pause => self.buttons = MouseButtons::default(),
+			_ => {}
+		}
+	}
+}
@@ -0,0 +1,326 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Double clicks, drags and hovering, synthesized from mouse events.
+
+use std::collections::VecDeque;
+
+use Event;
+
+/// Longest time between the clicks of a double click (seconds), if the
+/// system doesn't have a setting for it.
+pub(crate) const DOUBLE_CLICK_TIME: f32 = 0.4;
+/// How far the cursor moves with the button held (pixels) before it's a
+/// drag, if the system doesn't have a setting for it.
+pub(crate) const DRAG_THRESHOLD: u16 = 8;
+
+/// Synthesizes `DoubleClick`, `TripleClick`, `Drag*` and `Hover*` events
+/// from the cursor and the left button.
+pub(crate) struct Pointer {
+	// Longest time between clicks (seconds), and how far the cursor can
+	// move (pixels) and still be a click.
+	double_click: f32,
+	threshold: f32,
+	// Size of the window, to measure the cursor in pixels.
+	wh: (u16, u16),
+	// Whether the cursor is in the window.
+	inside: bool,
+	// Where and when the left button was last pressed, and how many
+	// clicks in a row that was.
+	press: Option<((f32, f32), f64)>,
+	clicks: u8,
+	// Where the left button was pressed, while it's held.
+	held: Option<(f32, f32)>,
+	// Where the cursor was at the last `DragMove`, while dragging.
+	drag: Option<(f32, f32)>,
+	// Events synthesized, but not sent yet.
+	queue: VecDeque<Event>,
+}
+
+impl Pointer {
+	/// Synthesize events in a window of size `wh`, with the system's
+	/// double-click time (seconds) and drag threshold (pixels).
+	pub(crate) fn new(wh: (u16, u16), settings: (f32, u16)) -> Pointer {
+		Pointer {
+			double_click: settings.0,
+			threshold: settings.1 as f32,
+			wh,
+			inside: false,
+			press: None,
+			clicks: 0,
+			held: None,
+			drag: None,
+			queue: VecDeque::new(),
+		}
+	}
+
+	/// Update from `event`, which happened at `time` (seconds).
+	pub(crate) fn update(&mut self, event: Event, time: f64) {
+		match event {
+			Event::Resize(w, h) => self.wh = (w, h),
+			Event::Cursor(Some(xy)) => {
+				self.hover(xy);
+				self.moved(xy);
+			}
+			Event::Cursor(None) if self.inside => {
+				self.inside = false;
+				self.send(Event::HoverLeave);
+			}
+			Event::LeftButton(Some(true), Some(xy)) => {
+				self.click(xy, time)
+			}
+			Event::LeftButton(None, xy) => {
+				self.held = None;
+				if let Some(last) = self.drag.take() {
+					let xy = xy.unwrap_or(last);
+
+					self.send(Event::DragEnd(xy));
+				}
+			}
+			// The release won't be sent.
+			Event::Pause => {
+				self.held = None;
+				if let Some(last) = self.drag.take() {
+					self.send(Event::DragEnd(last));
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Get the next event synthesized.
+	pub(crate) fn pop(&mut self) -> Option<Event> {
+		self.queue.pop_front()
+	}
+
+	/// The cursor moved (outside of the window, if captured).
+	fn hover(&mut self, xy: (f32, f32)) {
+		let inside = (-1.0..=1.0).contains(&xy.0)
+			&& (-1.0..=1.0).contains(&xy.1);
+
+		if inside && !self.inside {
+			self.send(Event::HoverEnter(xy));
+		} else if !inside && self.inside {
+			self.send(Event::HoverLeave);
+		}
+		self.inside = inside;
+	}
+
+	/// The cursor moved: start dragging if it's far enough from where the
+	/// left button was pressed.
+	fn moved(&mut self, xy: (f32, f32)) {
+		let from = match self.held {
+			Some(from) => from,
+			None => return,
+		};
+
+		let last = match self.drag {
+			Some(last) => last,
+			None if self.pixels(xy, from) > self.threshold => {
+				// Not a click anymore.
+				self.clicks = 0;
+				self.send(Event::DragStart(from));
+				from
+			}
+			None => return,
+		};
+
+		if xy != last {
+			let movement = (xy.0 - last.0, xy.1 - last.1);
+
+			self.send(Event::DragMove(xy, movement));
+		}
+		self.drag = Some(xy);
+	}
+
+	/// The left button was pressed: count clicks in a row.
+	fn click(&mut self, xy: (f32, f32), time: f64) {
+		let again = match self.press {
+			Some((at, when)) => {
+				time - when <= self.double_click as f64
+					&& self.pixels(xy, at) <= self.threshold
+			}
+			None => false,
+		};
+
+		self.clicks = if again { self.clicks + 1 } else { 1 };
+		match self.clicks {
+			2 => self.send(Event::DoubleClick(xy)),
+			3 => {
+				// The next click starts over.
+				self.clicks = 0;
+				self.send(Event::TripleClick(xy));
+			}
+			_ => {}
+		}
+
+		self.press = Some((xy, time));
+		self.held = Some(xy);
+	}
+
+	/// Send an event.
+	fn send(&mut self, event: Event) {
+		self.queue.push_back(event);
+	}
+
+	/// Distance between two cursor positions, in pixels.
+	fn pixels(&self, a: (f32, f32), b: (f32, f32)) -> f32 {
+		let x = (a.0 - b.0) * self.wh.0 as f32 / 2.0;
+		let y = (a.1 - b.1) * self.wh.1 as f32 / 2.0;
+
+		x.hypot(y)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Send `events` at their time (seconds), and get the events
+	/// synthesized.
+	fn run(pointer: &mut Pointer, events: &[(Event, f64)]) -> Vec<String> {
+		let mut sent = vec![];
+
+		for &(event, time) in events {
+			pointer.update(event, time);
+			while let Some(event) = pointer.pop() {
+				sent.push(event.to_string());
+			}
+		}
+
+		sent
+	}
+
+	fn names(events: &[Event]) -> Vec<String> {
+		events.iter().map(Event::to_string).collect()
+	}
+
+	fn click(xy: (f32, f32), time: f64) -> [(Event, f64); 2] {
+		[(Event::LeftButton(Some(true), Some(xy)), time),
+			(Event::LeftButton(None, Some(xy)), time + 0.05)]
+	}
+
+	// 200×200 pixels, so 0.01 is 1 pixel.
+	fn new() -> Pointer {
+		Pointer::new((200, 200), (DOUBLE_CLICK_TIME, DRAG_THRESHOLD))
+	}
+
+	#[test]
+	fn double_and_triple_click() {
+		let mut pointer = new();
+		let mut events = vec![];
+
+		for i in 0..5 {
+			events.extend(&click((0.0, 0.0), i as f64 * 0.2));
+		}
+
+		// The count starts over after a triple click.
+		assert_eq!(run(&mut pointer, &events), names(&[
+			Event::DoubleClick((0.0, 0.0)),
+			Event::TripleClick((0.0, 0.0)),
+			Event::DoubleClick((0.0, 0.0)),
+		]));
+	}
+
+	#[test]
+	fn double_click_limits() {
+		// Too slow.
+		let mut pointer = new();
+		let mut events = click((0.0, 0.0), 0.0).to_vec();
+		events.extend(&click((0.0, 0.0), 0.5));
+
+		assert!(run(&mut pointer, &events).is_empty());
+
+		// Too far.
+		let mut pointer = new();
+		let mut events = click((0.0, 0.0), 0.0).to_vec();
+		events.extend(&click((0.09, 0.0), 0.2));
+
+		assert!(run(&mut pointer, &events).is_empty());
+
+		// Close enough.
+		let mut pointer = new();
+		let mut events = click((0.0, 0.0), 0.0).to_vec();
+		events.extend(&click((0.07, 0.0), 0.2));
+
+		assert_eq!(run(&mut pointer, &events),
+			names(&[Event::DoubleClick((0.07, 0.0))]));
+	}
+
+	#[test]
+	fn drag_threshold() {
+		let mut pointer = new();
+		let sent = run(&mut pointer, &[
+			(Event::Cursor(Some((0.0, 0.0))), 0.0),
+			(Event::LeftButton(Some(true), Some((0.0, 0.0))), 0.0),
+			(Event::Cursor(Some((0.05, 0.0))), 0.1),
+			(Event::Cursor(Some((0.1, 0.0))), 0.2),
+			(Event::Cursor(Some((0.1, 0.5))), 0.3),
+			(Event::LeftButton(None, Some((0.1, 0.5))), 0.4),
+		]);
+
+		assert_eq!(sent, names(&[
+			Event::HoverEnter((0.0, 0.0)),
+			Event::DragStart((0.0, 0.0)),
+			Event::DragMove((0.1, 0.0), (0.1, 0.0)),
+			Event::DragMove((0.1, 0.5), (0.0, 0.5)),
+			Event::DragEnd((0.1, 0.5)),
+		]));
+	}
+
+	#[test]
+	fn drag_isnt_click() {
+		let mut pointer = new();
+		let sent = run(&mut pointer, &[
+			(Event::LeftButton(Some(true), Some((0.0, 0.0))), 0.0),
+			(Event::Cursor(Some((0.5, 0.0))), 0.1),
+			(Event::LeftButton(None, Some((0.5, 0.0))), 0.15),
+			(Event::LeftButton(Some(true), Some((0.5, 0.0))), 0.2),
+		]);
+
+		assert!(!sent.iter().any(|e| e.starts_with("DoubleClick")),
+			"{:?}", sent);
+	}
+
+	#[test]
+	fn pause_ends_drag() {
+		let mut pointer = new();
+		let sent = run(&mut pointer, &[
+			(Event::LeftButton(Some(true), Some((0.0, 0.0))), 0.0),
+			(Event::Cursor(Some((0.5, 0.0))), 0.1),
+			(Event::Pause, 0.2),
+			(Event::Cursor(Some((0.8, 0.0))), 0.3),
+			(Event::LeftButton(None, Some((0.8, 0.0))), 0.4),
+		]);
+
+		assert_eq!(sent, names(&[
+			Event::HoverEnter((0.5, 0.0)),
+			Event::DragStart((0.0, 0.0)),
+			Event::DragMove((0.5, 0.0), (0.5, 0.0)),
+			Event::DragEnd((0.5, 0.0)),
+		]));
+	}
+
+	#[test]
+	fn hover() {
+		let mut pointer = new();
+		let sent = run(&mut pointer, &[
+			(Event::Cursor(Some((0.0, 0.0))), 0.0),
+			(Event::Cursor(Some((0.5, 0.0))), 0.1),
+			// Captured, outside of the window.
+			(Event::Cursor(Some((1.5, 0.0))), 0.2),
+			(Event::Cursor(Some((0.5, 0.0))), 0.3),
+			(Event::Cursor(None), 0.4),
+			(Event::Cursor(None), 0.5),
+		]);
+
+		assert_eq!(sent, names(&[
+			Event::HoverEnter((0.0, 0.0)),
+			Event::HoverLeave,
+			Event::HoverEnter((0.5, 0.0)),
+			Event::HoverLeave,
+		]));
+	}
+}
@@ -0,0 +1,493 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Recording of the `Event` stream to a file, and deterministic replay.
+//!
+//! A recording is a text file.  The first line is the header, then there is
+//! one line per event: `TIME DT EVENT FIELDS...`, where `TIME` is the number
+//! of seconds since recording started, and `DT` is the timestep that was
+//! passed along with the event.  `CPluggedIn` lines end with the name of
+//! the controller, so replay doesn't depend on what's plugged in.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use {Event, KeyState};
+
+const HEADER: &str = "awi-recording 3";
+
+/// How fast a recording is replayed.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Playback {
+	/// Wait between events, so they arrive at the speed they were
+	/// recorded.
+	RealTime,
+	/// Don't wait between events.
+	Fast,
+}
+
+/// Writes events to a recording file.
+pub(crate) struct Recorder {
+	file: BufWriter<File>,
+	start: Instant,
+}
+
+impl Recorder {
+	/// Create (or truncate) the recording file at `path`.
+	pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Recorder> {
+		let mut file = BufWriter::new(File::create(path)?);
+		let start = Instant::now();
+
+		writeln!(file, "{}", HEADER)?;
+
+		Ok(Recorder { file, start })
+	}
+
+	/// Add an event to the recording, with the controller's `name` for
+	/// `CPluggedIn`.  The file is flushed every `Timestep`, so a recording
+	/// survives the program crashing.
+	pub fn record(&mut self, event: Event, dt: f32, name: Option<&str>)
+		-> io::Result<()>
+	{
+		let time = seconds(self.start.elapsed());
+
+		write!(self.file, "{} {} {}", time, dt, encode(event))?;
+		if let Some(name) = name {
+			write!(self.file, " {}", name.replace('\n', " "))?;
+		}
+		writeln!(self.file)?;
+
+		if event == Event::Timestep {
+			self.file.flush()?;
+		}
+
+		Ok(())
+	}
+
+	/// Write any buffered events to the file.
+	pub fn flush(&mut self) -> io::Result<()> {
+		self.file.flush()
+	}
+}
+
+/// Reads events from a recording file.
+pub(crate) struct Player {
+	records: Vec<(f64, f32, Event, Option<String>)>,
+	index: usize,
+	start: Instant,
+	playback: Playback,
+}
+
+impl Player {
+	/// Load the recording file at `path`.
+	pub fn new<P: AsRef<Path>>(path: P, playback: Playback)
+		-> io::Result<Player>
+	{
+		let mut lines = BufReader::new(File::open(path)?).lines();
+		let mut records = vec![];
+
+		match lines.next() {
+			Some(header) => if header?.trim() != HEADER {
+				return Err(invalid("not an awi recording"));
+			},
+			None => return Err(invalid("empty recording")),
+		}
+
+		for line in lines {
+			let line = line?;
+
+			if line.trim().is_empty() {
+				continue;
+			}
+
+			records.push(decode_line(&line)?);
+		}
+
+		Ok(Player { records, index: 0, start: Instant::now(), playback })
+	}
+
+	/// Get the next event's time (seconds since the recording started),
+	/// the event, it's timestep and the controller's name (for
+	/// `CPluggedIn`), `None` at the end of the recording.  In
+	/// `Playback::RealTime` this sleeps until the event is due.
+	pub fn next(&mut self) -> Option<(f64, Event, f32, Option<String>)> {
+		let (time, dt, event, name) = self.records.get(self.index)?
+			.clone();
+
+		self.index += 1;
+
+		if self.playback == Playback::RealTime {
+			let elapsed = seconds(self.start.elapsed());
+
+			if time > elapsed {
+				::std::thread::sleep(duration(time - elapsed));
+			}
+		}
+
+		Some((time, event, dt, name))
+	}
+}
+
+pub(crate) fn seconds(duration: Duration) -> f64 {
+	duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9
+}
+
+fn duration(seconds: f64) -> Duration {
+	Duration::new(seconds as u64, (seconds.fract() * 1e9) as u32)
+}
+
+fn invalid(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Calls `$m!` with the list of every event that has no fields.
+macro_rules! unit_events {
+	($m:ident, $($args:tt)*) => {
+		$m!($($args)*; Timestep, Exit, Minimized, Hidden,
+			Shown, Redraw, Resume, Pause, AlignLeft,
+			AlignCenter, AlignRight, AlignJustified,
+			EmphasisBrokenUnderline, EmphasisOverline, EmphasisBold,
+			EmphasisInvertColor, EmphasisNone, EmphasisStrikeOut,
+			EmphasisDoubleUnderline, EmphasisUnderline,
+			EmphasisItalic, Select, Copy, Cancel, Delete, Find,
+			Help, Info, Close, Share, SaveCopy, Undo, Redo, Cut,
+			Paste, Print, HoverLeave)
+	}
+}
+
+macro_rules! encode_units {
+	($event:expr; $($name:ident),*) => {
+		match $event {
+			$(Event::$name => return stringify!($name).to_string(),)*
+			_ => {}
+		}
+	}
+}
+
+macro_rules! decode_units {
+	($name:expr; $($variant:ident),*) => {
+		match $name {
+			$(stringify!($variant) => return Ok(Event::$variant),)*
+			_ => {}
+		}
+	}
+}
+
+fn state(state: Option<bool>) -> &'static str {
+	match state {
+		None => "-",
+		Some(true) => "t",
+		Some(false) => "f",
+	}
+}
+
+fn key_state(state: KeyState) -> &'static str {
+	match state {
+		KeyState::Pressed => "p",
+		KeyState::Held => "h",
+		KeyState::Released => "r",
+		KeyState::Repeat => "a",
+	}
+}
+
+fn xy(xy: Option<(f32, f32)>) -> String {
+	match xy {
+		None => "-".to_string(),
+		Some((x, y)) => format!("{},{}", x, y),
+	}
+}
+
+/// Convert an event to it's text form in a recording.
+pub(crate) fn encode(event: Event) -> String {
+	use self::Event::*;
+
+	unit_events!(encode_units, event);
+
+	match event {
+		Resize(w, h) => format!("Resize {} {}", w, h),
+		Moved(x, y) => format!("Moved {} {}", x, y),
+		Text(c) => format!("Text {}", c as u32),
+		// Only the name of the file to open is lost.
+		Open(_) => "Open".to_string(),
+		Cursor(c) => format!("Cursor {}", xy(c)),
+		LeftButton(s, c) => format!("LeftButton {} {}", state(s), xy(c)),
+		MiddleButton(s, c) => format!("MiddleButton {} {}", state(s),
+			xy(c)),
+		RightButton(s, c) => format!("RightButton {} {}", state(s),
+			xy(c)),
+		Touch(i, s, c) => format!("Touch {} {} {}", i, state(s), xy(c)),
+		Scroll(s, c) => format!("Scroll {} {}", xy(Some(s)), xy(c)),
+		Tap(c) => format!("Tap {}", xy(Some(c))),
+		DoubleTap(c) => format!("DoubleTap {}", xy(Some(c))),
+		LongPress(c) => format!("LongPress {}", xy(Some(c))),
+		Pan(c, m) => format!("Pan {} {}", xy(Some(c)), xy(Some(m))),
+		Pinch(c, s) => format!("Pinch {} {}", xy(Some(c)), s),
+		Rotate(c, a) => format!("Rotate {} {}", xy(Some(c)), a),
+		DoubleClick(c) => format!("DoubleClick {}", xy(Some(c))),
+		TripleClick(c) => format!("TripleClick {}", xy(Some(c))),
+		DragStart(c) => format!("DragStart {}", xy(Some(c))),
+		DragMove(c, m) => format!("DragMove {} {}", xy(Some(c)),
+			xy(Some(m))),
+		DragEnd(c) => format!("DragEnd {}", xy(Some(c))),
+		HoverEnter(c) => format!("HoverEnter {}", xy(Some(c))),
+		Key { key, state } => format!("Key {} {}", key,
+			key_state(state)),
+		CMove(i, x, y) => format!("CMove {} {} {}", i, x, y),
+		CCamera(i, x, y) => format!("CCamera {} {} {}", i, x, y),
+		CThrottleL(i, x) => format!("CThrottleL {} {}", i, x),
+		CThrottleR(i, x) => format!("CThrottleR {} {}", i, x),
+		CAccept(i, s) => format!("CAccept {} {}", i, state(s)),
+		CCancel(i, s) => format!("CCancel {} {}", i, state(s)),
+		CExecute(i, s) => format!("CExecute {} {}", i, state(s)),
+		CAction(i, s) => format!("CAction {} {}", i, state(s)),
+		CL(i, b, s) => format!("CL {} {} {}", i, b, state(s)),
+		CR(i, b, s) => format!("CR {} {} {}", i, b, state(s)),
+		CMenu(i, s) => format!("CMenu {} {}", i, state(s)),
+		CControls(i) => format!("CControls {}", i),
+		CExit(i) => format!("CExit {}", i),
+		CUp(i, s) => format!("CUp {} {}", i, state(s)),
+		CDown(i, s) => format!("CDown {} {}", i, state(s)),
+		CLeft(i, s) => format!("CLeft {} {}", i, state(s)),
+		CRight(i, s) => format!("CRight {} {}", i, state(s)),
+		CMoveStick(i, s) => format!("CMoveStick {} {}", i, state(s)),
+		CCamStick(i, s) => format!("CCamStick {} {}", i, state(s)),
+		CPluggedIn(i, id) => format!("CPluggedIn {} {}", i, id),
+		CUnPlugged(i, id) => format!("CUnPlugged {} {}", i, id),
+		CGyro(i, x, y, z) => format!("CGyro {} {} {} {}", i, x, y, z),
+		CAccel(i, x, y, z) => format!("CAccel {} {} {} {}", i, x, y, z),
+		User(x) => format!("User {}", x),
+		_ => unreachable!(),
+	}
+}
+
+fn parse<T: ::std::str::FromStr>(field: Option<&str>) -> io::Result<T> {
+	field.and_then(|f| f.parse().ok())
+		.ok_or_else(|| invalid("bad or missing event field"))
+}
+
+fn parse_state(field: Option<&str>) -> io::Result<Option<bool>> {
+	match field {
+		Some("-") => Ok(None),
+		Some("t") => Ok(Some(true)),
+		Some("f") => Ok(Some(false)),
+		_ => Err(invalid("bad button state")),
+	}
+}
+
+fn parse_key_state(field: Option<&str>) -> io::Result<KeyState> {
+	match field {
+		Some("p") => Ok(KeyState::Pressed),
+		Some("h") => Ok(KeyState::Held),
+		Some("r") => Ok(KeyState::Released),
+		Some("a") => Ok(KeyState::Repeat),
+		_ => Err(invalid("bad key state")),
+	}
+}
+
+fn parse_xy(field: Option<&str>) -> io::Result<Option<(f32, f32)>> {
+	match field {
+		Some("-") => Ok(None),
+		Some(field) => {
+			let mut xy = field.split(',');
+			let x = parse(xy.next())?;
+			let y = parse(xy.next())?;
+
+			Ok(Some((x, y)))
+		}
+		None => Err(invalid("missing coordinates")),
+	}
+}
+
+fn parse_point(field: Option<&str>) -> io::Result<(f32, f32)> {
+	parse_xy(field)?.ok_or_else(|| invalid("missing coordinates"))
+}
+
+fn decode_line(line: &str)
+	-> io::Result<(f64, f32, Event, Option<String>)>
+{
+	let mut fields = line.split_whitespace();
+	let time = parse(fields.next())?;
+	let dt = parse(fields.next())?;
+	let event = decode(&mut fields)?;
+	// Recordings from before names were recorded don't have one.
+	let name = match event {
+		Event::CPluggedIn(_, _) => {
+			Some(fields.collect::<Vec<_>>().join(" "))
+				.filter(|name| !name.is_empty())
+		}
+		_ => None,
+	};
+
+	Ok((time, dt, event, name))
+}
+
+/// Convert the text form of an event in a recording back to the event.
+pub(crate) fn decode<'a, I>(mut fields: I) -> io::Result<Event>
+	where I: Iterator<Item = &'a str>
+{
+	use self::Event::*;
+
+	let name = fields.next().ok_or_else(|| invalid("missing event"))?;
+
+	unit_events!(decode_units, name);
+
+	let f = &mut fields;
+
+	Ok(match name {
+		"Resize" => Resize(parse(f.next())?, parse(f.next())?),
+		"Moved" => Moved(parse(f.next())?, parse(f.next())?),
+		"Text" => Text(::std::char::from_u32(parse(f.next())?)
+			.ok_or_else(|| invalid("bad character"))?),
+		"Open" => Open(None),
+		"Cursor" => Cursor(parse_xy(f.next())?),
+		"LeftButton" => LeftButton(parse_state(f.next())?,
+			parse_xy(f.next())?),
+		"MiddleButton" => MiddleButton(parse_state(f.next())?,
+			parse_xy(f.next())?),
+		"RightButton" => RightButton(parse_state(f.next())?,
+			parse_xy(f.next())?),
+		"Touch" => Touch(parse(f.next())?, parse_state(f.next())?,
+			parse_xy(f.next())?),
+		"Scroll" => Scroll(parse_xy(f.next())?
+			.ok_or_else(|| invalid("missing scroll"))?,
+			parse_xy(f.next())?),
+		"Tap" => Tap(parse_point(f.next())?),
+		"DoubleTap" => DoubleTap(parse_point(f.next())?),
+		"LongPress" => LongPress(parse_point(f.next())?),
+		"Pan" => Pan(parse_point(f.next())?, parse_point(f.next())?),
+		"Pinch" => Pinch(parse_point(f.next())?, parse(f.next())?),
+		"Rotate" => Rotate(parse_point(f.next())?, parse(f.next())?),
+		"DoubleClick" => DoubleClick(parse_point(f.next())?),
+		"TripleClick" => TripleClick(parse_point(f.next())?),
+		"DragStart" => DragStart(parse_point(f.next())?),
+		"DragMove" => DragMove(parse_point(f.next())?,
+			parse_point(f.next())?),
+		"DragEnd" => DragEnd(parse_point(f.next())?),
+		"HoverEnter" => HoverEnter(parse_point(f.next())?),
+		"Key" => Key { key: parse(f.next())?,
+			state: parse_key_state(f.next())? },
+		"CMove" => CMove(parse(f.next())?, parse(f.next())?,
+			parse(f.next())?),
+		"CCamera" => CCamera(parse(f.next())?, parse(f.next())?,
+			parse(f.next())?),
+		"CThrottleL" => CThrottleL(parse(f.next())?, parse(f.next())?),
+		"CThrottleR" => CThrottleR(parse(f.next())?, parse(f.next())?),
+		"CAccept" => CAccept(parse(f.next())?, parse_state(f.next())?),
+		"CCancel" => CCancel(parse(f.next())?, parse_state(f.next())?),
+		"CExecute" => CExecute(parse(f.next())?,
+			parse_state(f.next())?),
+		"CAction" => CAction(parse(f.next())?, parse_state(f.next())?),
+		"CL" => CL(parse(f.next())?, parse(f.next())?,
+			parse_state(f.next())?),
+		"CR" => CR(parse(f.next())?, parse(f.next())?,
+			parse_state(f.next())?),
+		"CMenu" => CMenu(parse(f.next())?, parse_state(f.next())?),
+		"CControls" => CControls(parse(f.next())?),
+		"CExit" => CExit(parse(f.next())?),
+		"CUp" => CUp(parse(f.next())?, parse_state(f.next())?),
+		"CDown" => CDown(parse(f.next())?, parse_state(f.next())?),
+		"CLeft" => CLeft(parse(f.next())?, parse_state(f.next())?),
+		"CRight" => CRight(parse(f.next())?, parse_state(f.next())?),
+		"CMoveStick" => CMoveStick(parse(f.next())?,
+			parse_state(f.next())?),
+		"CCamStick" => CCamStick(parse(f.next())?,
+			parse_state(f.next())?),
+		"CPluggedIn" => CPluggedIn(parse(f.next())?, parse(f.next())?),
+		"CUnPlugged" => CUnPlugged(parse(f.next())?, parse(f.next())?),
+		"CGyro" => CGyro(parse(f.next())?, parse(f.next())?,
+			parse(f.next())?, parse(f.next())?),
+		"CAccel" => CAccel(parse(f.next())?, parse(f.next())?,
+			parse(f.next())?, parse(f.next())?),
+		"User" => User(parse(f.next())?),
+		_ => return Err(invalid("unknown event")),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use Key;
+
+	// Events aren't `Debug`, so compare their `Display`.
+	fn round_trip(event: Event) {
+		let text = encode(event);
+		let decoded = decode(text.split_whitespace()).unwrap();
+
+		assert!(decoded == event, "{} became {}", event, decoded);
+	}
+
+	#[test]
+	fn encode_decode() {
+		let events = [
+			Event::Timestep,
+			Event::HoverLeave,
+			Event::Resize(640, 480),
+			Event::Moved(-10, 20),
+			Event::Text('ß'),
+			Event::Cursor(None),
+			Event::Cursor(Some((0.25, -0.5))),
+			Event::LeftButton(Some(true), Some((0.0, 1.0))),
+			Event::RightButton(None, None),
+			Event::Touch(3, Some(false), Some((0.5, 0.5))),
+			Event::Scroll((1.0, -2.0), None),
+			Event::Pan((0.0, 0.0), (0.125, 0.25)),
+			Event::Pinch((0.0, 0.0), 1.5),
+			Event::DragMove((1.5, 0.0), (0.25, 0.0)),
+			Event::Key { key: Key::A, state: KeyState::Repeat },
+			Event::CMove(1, -0.5, 0.5),
+			Event::CL(0, 2, Some(false)),
+			Event::CGyro(2, 0.1, 0.2, 0.3),
+			Event::User(u64::MAX),
+		];
+
+		for event in events.iter() {
+			round_trip(*event);
+		}
+	}
+
+	#[test]
+	fn decode_errors() {
+		assert!(decode("Nope".split_whitespace()).is_err());
+		assert!(decode("Resize 640".split_whitespace()).is_err());
+		assert!(decode("LeftButton x -".split_whitespace()).is_err());
+		assert!(decode("".split_whitespace()).is_err());
+	}
+
+	#[test]
+	fn record_and_play() {
+		let path = ::std::env::temp_dir()
+			.join(format!("awi-test-{}.rec", ::std::process::id()));
+		let events = [
+			Event::Resize(100, 100),
+			Event::Cursor(Some((0.5, 0.5))),
+			Event::CPluggedIn(0, 0x045e028e),
+			Event::CPluggedIn(1, 0x045e028e),
+			Event::Timestep,
+		];
+		let names = [None, None, Some("Xbox  360 Pad"), None, None];
+
+		let mut recorder = Recorder::new(&path).unwrap();
+		for (i, event) in events.iter().enumerate() {
+			recorder.record(*event, i as f32, names[i]).unwrap();
+		}
+		recorder.flush().unwrap();
+
+		let mut player = Player::new(&path, Playback::Fast).unwrap();
+		for (i, event) in events.iter().enumerate() {
+			let (_time, played, dt, name) = player.next().unwrap();
+
+			assert!(played == *event);
+			assert_eq!(dt, i as f32);
+			// Spaces in names aren't kept.
+			assert_eq!(name.as_deref(),
+				names[i].map(|_| "Xbox 360 Pad"));
+		}
+		assert!(player.next().is_none());
+
+		::std::fs::remove_file(&path).unwrap();
+	}
+}
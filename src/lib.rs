@@ -18,6 +18,9 @@
 extern crate stick;
 pub extern crate afi;
 #[cfg(target_os="windows")] extern crate winapi;
+#[cfg(unix)] extern crate libc;
+#[cfg(feature = "raw-window-handle")] extern crate raw_window_handle;
+#[cfg(feature = "serde")] #[macro_use] extern crate serde;
 #[cfg(not(target_arch="wasm32"))] #[macro_use] extern crate dl_api;
 #[cfg(target_arch="wasm32")] #[macro_use] extern crate stdweb;
 #[cfg(target_arch="wasm32")] #[macro_use] extern crate stdweb_derive;
@@ -25,14 +28,16 @@ pub extern crate afi;
 pub mod screen;
 
 mod window_connection;
+mod window_builder;
 pub(crate) mod input;
 #[cfg(not(target_arch="wasm32"))] pub(crate) mod window;
 #[cfg(not(target_arch="wasm32"))] pub(crate) mod window_ops;
+#[cfg(not(target_arch="wasm32"))] mod waker;
 pub mod render;
 
-/* 1. Windows */ #[cfg(target_os = "windows")] pub(crate) mod os { mod windows; pub use self::windows::*; }
-/* 2. Linux / BSD */ #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "bitrig", target_os = "openbsd", target_os = "netbsd"))] pub(crate) mod os { mod linux; pub use self::linux::*; }
-/* 3. Raspberry Pi (feature) */ #[cfg(target_os = "rpi")] pub(crate) mod os { mod rpi; pub use self::rpi::*; }
+/* 1. Windows */ #[cfg(all(not(feature = "headless"), target_os = "windows"))] pub(crate) mod os { mod windows; pub use self::windows::*; }
+/* 2. Linux / BSD */ #[cfg(all(not(feature = "headless"), not(feature = "kiosk"), any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "bitrig", target_os = "openbsd", target_os = "netbsd")))] pub(crate) mod os { mod linux; pub use self::linux::*; }
+/* 3. Raspberry Pi / Kiosk (feature) */ #[cfg(all(not(feature = "headless"), any(target_os = "rpi", feature = "kiosk")))] pub(crate) mod os { mod rpi; pub use self::rpi::*; }
 /* 4. Deskron (feature) */ #[cfg(target_os = "deskron")] pub(crate) mod os { mod deskron; pub use self::deskron::*; }
 /* 5. Android */ #[cfg(target_os = "android")] pub(crate) mod os { mod android; pub use self::android::*; }
 /* 6. MacOS / iOS */ #[cfg(any(target_os = "macos", target_os = "ios"))] pub(crate) mod os { mod apple; pub use self::apple::*; }
@@ -40,6 +45,17 @@ pub mod render;
 /* 8. Nintendo Switch (Custom target_os) */ #[cfg(target_os = "switch")] pub(crate) mod os { mod switch; pub use self::switch::*; }
 /* 9. Redox */ #[cfg(target_os = "redox")] pub(crate) mod os { mod redox; pub use self::redox::*; }
 /* 10. XBox One (Custom target_os) */ #[cfg(target_os = "xbox")] pub(crate) mod os { mod xbox; pub use self::xbox::*; }
+/* 11. Headless (feature) */ #[cfg(feature = "headless")] pub(crate) mod os { pub(crate) mod headless; pub use self::headless::*; }
+
+/// Headless window, for running without a display server (feature
+/// `headless`).  Input comes from injected events.
+///
+/// Nothing is drawn, not even offscreen: models, textures and shapes are
+/// only handles, and there's no way to read back pixels.  Use it to test
+/// input handling and program logic, not rendering.
+#[cfg(feature = "headless")] pub mod headless {
+	pub use os::headless::{inject, resize};
+}
 
 /// Compatibility with different platforms, languages, C API
 // pub mod c_api;
@@ -47,8 +63,17 @@ pub mod render;
 pub(crate) use std::os::raw::c_void;
 pub(crate) use input::keyboard::Keyboard;
 
-pub use input::Event;
-#[cfg(not(target_arch="wasm32"))] pub(crate) use window_connection::WindowConnection;
+pub use input::{Event, Overflow};
+pub use input::record::Playback;
+pub use input::action::{Actions, Binding};
+pub use input::controller::Controller;
+pub use input::keyboard::{Key, KeyCombo, KeyState, ParseKeyError};
+pub use input::locale::Locale;
+pub use input::mouse::MouseButtons;
+pub use input::gamepad::{Axis, AxisConfig, Calibration, Effect};
+pub use window_builder::{WindowBuilder, WindowType};
+#[cfg(not(target_arch="wasm32"))] pub use window_connection::WindowConnection;
 #[cfg(not(target_arch="wasm32"))] pub(crate) use window::Window;
+#[cfg(not(target_arch="wasm32"))] pub use waker::Waker;
 
 pub use ami::*;
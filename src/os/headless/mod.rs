@@ -0,0 +1,122 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Headless backend: a window that needs no display server.  Input only
+//! comes from events injected with `inject()`.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, Condvar};
+use std::time::Duration;
+
+use Event;
+
+// Events injected, but not yet polled by the window.
+static EVENTS: Mutex<VecDeque<Event>> = Mutex::new(VecDeque::new());
+// Set when `Wake::wake()` is called, cleared by `Window::wait()`.
+static WOKEN: Mutex<bool> = Mutex::new(false);
+// Notified when an event is injected, or `Wake::wake()` is called.
+static NOTIFY: Condvar = Condvar::new();
+// Size of the window, in pixels.
+static SIZE: Mutex<(u16, u16)> = Mutex::new((640, 360));
+
+/// Put an event on the input queue of the headless window, as if it came
+/// from the user.  Can be called from any thread, and before the window is
+/// created.
+pub fn inject(event: Event) {
+	EVENTS.lock().unwrap().push_back(event);
+	Wake.wake();
+}
+
+/// Set the size of the headless window, in pixels.  Called before the
+/// window is created this sets it's initial size, otherwise it generates a
+/// `Resize` event.
+pub fn resize(wh: (u16, u16)) {
+	*SIZE.lock().unwrap() = wh;
+}
+
+pub struct Window {
+	wh: (u16, u16),
+}
+
+/// Handle to wake up a window that's waiting for events.
+#[derive(Clone)]
+pub struct Wake;
+
+impl Wake {
+	pub fn wake(&self) {
+		*WOKEN.lock().unwrap() = true;
+		NOTIFY.notify_all();
+	}
+}
+
+impl Window {
+	pub fn new(_v: Option<i32>, _builder: &::WindowBuilder) -> Self {
+		Window { wh: *SIZE.lock().unwrap() }
+	}
+
+	pub fn external(_connection: ::WindowConnection, _wh: (u16, u16))
+		-> Self
+	{
+		Window { wh: *SIZE.lock().unwrap() }
+	}
+
+	pub fn poll_event(&mut self, input: &mut ::input::InputQueue,
+		_keyboard: &mut ::input::keyboard::Keyboard) -> bool
+	{
+		let wh = *SIZE.lock().unwrap();
+
+		input.resize(&mut self.wh, wh);
+
+		match EVENTS.lock().unwrap().pop_front() {
+			Some(event) => {
+				input.event(event);
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Block until an event is injected, `Wake::wake()` is called, or
+	/// the timeout (if any) runs out.
+	pub fn wait(&mut self, timeout: Option<Duration>) {
+		let mut woken = WOKEN.lock().unwrap();
+
+		if !*woken && EVENTS.lock().unwrap().is_empty() {
+			woken = match timeout {
+				Some(timeout) => NOTIFY.wait_timeout(woken,
+					timeout).unwrap().0,
+				None => NOTIFY.wait(woken).unwrap(),
+			};
+		}
+
+		*woken = false;
+	}
+
+	/// Get a handle to interrupt `wait()` from another thread.
+	pub fn wake(&self) -> Wake {
+		Wake
+	}
+
+	pub fn get_connection(&self) -> ::WindowConnection {
+		::WindowConnection::NoOS
+	}
+
+	pub fn wh(&self) -> (u16, u16) {
+		self.wh
+	}
+
+	// The size is set with `resize()` above.
+	pub fn resize(&mut self, _wh: (u16, u16)) {}
+
+	// There's no user or screensaver.
+	pub fn attention(&mut self) {}
+	pub fn inhibit_screensaver(&mut self, _inhibit: bool)
+		-> Result<(), String> { Ok(()) }
+	pub fn key_label(&self, _key: ::Key) -> Option<String> { None }
+	pub fn click_settings(&self) -> (f32, u16) {
+		(::input::pointer::DOUBLE_CLICK_TIME,
+			::input::pointer::DRAG_THRESHOLD)
+	}
+}
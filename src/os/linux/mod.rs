@@ -3,9 +3,18 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-use input::keyboard;
+use Key;
 use c_void;
 use std::ptr::null_mut;
+use std::sync::Arc;
+use std::time::Duration;
+
+use libc;
+
+#[path = "../pipe.rs"] mod pipe;
+
+use self::pipe::Pipe;
+pub use self::pipe::Wake;
 
 pub struct Window {
 	// Keyboard (XKB)
@@ -15,25 +24,102 @@ pub struct Window {
 	xkb: XkbCommonX11,
 	// Window (XCB)
 	window: u32,
+	root: u32,
+	// Colormap for a window that's not the same depth as the root, or 0.
+	colormap: u32,
+	// `_XEMBED` atom and the embedder's window, if embedded (or 0).
+	xembed: u32,
+	embedder: u32,
 	connection: *mut c_void,
+	// Screen number of the connection (`:0.1` is screen 1).
+	screen: i32,
 	wh: (u16, u16),
+	xy: (i32, i32),
+	// Position in the parent window, from the last real ConfigureNotify.
+	parent_xy: Option<(i16, i16)>,
+	// Whether the window is mapped, and whether any of it can be seen.
+	mapped: bool,
+	shown: bool,
 	xcb: Xcb,
+	// Event received while waiting, but not yet handled (and all of it, if
+	// it's an XInput 2 event).
+	pending: Option<(XcbGenericEvent, Vec<u8>)>,
+	// Pipe to wake up `wait()` from other threads.
+	pipe: Arc<Pipe>,
+	// Window created by another library, which handles it's input.
+	external: Option<::WindowConnection>,
+	// MIT-SCREEN-SAVER extension, loaded when first inhibiting.
+	screensaver: Option<XcbScreenSaver>,
+	inhibited: bool,
+	// XInput 2 extension (touches and touchpad gestures), and it's major
+	// opcode, if the X server has it.
+	xinput: Option<(XcbInput, u8)>,
+	// Touch ID of each finger touching, by finger number.
+	touches: Vec<Option<u32>>,
+	// Scale of the touchpad pinch so far.
+	pinch: f32,
 }
 
 impl Window {
-	pub fn new(v: Option<i32>) -> Self {
+	pub fn new(v: Option<i32>, builder: &::WindowBuilder) -> Self {
 		// TODO: Try Wayland first
 
 		let (xcb, xkb) = xcb_load();
-		let connection = xcb_connect(&xcb);
-		let mut screen = xcb_screen(connection, &xcb);
-		let window = xcb_window(connection, &xcb, &mut screen, v);
+		let (connection, number) = xcb_connect(&xcb);
+		let mut screen = xcb_screen(connection, &xcb, number);
+		let parent = xcb_parent(connection, &xcb, &mut screen, builder);
+		let (window, colormap) = xcb_window(connection, &xcb,
+			&mut screen, number, v, parent, builder);
 		let (state, keymap, context) = xkb_keyboard(connection, &xkb);
 		let wh = (screen.width_in_pixels, screen.height_in_pixels);
+		let xy = (0, 0);
+		let root = screen.root;
+		let (xembed, embedder) = match parent {
+			Some(parent) => {
+				(get_atom(connection, &xcb, b"_XEMBED"), parent)
+			}
+			None => (0, 0),
+		};
+
+		let pending = None;
+		let pipe = Arc::new(Pipe::new());
+		let xinput = xinput_select(connection, &xcb, window);
+
+		Window {
+			state, keymap, context, xkb, window, root, colormap,
+			xembed, embedder, connection, screen: number, wh, xy,
+			parent_xy: None, mapped: false,
+			shown: false, xcb, pending, pipe, external: None,
+			screensaver: None, inhibited: false, xinput,
+			touches: Vec::new(), pinch: 1.0,
+		}
+	}
+
+	/// Wrap a window created by another library, only to render to it.
+	/// The other library handles it's input.
+	pub fn external(connection: ::WindowConnection, wh: (u16, u16)) -> Self {
+		let (xcb, xkb) = xcb_load();
+		let (window, xcb_connection, screen, root) = match connection {
+			::WindowConnection::Xcb(connection, window, screen) => {
+				let root = xcb_screen(connection, &xcb, screen)
+					.root;
+
+				(window, connection, screen, root)
+			}
+			_ => (0, null_mut(), 0, 0),
+		};
 
 		Window {
-			state, keymap, context, xkb, window, connection, wh,
-			xcb
+			state: null_mut(), keymap: null_mut(),
+			context: null_mut(), xkb, window, root, colormap: 0,
+			xembed: 0, embedder: 0, connection: xcb_connection,
+			screen, wh,
+			xy: (0, 0), parent_xy: None, mapped: true, shown: true,
+			xcb,
+			pending: None, pipe: Arc::new(Pipe::new()),
+			external: Some(connection), screensaver: None,
+			inhibited: false, xinput: None, touches: Vec::new(),
+			pinch: 1.0,
 		}
 	}
 
@@ -41,14 +127,438 @@ impl Window {
 		keyboard: &mut ::input::keyboard::Keyboard)
 		-> bool
 	{
+		if self.external.is_some() {
+			return false;
+		}
+
 		unsafe { (self.xcb.xcb_flush)(self.connection) };
-		xcb_poll_for_event(self.connection, &self.xcb, &self.xkb,
-			self.state, input, &mut self.wh,
-			keyboard)
+
+		let (event, data) = match self.pending.take() {
+			Some(event) => event,
+			None => match xcb_next_event(self.connection, &self.xcb) {
+				Some(event) => event,
+				None => return false,
+			},
+		};
+
+		match event.response_type & !SYNTHETIC {
+			GE_GENERIC => self.xinput_event(&data, input),
+			CLIENT_MESSAGE if self.is_xembed(&event) => {
+				self.xembed_message(&event)
+			}
+			BUTTON_PRESS if self.embedder != 0 => {
+				self.xembed_request_focus();
+				xcb_handle_event(event, &self.xkb, self.state,
+					input, &mut self.wh, keyboard)
+			}
+			CONFIGURE_NOTIFY => self.configure_notify(&event, input),
+			EXPOSE | VISIBILITY_NOTIFY | MAP_NOTIFY | UNMAP_NOTIFY => {
+				self.visibility(&event, input)
+			}
+			_ => xcb_handle_event(event, &self.xkb, self.state,
+				input, &mut self.wh, keyboard),
+		}
+
+		true
+	}
+
+	/// An XInput 2 event (`data` is all of it): a touch, or a touchpad
+	/// gesture.
+	fn xinput_event(&mut self, data: &[u8],
+		input: &mut ::input::InputQueue)
+	{
+		const TOUCH_BEGIN: u16 = 18;
+		const TOUCH_UPDATE: u16 = 19;
+		const TOUCH_END: u16 = 20;
+		const PINCH_BEGIN: u16 = 27;
+		const PINCH_UPDATE: u16 = 28;
+		const SWIPE_UPDATE: u16 = 31;
+
+		// Another extension's event, or too short.
+		match self.xinput {
+			Some((_, opcode)) if data.len() >= 76
+				&& data[1] == opcode => {}
+			_ => return,
+		}
+
+		let u32_at = |i: usize| {
+			u32::from_ne_bytes([data[i], data[i + 1], data[i + 2],
+				data[i + 3]])
+		};
+		// 16.16 fixed point.
+		let fixed = |i: usize| u32_at(i) as i32 as f32 / 65536.0;
+		let event_type = u16::from_ne_bytes([data[8], data[9]]);
+		let detail = u32_at(16);
+		let xy = (fixed(44) as i16, fixed(48) as i16);
+		let finger = self.touches.iter()
+			.position(|&touch| touch == Some(detail));
+
+		match (event_type, finger) {
+			(TOUCH_BEGIN, _) => {
+				let finger = match self.touches.iter()
+					.position(Option::is_none)
+				{
+					Some(finger) => finger,
+					None => {
+						self.touches.push(None);
+						self.touches.len() - 1
+					}
+				};
+
+				self.touches[finger] = Some(detail);
+				input.touch_press(finger, self.wh, xy);
+			}
+			(TOUCH_UPDATE, Some(finger)) => {
+				input.touch_move(finger, self.wh, xy)
+			}
+			(TOUCH_END, Some(finger)) => {
+				self.touches[finger] = None;
+				input.touch_release(finger, self.wh, xy);
+			}
+			(PINCH_BEGIN, _) => self.pinch = 1.0,
+			(PINCH_UPDATE, _) | (SWIPE_UPDATE, _) => {
+				let movement = (fixed(52), fixed(56));
+
+				if movement != (0.0, 0.0) {
+					input.pan(self.wh, xy, movement);
+				}
+				if event_type == SWIPE_UPDATE {
+					return;
+				}
+
+				// Scale is since the pinch began, angle is since
+				// the last update (degrees clockwise).
+				let scale = fixed(68);
+				let angle = fixed(72).to_radians();
+
+				if scale != self.pinch && self.pinch > 0.0 {
+					let scale = scale / self.pinch;
+
+					input.pinch(self.wh, xy, scale);
+				}
+				if angle != 0.0 {
+					input.rotate(self.wh, xy, angle);
+				}
+				self.pinch = scale;
+			}
+			_ => {}
+		}
+	}
+
+	/// Whether `event` is an XEmbed message from the embedder.
+	fn is_xembed(&self, event: &XcbGenericEvent) -> bool {
+		let event = unsafe {
+			&*(event as *const _ as *const XcbClientMessageEvent)
+		};
+
+		self.xembed != 0 && event.message_type == self.xembed
+	}
+
+	/// Handle an XEmbed message from the embedder.
+	fn xembed_message(&mut self, event: &XcbGenericEvent) {
+		const EMBEDDED_NOTIFY: u32 = 0;
+		const FOCUS_IN: u32 = 4;
+		const REVERT_TO_PARENT: u8 = 2;
+
+		let event = unsafe {
+			&*(event as *const _ as *const XcbClientMessageEvent)
+		};
+
+		match event.data[1] {
+			EMBEDDED_NOTIFY => self.embedder = event.data[3],
+			// The X server sends `FocusIn`, which is `Event::Resume`.
+			FOCUS_IN => unsafe {
+				(self.xcb.xcb_set_input_focus)(self.connection,
+					REVERT_TO_PARENT, self.window, event.data[0]);
+			},
+			// Activation and `FocusOut` are handled by the X server.
+			_ => {}
+		}
+	}
+
+	/// Ask the embedder to give the window keyboard focus.
+	fn xembed_request_focus(&self) {
+		const REQUEST_FOCUS: u32 = 3;
+
+		let message = XcbClientMessageEvent {
+			response_type: CLIENT_MESSAGE,
+			format: 32,
+			sequence: 0,
+			window: self.embedder,
+			message_type: self.xembed,
+			data: [0 /*CurrentTime*/, REQUEST_FOCUS, 0, 0, 0],
+		};
+
+		unsafe {
+			(self.xcb.xcb_send_event)(self.connection, 0,
+				self.embedder, 0, &message as *const _ as *const u8);
+		}
+	}
+
+	/// The window was exposed, mapped, unmapped or (un)covered.
+	fn visibility(&mut self, event: &XcbGenericEvent,
+		input: &mut ::input::InputQueue)
+	{
+		const FULLY_OBSCURED: u8 = 2;
+
+		match event.response_type & !SYNTHETIC {
+			EXPOSE => {
+				let event = unsafe { &*(event as *const _
+					as *const XcbExposeEvent) };
+
+				// Only once for a series of exposed areas.
+				if event.count == 0 {
+					input.redraw();
+				}
+			}
+			VISIBILITY_NOTIFY => {
+				let event = unsafe { &*(event as *const _
+					as *const XcbVisibilityNotifyEvent) };
+
+				if event.state == FULLY_OBSCURED {
+					if self.shown {
+						self.shown = false;
+						input.hidden();
+					}
+				} else if self.mapped && !self.shown {
+					self.shown = true;
+					input.shown();
+				}
+			}
+			MAP_NOTIFY => {
+				self.mapped = true;
+				if !self.shown {
+					self.shown = true;
+					input.shown();
+				}
+			}
+			_ /*UNMAP_NOTIFY*/ => {
+				self.mapped = false;
+				self.shown = false;
+				input.minimized();
+			}
+		}
+	}
+
+	/// The window was moved or resized.
+	fn configure_notify(&mut self, event: &XcbGenericEvent,
+		input: &mut ::input::InputQueue)
+	{
+		let event = unsafe {
+			&*(event as *const _ as *const XcbConfigureNotifyEvent)
+		};
+
+		input.resize(&mut self.wh, (event.width, event.height));
+
+		// Synthetic events (from the window manager) are relative to
+		// the root window, real ones are relative to the parent window
+		// (usually the window manager's frame).  Moving the frame only
+		// sends a synthetic event, so real ones only need the position
+		// looked up (a round trip) when it changed in the parent.
+		let xy = if event.response_type & SYNTHETIC != 0 {
+			(event.x as i32, event.y as i32)
+		} else if self.parent_xy != Some((event.x, event.y)) {
+			self.parent_xy = Some((event.x, event.y));
+			xcb_root_xy(self.connection, &self.xcb, self.window,
+				self.root)
+		} else {
+			return;
+		};
+
+		input.moved(&mut self.xy, xy);
+	}
+
+	/// Block until there's an event, `Wake::wake()` is called, or the
+	/// timeout (if any) runs out.
+	pub fn wait(&mut self, timeout: Option<Duration>) {
+		let timeout = match timeout {
+			// Long timeouts wait as long as `poll()` can (-1 is
+			// forever).
+			Some(t) => t.as_millis().min(i32::MAX as u128) as i32,
+			None => -1,
+		};
+
+		// Another library reads the external window's events.
+		if self.external.is_some() {
+			let mut fd = libc::pollfd {
+				fd: self.pipe.read, events: libc::POLLIN,
+				revents: 0
+			};
+
+			unsafe { libc::poll(&mut fd, 1, timeout) };
+
+			self.pipe.drain();
+			return;
+		}
+
+		unsafe { (self.xcb.xcb_flush)(self.connection) };
+
+		// XCB may have already read an event from the socket.
+		if self.pending.is_none() {
+			self.pending = xcb_next_event(self.connection, &self.xcb);
+		}
+		if self.pending.is_some() {
+			return;
+		}
+
+		let fd = unsafe {
+			(self.xcb.xcb_get_file_descriptor)(self.connection)
+		};
+		let mut fds = [
+			libc::pollfd { fd, events: libc::POLLIN, revents: 0 },
+			libc::pollfd {
+				fd: self.pipe.read, events: libc::POLLIN,
+				revents: 0
+			},
+		];
+
+		unsafe { libc::poll(fds.as_mut_ptr(), 2, timeout) };
+
+		self.pipe.drain();
+	}
+
+	/// Get a handle to interrupt `wait()` from another thread.
+	pub fn wake(&self) -> Wake {
+		Wake(self.pipe.clone())
 	}
 
 	pub fn get_connection(&self) -> ::WindowConnection {
-		::WindowConnection::Xcb(self.connection, self.window)
+		match self.external {
+			Some(ref connection) => connection.clone(),
+			None => ::WindowConnection::Xcb(self.connection,
+				self.window, self.screen),
+		}
+	}
+
+	/// Ask the window manager to get the user's attention.
+	pub fn attention(&mut self) {
+		const ADD: u32 = 1;
+		const SOURCE_APPLICATION: u32 = 1;
+		// SUBSTRUCTURE_NOTIFY | SUBSTRUCTURE_REDIRECT
+		const MASK: u32 = 0x180000;
+
+		// Not an X11 window.
+		if self.connection.is_null() {
+			return;
+		}
+
+		let state = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_STATE");
+		let attention = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_STATE_DEMANDS_ATTENTION");
+		let message = XcbClientMessageEvent {
+			response_type: CLIENT_MESSAGE,
+			format: 32,
+			sequence: 0,
+			window: self.window,
+			message_type: state,
+			data: [ADD, attention, 0, SOURCE_APPLICATION, 0],
+		};
+
+		unsafe {
+			(self.xcb.xcb_send_event)(self.connection, 0, self.root,
+				MASK, &message as *const _ as *const u8);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	/// Suspend (or resume) the screensaver and display power management.
+	pub fn inhibit_screensaver(&mut self, inhibit: bool)
+		-> Result<(), String>
+	{
+		if self.connection.is_null() {
+			return Err("not an X11 window".to_string());
+		}
+		if inhibit == self.inhibited {
+			return Ok(());
+		}
+
+		if self.screensaver.is_none() {
+			let screensaver = XcbScreenSaver::new().map_err(|err| {
+				format!("couldn't load MIT-SCREEN-SAVER: {}",
+					err)
+			})?;
+
+			self.screensaver = Some(screensaver);
+		}
+
+		let screensaver = self.screensaver.as_ref().unwrap();
+
+		unsafe {
+			(screensaver.xcb_screensaver_suspend)(self.connection,
+				inhibit as u32);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+
+		self.inhibited = inhibit;
+
+		Ok(())
+	}
+
+	/// Get the character the XKB keymap types with `key` (uppercase, no
+	/// modifiers held), for keys that type a character.
+	pub fn key_label(&self, key: Key) -> Option<String> {
+		// No keymap for an external window; other keys aren't labeled.
+		if self.keymap.is_null() || key.name().chars().count() != 1 {
+			return None;
+		}
+
+		// Keycodes are 8-255: the first one is the main keyboard's.
+		let keycode = (8..=255).find(|&k| self::key(k) == Some(key))?;
+		// A new state, so the modifiers held now don't change it.
+		let state = unsafe { (self.xkb.xkb_state_new)(self.keymap) };
+		if state.is_null() {
+			return None;
+		}
+		let label = xkb_state_key_get_utf8(&self.xkb, state, keycode);
+		unsafe { (self.xkb.xkb_state_unref)(state) };
+
+		// Dead keys and control characters aren't labels.
+		if label.is_empty() || label.chars().any(char::is_control) {
+			return None;
+		}
+
+		// Keep "ß" instead of "SS".
+		let upper = label.to_uppercase();
+		Some(if upper.chars().count() == label.chars().count() {
+			upper
+		} else {
+			label
+		})
+	}
+
+	/// Get the double-click time and drag threshold from XSettings (set by
+	/// the desktop environment), or the defaults if it isn't running.
+	pub fn click_settings(&self) -> (f32, u16) {
+		let mut settings = (::input::pointer::DOUBLE_CLICK_TIME,
+			::input::pointer::DRAG_THRESHOLD);
+
+		if self.connection.is_null() {
+			return settings;
+		}
+
+		let data = xsettings(self.connection, &self.xcb);
+
+		for (name, value) in xsettings_ints(&data) {
+			match name {
+				b"Net/DoubleClickTime" if value > 0 => {
+					settings.0 = value as f32 / 1000.0
+				}
+				b"Net/DndDragThreshold" if value >= 0 => {
+					settings.1 = value.min(0xFFFF) as u16
+				}
+				_ => {}
+			}
+		}
+
+		settings
+	}
+
+	/// The external window was resized.
+	pub fn resize(&mut self, wh: (u16, u16)) {
+		if self.external.is_some() {
+			self.wh = wh;
+		}
 	}
 
 	pub fn wh(&self) -> (u16, u16) {
@@ -58,12 +568,21 @@ impl Window {
 
 impl Drop for Window {
 	fn drop(&mut self) {
+		// The other library owns the external window.
+		if self.external.is_some() {
+			return;
+		}
+
 		unsafe {
 			(self.xkb.xkb_state_unref)(self.state);
 			(self.xkb.xkb_keymap_unref)(self.keymap);
 			(self.xkb.xkb_context_unref)(self.context);
 			(self.xcb.xcb_destroy_window)(self.connection,
 				self.window);
+			if self.colormap != 0 {
+				(self.xcb.xcb_free_colormap)(self.connection,
+					self.colormap);
+			}
 			(self.xcb.xcb_disconnect)(self.connection);
 		}
 	}
@@ -71,27 +590,69 @@ impl Drop for Window {
 
 dl_api!(Xcb, "libxcb.so.1",
 	fn xcb_poll_for_event(*mut c_void) -> *mut XcbGenericEvent,
+	fn xcb_get_file_descriptor(*mut c_void) -> i32,
+	fn xcb_translate_coordinates(*mut c_void, u32, u32, i16, i16) -> u32,
+	fn xcb_translate_coordinates_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbTranslateCoordinatesReply,
 	fn xcb_flush(*mut c_void) -> i32,
 	fn xcb_intern_atom(*mut c_void, u8, u16, *const u8) -> u32,
 	fn xcb_intern_atom_reply(*mut c_void, u32, *mut c_void)
 		-> *mut XcbInternAtomReply,
+	fn xcb_query_extension(*mut c_void, u16, *const u8) -> u32,
+	fn xcb_query_extension_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbQueryExtensionReply,
+	fn xcb_get_selection_owner(*mut c_void, u32) -> u32,
+	fn xcb_get_selection_owner_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbGetSelectionOwnerReply,
+	fn xcb_get_property(*mut c_void, u8, u32, u32, u32, u32, u32) -> u32,
+	fn xcb_get_property_reply(*mut c_void, u32, *mut c_void)
+		-> *mut c_void,
+	fn xcb_get_property_value(*const c_void) -> *mut u8,
+	fn xcb_get_property_value_length(*const c_void) -> i32,
 	fn xcb_change_property(*mut c_void, u8, u32, u32, u32, u8, u32,
 		*const c_void) -> u32,
 	fn xcb_map_window(*mut c_void, u32) -> u32,
 	fn xcb_get_setup(*mut c_void) -> *mut c_void,
 	fn xcb_setup_roots_iterator(*mut c_void) -> XcbScreenIterator,
+	fn xcb_screen_next(*mut XcbScreenIterator) -> (),
+	fn xcb_screen_allowed_depths_iterator(*const XcbScreen)
+		-> XcbDepthIterator,
+	fn xcb_depth_next(*mut XcbDepthIterator) -> (),
+	fn xcb_depth_visuals_iterator(*const XcbDepth)
+		-> XcbVisualtypeIterator,
+	fn xcb_visualtype_next(*mut XcbVisualtypeIterator) -> (),
+	fn xcb_create_colormap(*mut c_void, u8, u32, u32, u32) -> u32,
+	fn xcb_free_colormap(*mut c_void, u32) -> u32,
+	fn xcb_get_geometry(*mut c_void, u32) -> u32,
+	fn xcb_get_geometry_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbGetGeometryReply,
+	fn xcb_send_event(*mut c_void, u8, u32, u32, *const u8) -> u32,
+	fn xcb_set_input_focus(*mut c_void, u8, u32, u32) -> u32,
 	fn xcb_generate_id(*mut c_void) -> u32,
 	fn xcb_create_window(*mut c_void, u8, u32, u32, i16, i16, u16, u16, u16,
 		u16, u32, u32, *mut u32) -> u32,
-	fn xcb_connect(*mut c_void, *mut c_void) -> *mut c_void,
+	fn xcb_connect(*mut c_void, *mut i32) -> *mut c_void,
 	fn xcb_destroy_window(*mut c_void, u32) -> u32,
 	fn xcb_disconnect(*mut c_void) -> ()
 );
 
+dl_api!(XcbScreenSaver, "libxcb-screensaver.so.0",
+	fn xcb_screensaver_suspend(*mut c_void, u32) -> u32
+);
+
+dl_api!(XcbInput, "libxcb-xinput.so.0",
+	fn xcb_input_xi_query_version(*mut c_void, u16, u16) -> u32,
+	fn xcb_input_xi_query_version_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbInputXiQueryVersionReply,
+	fn xcb_input_xi_select_events(*mut c_void, u32, u16,
+		*const XcbInputEventMask) -> u32
+);
+
 dl_api!(XkbCommonX11, "libxkbcommon-x11.so.0",
 	fn xkb_context_unref(*mut c_void) -> (),
 	fn xkb_keymap_unref(*mut c_void) -> (),
 	fn xkb_state_unref(*mut c_void) -> (),
+	fn xkb_state_new(*mut c_void) -> *mut c_void,
 	fn xcb_xkb_use_extension(*mut c_void, u16, u16) -> u32,
 	fn xkb_state_key_get_utf8(*mut c_void, u32, *mut u8, usize) -> i32,
 	fn xkb_state_update_key(*mut c_void, u32, KeyDirection)
@@ -104,6 +665,9 @@ dl_api!(XkbCommonX11, "libxkbcommon-x11.so.0",
 	fn xkb_x11_get_core_keyboard_device_id(*mut c_void) -> i32
 );
 
+// Visual class with red, green and blue masks.
+const TRUE_COLOR: u8 = 4;
+
 #[allow(dead_code)]
 #[repr(C)]
 enum StateComponent { None }
@@ -123,6 +687,71 @@ struct XcbInternAtomReply {
 	atom: u32,
 }
 
+#[repr(C)]
+struct XcbGetSelectionOwnerReply {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	length: u32,
+	owner: u32,
+}
+
+#[repr(C)]
+struct XcbQueryExtensionReply {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	length: u32,
+	present: u8,
+	major_opcode: u8,
+	first_event: u8,
+	first_error: u8,
+}
+
+#[repr(C)]
+struct XcbInputXiQueryVersionReply {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	length: u32,
+	major_version: u16,
+	minor_version: u16,
+}
+
+#[repr(C)]
+struct XcbInputEventMask {
+	deviceid: u16,
+	mask_len: u16,
+	// Bit for each event type.
+	mask: [u32; 2],
+}
+
+#[repr(C)]
+struct XcbTranslateCoordinatesReply {
+	response_type: u8,
+	same_screen: u8,
+	sequence: u16,
+	length: u32,
+	child: u32,
+	dst_x: i16,
+	dst_y: i16,
+}
+
+#[repr(C)]
+struct XcbGetGeometryReply {
+	response_type: u8,
+	depth: u8,
+	sequence: u16,
+	length: u32,
+	root: u32,
+	x: i16,
+	y: i16,
+	width: u16,
+	height: u16,
+	border_width: u16,
+	pad0: [u8; 2],
+}
+
 #[repr(C)]
 enum CompileFlags { NoFlags = 0 }
 
@@ -156,6 +785,40 @@ struct XcbScreenIterator {
 	index: i32,
 }
 
+#[repr(C)]
+struct XcbDepth {
+	depth: u8,
+	pad0: u8,
+	visuals_len: u16,
+	pad1: [u8; 4],
+}
+
+#[repr(C)]
+struct XcbDepthIterator {
+	data: *mut XcbDepth,
+	rem: i32,
+	index: i32,
+}
+
+#[repr(C)]
+struct XcbVisualtype {
+	visual_id: u32,
+	class: u8,
+	bits_per_rgb_value: u8,
+	colormap_entries: u16,
+	red_mask: u32,
+	green_mask: u32,
+	blue_mask: u32,
+	pad0: [u8; 4],
+}
+
+#[repr(C)]
+struct XcbVisualtypeIterator {
+	data: *mut XcbVisualtype,
+	rem: i32,
+	index: i32,
+}
+
 #[repr(C)] #[derive(Clone)]
 struct XcbGenericEvent {
 	response_type: u8,
@@ -174,6 +837,81 @@ struct XcbGenericEvent {
 	pad0: u8,
 }
 
+#[repr(C)]
+struct XcbConfigureNotifyEvent {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	event: u32,
+	window: u32,
+	above_sibling: u32,
+	x: i16,
+	y: i16,
+	width: u16,
+	height: u16,
+	border_width: u16,
+	override_redirect: u8,
+	pad1: u8,
+}
+
+#[repr(C)]
+struct XcbExposeEvent {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	window: u32,
+	x: u16,
+	y: u16,
+	width: u16,
+	height: u16,
+	count: u16,
+}
+
+#[repr(C)]
+struct XcbClientMessageEvent {
+	response_type: u8,
+	format: u8,
+	sequence: u16,
+	window: u32,
+	message_type: u32,
+	data: [u32; 5],
+}
+
+#[repr(C)]
+struct XcbVisibilityNotifyEvent {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	window: u32,
+	state: u8,
+}
+
+// Bit set in `response_type` for events sent with `xcb_send_event()`.
+const SYNTHETIC: u8 = 0x80;
+const BUTTON_PRESS: u8 = 4;
+const EXPOSE: u8 = 12;
+const VISIBILITY_NOTIFY: u8 = 15;
+const UNMAP_NOTIFY: u8 = 18;
+const MAP_NOTIFY: u8 = 19;
+const CONFIGURE_NOTIFY: u8 = 22;
+const CLIENT_MESSAGE: u8 = 33;
+const GE_GENERIC: u8 = 35;
+
+/// Get the 32-bit (ARGB) visuals of the default screen, for EGL to choose a
+/// config that a transparent window can use.
+pub fn argb_visuals() -> Vec<i32> {
+	let (xcb, _) = xcb_load();
+	let (connection, number) = xcb_connect(&xcb);
+	let visuals = xcb_visuals(connection, &xcb, number).iter()
+		.filter(|v| v.0 == 32 && v.2 == TRUE_COLOR)
+		.map(|v| v.1 as i32)
+		.collect();
+
+	unsafe { (xcb.xcb_disconnect)(connection) };
+
+	visuals
+}
+
 fn xcb_load() -> (Xcb, XkbCommonX11) {
 	unsafe fn load_xcb_dl() -> Result<(Xcb,XkbCommonX11), ::dl_api::Error> {
 		Ok((Xcb::new()?, XkbCommonX11::new()?))
@@ -184,52 +922,268 @@ fn xcb_load() -> (Xcb, XkbCommonX11) {
 	})
 }
 
-fn xcb_connect(xcb: &Xcb) -> *mut c_void {
-	let connection = unsafe { (xcb.xcb_connect)(null_mut(), null_mut()) };
+/// Connect to the X server, returns the connection and the screen number
+/// from `$DISPLAY`.
+fn xcb_connect(xcb: &Xcb) -> (*mut c_void, i32) {
+	let mut screen = 0;
+	let connection = unsafe { (xcb.xcb_connect)(null_mut(), &mut screen) };
 	if connection.is_null() {
 		eprintln!(
 			"ERROR: XCB couldn't connect to X server, aborting..."
 		);
 		::std::process::abort();
 	}
-	connection
+	(connection, screen)
 }
 
-fn xcb_screen(connection: *mut c_void, xcb: &Xcb) -> XcbScreen {
-	let setup = unsafe { (xcb.xcb_get_setup)(connection) };
-	unsafe { (*((xcb.xcb_setup_roots_iterator)(setup).data)).clone() }
+/// Get screen number `number`, or the first screen if there isn't one.
+fn xcb_screen_data(connection: *mut c_void, xcb: &Xcb, number: i32)
+	-> *mut XcbScreen
+{
+	unsafe {
+		let setup = (xcb.xcb_get_setup)(connection);
+		let mut screens = (xcb.xcb_setup_roots_iterator)(setup);
+		let first = screens.data;
+
+		for _ in 0..number {
+			if screens.rem <= 1 {
+				return first;
+			}
+			(xcb.xcb_screen_next)(&mut screens);
+		}
+		screens.data
+	}
+}
+
+fn xcb_screen(connection: *mut c_void, xcb: &Xcb, number: i32) -> XcbScreen {
+	unsafe { (*xcb_screen_data(connection, xcb, number)).clone() }
+}
+
+/// Get the depth, visual ID and class of every visual on screen `number`.
+fn xcb_visuals(connection: *mut c_void, xcb: &Xcb, number: i32)
+	-> Vec<(u8, u32, u8)>
+{
+	let mut visuals = vec![];
+
+	unsafe {
+		let screen = xcb_screen_data(connection, xcb, number);
+		let mut depths = (xcb.xcb_screen_allowed_depths_iterator)(screen);
+
+		while depths.rem > 0 {
+			let depth = &*depths.data;
+			let mut types = (xcb.xcb_depth_visuals_iterator)(depth);
+
+			while types.rem > 0 {
+				let visual = &*types.data;
+
+				visuals.push((depth.depth, visual.visual_id,
+					visual.class));
+				(xcb.xcb_visualtype_next)(&mut types);
+			}
+
+			(xcb.xcb_depth_next)(&mut depths);
+		}
+	}
+
+	visuals
 }
 
+/// Create the window, returns the window and it's colormap (or 0).
 fn xcb_window(connection: *mut c_void, xcb: &Xcb, screen: &mut XcbScreen,
-	v: Option<i32>) -> u32
+	number: i32, v: Option<i32>, parent: Option<u32>,
+	builder: &::WindowBuilder) -> (u32, u32)
 {
+	// Keys, buttons, enter and leave, motion, exposure, visibility,
+	// structure and focus.
+	const EVENT_MASK: u32 = 0b01000111000000001111111;
+
 	let atom1 = get_atom(connection, xcb, b"_MOTIF_WM_HINTS");
-	let atom2 = get_atom(connection, xcb, b"_NET_WM_STATE");
-	let atom3 = get_atom(connection, xcb, b"_NET_WM_STATE_MAXIMIZED_VERT");
-	let atom4 = get_atom(connection, xcb, b"_NET_WM_STATE_MAXIMIZED_HORZ");
-	let atom5 = get_atom(connection, xcb, b"WM_PROTOCOLS");
-	let atom6 = get_atom(connection, xcb, b"WM_DELETE_WINDOW");
+	let atom2 = get_atom(connection, xcb, b"WM_PROTOCOLS");
+	let atom3 = get_atom(connection, xcb, b"WM_DELETE_WINDOW");
 	let window = unsafe { (xcb.xcb_generate_id)(connection) };
-	let mut value_list = [ 0b01000100000000001101111 ];
-	if let Some(v) = v {
-		screen.root_visual = unsafe { ::std::mem::transmute(v) };
+	let visuals = xcb_visuals(connection, xcb, number);
+
+	// EGL picks the visual, otherwise find one with an alpha channel.
+	let visual = match v {
+		Some(v) => Some(v as u32),
+		None if builder.transparent => {
+			let argb = visuals.iter()
+				.find(|v| v.0 == 32 && v.2 == TRUE_COLOR)
+				.map(|v| v.1);
+
+			if argb.is_none() {
+				eprintln!("awi: no 32-bit visual, window won't \
+					be transparent");
+			}
+			argb
+		}
+		None => None,
+	};
+	if let Some(visual) = visual {
+		screen.root_visual = visual;
 	}
+	let depth = visuals.iter().find(|v| v.1 == screen.root_visual)
+		.map(|v| v.0).unwrap_or(screen.root_depth);
+
+	// A window that's not the same depth as it's parent needs it's own
+	// colormap and border pixel, or creating it fails.
+	let colormap = if depth != screen.root_depth {
+		unsafe {
+			let colormap = (xcb.xcb_generate_id)(connection);
+			(xcb.xcb_create_colormap)(connection, 0, colormap,
+				screen.root, screen.root_visual);
+			colormap
+		}
+	} else {
+		0
+	};
+	// BACK_PIXEL | BORDER_PIXEL | EVENT_MASK | COLORMAP or EVENT_MASK
+	let (value_mask, mut value_list) = if colormap != 0 {
+		(2 | 8 | 2048 | 8192, vec![0, 0, EVENT_MASK, colormap])
+	} else {
+		(2048, vec![EVENT_MASK])
+	};
+
 	unsafe {
 		(xcb.xcb_create_window)(
-			connection, 0, window, screen.root, 0, 0,
+			connection, depth, window,
+			parent.unwrap_or(screen.root), 0, 0,
 			screen.width_in_pixels, screen.height_in_pixels, 0, 1,
-			screen.root_visual, 2048, &mut value_list[0]
+			screen.root_visual, value_mask, value_list.as_mut_ptr()
 		);
 		(xcb.xcb_change_property)(connection, 0, window, atom1,
 			atom1, 32, 5, &[2u32, 0, 0, 0, 0] as *const _ as *const c_void);
 		(xcb.xcb_change_property)(connection, 0, window, atom2,
-			4, 32, 2, [atom3, atom4].as_ptr() as *const _ as *const c_void);
-		(xcb.xcb_change_property)(connection, 0, window, atom5,
-			4, 32, 1, [atom6].as_ptr() as *const _ as *const c_void);
+			4, 32, 1, [atom3].as_ptr() as *const _ as *const c_void);
+	}
+	if parent.is_some() {
+		// XEmbed version 0, mapped.
+		let info = get_atom(connection, xcb, b"_XEMBED_INFO");
+		unsafe {
+			(xcb.xcb_change_property)(connection, 0, window, info,
+				info, 32, 2, [0u32, 1].as_ptr() as *const c_void);
+		}
+	} else {
+		xcb_window_hints(connection, xcb, window, builder);
+	}
+	unsafe {
 		(xcb.xcb_map_window)(connection, window);
 		(xcb.xcb_flush)(connection);
 	}
-	window
+	(window, colormap)
+}
+
+/// Get the foreign parent window to embed in, if any, and make the window
+/// fill it.
+fn xcb_parent(connection: *mut c_void, xcb: &Xcb, screen: &mut XcbScreen,
+	builder: &::WindowBuilder) -> Option<u32>
+{
+	match builder.parent {
+		Some(::WindowConnection::Xcb(_, parent, _)) => {
+			match xcb_geometry(connection, xcb, parent) {
+				Some((depth, wh)) => {
+					screen.root_depth = depth;
+					screen.width_in_pixels = wh.0;
+					screen.height_in_pixels = wh.1;
+					Some(parent)
+				}
+				None => {
+					eprintln!("awi: parent window doesn't \
+						exist, not embedding");
+					None
+				}
+			}
+		}
+		Some(_) => {
+			eprintln!("awi: parent isn't an X11 window, not \
+				embedding");
+			None
+		}
+		None => None,
+	}
+}
+
+/// Get the depth and size of a window, `None` if it doesn't exist.
+fn xcb_geometry(connection: *mut c_void, xcb: &Xcb, window: u32)
+	-> Option<(u8, (u16, u16))>
+{
+	let reply = unsafe {
+		let cookie = (xcb.xcb_get_geometry)(connection, window);
+		(xcb.xcb_get_geometry_reply)(connection, cookie, null_mut())
+	};
+
+	if reply.is_null() {
+		return None;
+	}
+
+	unsafe {
+		let geometry = ((*reply).depth,
+			((*reply).width, (*reply).height));
+		libc::free(reply as *mut libc::c_void);
+		Some(geometry)
+	}
+}
+
+/// Set the window type, state and transient-for hints before mapping.
+fn xcb_window_hints(connection: *mut c_void, xcb: &Xcb, window: u32,
+	builder: &::WindowBuilder)
+{
+	use WindowType;
+
+	const ATOM: u32 = 4;
+	const WINDOW: u32 = 33;
+	const WM_TRANSIENT_FOR: u32 = 68;
+
+	let window_type = get_atom(connection, xcb, b"_NET_WM_WINDOW_TYPE");
+	let state = get_atom(connection, xcb, b"_NET_WM_STATE");
+
+	let type_name: &[u8] = match builder.window_type {
+		WindowType::Normal => b"_NET_WM_WINDOW_TYPE_NORMAL",
+		WindowType::Dialog => b"_NET_WM_WINDOW_TYPE_DIALOG",
+		WindowType::Utility => b"_NET_WM_WINDOW_TYPE_UTILITY",
+		WindowType::Splash => b"_NET_WM_WINDOW_TYPE_SPLASH",
+		WindowType::Tooltip => b"_NET_WM_WINDOW_TYPE_TOOLTIP",
+		WindowType::Dock => b"_NET_WM_WINDOW_TYPE_DOCK",
+	};
+	let type_atom = get_atom(connection, xcb, type_name);
+
+	let mut states = vec![];
+	if builder.window_type == WindowType::Normal {
+		states.push(get_atom(connection, xcb,
+			b"_NET_WM_STATE_MAXIMIZED_VERT"));
+		states.push(get_atom(connection, xcb,
+			b"_NET_WM_STATE_MAXIMIZED_HORZ"));
+	}
+	if builder.above {
+		states.push(get_atom(connection, xcb, b"_NET_WM_STATE_ABOVE"));
+	}
+	if builder.skip_taskbar {
+		states.push(get_atom(connection, xcb,
+			b"_NET_WM_STATE_SKIP_TASKBAR"));
+	}
+	if builder.skip_pager {
+		states.push(get_atom(connection, xcb,
+			b"_NET_WM_STATE_SKIP_PAGER"));
+	}
+
+	unsafe {
+		(xcb.xcb_change_property)(connection, 0, window, window_type,
+			ATOM, 32, 1, &type_atom as *const _ as *const c_void);
+		(xcb.xcb_change_property)(connection, 0, window, state,
+			ATOM, 32, states.len() as u32,
+			states.as_ptr() as *const c_void);
+	}
+
+	match builder.transient_for {
+		Some(::WindowConnection::Xcb(_, parent, _)) => unsafe {
+			(xcb.xcb_change_property)(connection, 0, window,
+				WM_TRANSIENT_FOR, WINDOW, 32, 1,
+				&parent as *const _ as *const c_void);
+		},
+		Some(_) => eprintln!("awi: transient_for() parent isn't an \
+			X11 window, ignoring"),
+		None => {}
+	}
 }
 
 fn get_atom(connection: *mut c_void, xcb: &Xcb, name: &[u8]) -> u32 {
@@ -250,6 +1204,133 @@ fn get_atom(connection: *mut c_void, xcb: &Xcb, name: &[u8]) -> u32 {
 	atom
 }
 
+/// Get the `_XSETTINGS_SETTINGS` property from the XSettings manager, or
+/// nothing if there isn't one.
+fn xsettings(connection: *mut c_void, xcb: &Xcb) -> Vec<u8> {
+	let selection = get_atom(connection, xcb, b"_XSETTINGS_S0");
+	let property = get_atom(connection, xcb, b"_XSETTINGS_SETTINGS");
+
+	let owner = unsafe {
+		let cookie = (xcb.xcb_get_selection_owner)(connection,
+			selection);
+		let reply = (xcb.xcb_get_selection_owner_reply)(connection,
+			cookie, null_mut());
+		if reply.is_null() {
+			return Vec::new();
+		}
+		let owner = (*reply).owner;
+		libc::free(reply as *mut libc::c_void);
+		owner
+	};
+	if owner == 0 {
+		return Vec::new();
+	}
+
+	unsafe {
+		// Any type, up to 64K of 32-bit words.
+		let cookie = (xcb.xcb_get_property)(connection, 0, owner,
+			property, 0, 0, 0x10000);
+		let reply = (xcb.xcb_get_property_reply)(connection, cookie,
+			null_mut());
+		if reply.is_null() {
+			return Vec::new();
+		}
+		let value = (xcb.xcb_get_property_value)(reply);
+		let length = (xcb.xcb_get_property_value_length)(reply);
+		let data = if value.is_null() || length <= 0 {
+			Vec::new()
+		} else {
+			::std::slice::from_raw_parts(value, length as usize)
+				.to_vec()
+		};
+		libc::free(reply);
+		data
+	}
+}
+
+/// Get the integer settings from XSettings data, by name.
+fn xsettings_ints(data: &[u8]) -> Vec<(&[u8], i32)> {
+	let mut ints = Vec::new();
+
+	if data.len() < 12 {
+		return ints;
+	}
+
+	// Byte order of the data: 0 is little endian, 1 is big endian.
+	let big = data[0] == 1;
+	let u16_at = |i: usize| {
+		let bytes = [data[i], data[i + 1]];
+		if big { u16::from_be_bytes(bytes) }
+		else { u16::from_le_bytes(bytes) }
+	};
+	let u32_at = |i: usize| {
+		let bytes = [data[i], data[i + 1], data[i + 2], data[i + 3]];
+		if big { u32::from_be_bytes(bytes) }
+		else { u32::from_le_bytes(bytes) }
+	};
+	// Lengths are padded to 4 bytes.
+	let pad = |n: usize| (n + 3) & !3;
+
+	let count = u32_at(8);
+	let mut i = 12;
+
+	for _ in 0..count {
+		// Type, unused byte, name length, name, and last-changed serial.
+		if i + 4 > data.len() {
+			break;
+		}
+		let kind = data[i];
+		let name_len = u16_at(i + 2) as usize;
+		let name = i + 4;
+		let value = name + pad(name_len) + 4;
+		if value > data.len() {
+			break;
+		}
+		let name = &data[name..name + name_len];
+
+		i = match kind {
+			// Integer.
+			0 if value + 4 <= data.len() => {
+				ints.push((name, u32_at(value) as i32));
+				value + 4
+			}
+			// String.
+			1 if value + 4 <= data.len() => {
+				value + 4 + pad(u32_at(value) as usize)
+			}
+			// Color: 4 u16s.
+			2 => value + 8,
+			_ => break,
+		};
+	}
+
+	ints
+}
+
+/// Get the position of the window's top-left corner relative to the root
+/// window.
+fn xcb_root_xy(connection: *mut c_void, xcb: &Xcb, window: u32, root: u32)
+	-> (i32, i32)
+{
+	let cookie = unsafe {
+		(xcb.xcb_translate_coordinates)(connection, window, root, 0, 0)
+	};
+	let reply = unsafe {
+		(xcb.xcb_translate_coordinates_reply)(connection, cookie,
+			null_mut())
+	};
+
+	if reply.is_null() {
+		return (0, 0);
+	}
+
+	unsafe {
+		let xy = ((*reply).dst_x as i32, (*reply).dst_y as i32);
+		libc::free(reply as *mut libc::c_void);
+		xy
+	}
+}
+
 fn xkb_keyboard(connection: *mut c_void, xkb: &XkbCommonX11)
 	-> (*mut c_void, *mut c_void, *mut c_void)
 {
@@ -277,29 +1358,108 @@ fn xkb_keyboard(connection: *mut c_void, xkb: &XkbCommonX11)
 	(state, keymap, context)
 }
 
-fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
-	xkb: &XkbCommonX11, state: *mut c_void, queue: &mut ::input::InputQueue,
-	wh: &mut (u16, u16), keyboard: &mut ::Keyboard) -> bool
+/// Get the next event, and all of it if it's longer than 32 bytes (an
+/// XInput 2 event).
+fn xcb_next_event(connection: *mut c_void, xcb: &Xcb)
+	-> Option<(XcbGenericEvent, Vec<u8>)>
 {
-	use std::string::String;
-
 	extern { fn free(event: *mut XcbGenericEvent) -> (); }
 
 	let event = unsafe { (xcb.xcb_poll_for_event)(connection) };
-	let event = if event.is_null() {
-		return false;
+	if event.is_null() {
+		None
 	} else {
 		unsafe {
 			let r_event = (*event).clone();
+			// `length` 4-byte units follow the 32 bytes, and XCB's
+			// `full_sequence`.
+			let data = if r_event.response_type & !SYNTHETIC
+				== GE_GENERIC
+			{
+				let length = r_event.timestamp as usize;
+
+				::std::slice::from_raw_parts(event as *const u8,
+					36 + length * 4).to_vec()
+			} else {
+				Vec::new()
+			};
 			free(event);
-			r_event
+			Some((r_event, data))
+		}
+	}
+}
+
+/// Ask for XInput 2 touch events, and touchpad gesture events (XInput 2.4)
+/// if the X server has them.  Returns the extension, and it's major opcode.
+fn xinput_select(connection: *mut c_void, xcb: &Xcb, window: u32)
+	-> Option<(XcbInput, u8)>
+{
+	const NAME: &[u8] = b"XInputExtension";
+	const ALL_MASTER_DEVICES: u16 = 1;
+	// TouchBegin, TouchUpdate and TouchEnd.
+	const TOUCH: u32 = 0b111 << 18;
+	// GesturePinch and GestureSwipe Begin, Update and End.
+	const GESTURE: [u32; 2] = [0b11111 << 27, 0b1];
+
+	let xinput = XcbInput::new().ok()?;
+
+	let opcode = unsafe {
+		let cookie = (xcb.xcb_query_extension)(connection,
+			NAME.len() as u16, NAME.as_ptr());
+		let reply = (xcb.xcb_query_extension_reply)(connection, cookie,
+			null_mut());
+		if reply.is_null() {
+			return None;
+		}
+		let opcode = if (*reply).present != 0 {
+			Some((*reply).major_opcode)
+		} else {
+			None
+		};
+		libc::free(reply as *mut libc::c_void);
+		opcode?
+	};
+
+	// The X server only sends events from versions the client supports.
+	let version = unsafe {
+		let cookie = (xinput.xcb_input_xi_query_version)(connection,
+			2, 4);
+		let reply = (xinput.xcb_input_xi_query_version_reply)(
+			connection, cookie, null_mut());
+		if reply.is_null() {
+			return None;
 		}
+		let version = ((*reply).major_version, (*reply).minor_version);
+		libc::free(reply as *mut libc::c_void);
+		version
 	};
 
+	let mask = match version {
+		(2, minor) if minor >= 4 => [TOUCH | GESTURE[0], GESTURE[1]],
+		(2, minor) if minor >= 2 => [TOUCH, 0],
+		_ => return None,
+	};
+	let mask = XcbInputEventMask {
+		deviceid: ALL_MASTER_DEVICES, mask_len: 2, mask,
+	};
+
+	unsafe {
+		(xinput.xcb_input_xi_select_events)(connection, window, 1,
+			&mask);
+	}
+
+	Some((xinput, opcode))
+}
+
+fn xcb_handle_event(event: XcbGenericEvent, xkb: &XkbCommonX11,
+	state: *mut c_void, queue: &mut ::input::InputQueue,
+	wh: &mut (u16, u16), keyboard: &mut ::Keyboard)
+{
+	use std::string::String;
+
 	let response_type = event.response_type;
 	let detail = event.detail;
 	let event_xy = (event.event_x, event.event_y);
-	let root_xy = (event.root_x as u16, event.root_y as u16); // i16 -> u16
 
 	let string = match response_type {
 		2 => {
@@ -328,11 +1488,10 @@ fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
 	const BUTTON_DOWN: u8 = 4;
 	const BUTTON_UP: u8 = 5;
 	const CURSOR_MOVE: u8 = 6;
+	const CURSOR_ENTER: u8 = 7;
 	const CURSOR_LEAVE: u8 = 8;
 	const GAIN_FOCUS: u8 = 9;
 	const LOSE_FOCUS: u8 = 10;
-	const WINDOW_RESIZE: u8 = 22;
-	const WINDOW_SELECT: u8 = 31;
 	const WINDOW_CLOSE: u8 = 128 | 33;
 
 	match response_type {
@@ -352,7 +1511,7 @@ fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
 			5 => queue.scroll(*wh, event_xy, (0.0, 1.0)),
 			6 => queue.scroll(*wh, event_xy, (-1.0, 0.0)),
 			7 => queue.scroll(*wh, event_xy, (1.0, 0.0)),
-			uc => panic!("awi: Unknown Click {}!", uc)
+			_ => {} // Back, forward, and other buttons.
 		},
 		BUTTON_UP => match detail {
 			1 => queue.left_button_release(*wh, event_xy),
@@ -360,21 +1519,18 @@ fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
 			3 => queue.right_button_release(*wh, event_xy),
 			_ => {},
 		},
-		CURSOR_MOVE => queue.cursor_move(*wh, event_xy),
+		// Entering is a move, so hovering starts without moving.
+		CURSOR_MOVE | CURSOR_ENTER => queue.cursor_move(*wh, event_xy),
 		CURSOR_LEAVE => queue.cursor_leave(),
 		GAIN_FOCUS => queue.resume(),
 		LOSE_FOCUS => queue.pause(),
-		WINDOW_RESIZE => queue.resize(wh, root_xy),
-		WINDOW_SELECT => println!("!SELECT!"),
 		WINDOW_CLOSE => { queue.exit() }
-		a => { println!("a {}", a); } // ignore all other messages
+		_ => {} // ignore all other messages
 	}
 
 	if let Some(string) = string {
 		queue.text(string);
 	}
-
-	true
 }
 
 fn xkb_state_update_key(xkb: &XkbCommonX11, state: *mut c_void,
@@ -413,79 +1569,139 @@ fn xkb_state_key_get_utf8(xkb: &XkbCommonX11, state: *mut c_void,
 }
 
 // Keycode translator
-fn key(physical_key: u8) -> Option<u8> {
+fn key(physical_key: u8) -> Option<Key> {
 	Some(match physical_key {
-		49 => keyboard::EXT_BACKTICK,
-		86 => keyboard::EXT_PLUS,
-		63 => keyboard::EXT_ASTERISK,
-		61 | 106 => keyboard::SLASH,
-		36 | 104 => keyboard::ENTER,
-		10 | 87 => keyboard::NUM1,
-		11 | 88 => keyboard::NUM2,
-		12 | 89 => keyboard::NUM3,
-		13 | 83 => keyboard::NUM4,
-		14 | 84 => keyboard::NUM5,
-		15 | 85 => keyboard::NUM6,
-		16 | 79 => keyboard::NUM7,
-		17 | 80 => keyboard::NUM8,
-		18 | 81 => keyboard::NUM9,
-		19 | 90 => keyboard::NUM0,
-		60 | 91 => keyboard::PERIOD,
-		20 | 82 => keyboard::MINUS,
-		21 => keyboard::EQUAL_SIGN,
-		22 => keyboard::BACKSPACE,
-		23 => keyboard::TAB,
-		38 => keyboard::A,
-		56 => keyboard::B,
-		54 => keyboard::C,
-		40 => keyboard::D,
-		26 => keyboard::E,
-		41 => keyboard::F,
-		42 => keyboard::G,
-		43 => keyboard::H,
-		31 => keyboard::I,
-		44 => keyboard::J,
-		45 => keyboard::K,
-		46 => keyboard::L,
-		58 => keyboard::M,
-		57 => keyboard::N,
-		32 => keyboard::O,
-		33 => keyboard::P,
-		24 => keyboard::Q,
-		27 => keyboard::R,
-		39 => keyboard::S,
-		28 => keyboard::T,
-		30 => keyboard::U,
-		55 => keyboard::V,
-		25 => keyboard::W,
-		53 => keyboard::X,
-		29 => keyboard::Y,
-		52 => keyboard::Z,
-		34 => keyboard::BRACKET_OPEN,
-		35 => keyboard::BRACKET_CLOSE,
-		37 => keyboard::LCTRL,
-		105 => keyboard::RCTRL,
-		50 => keyboard::LSHIFT,
-		62 => keyboard::RSHIFT,
-		64 => keyboard::ALT,
-		108 => keyboard::EXT_ALT_GR,
-		66 => keyboard::COMPOSE,
-		47 => keyboard::SEMICOLON,
-		48 => keyboard::APOSTROPHE,
-		51 => keyboard::BACKSLASH,
-		59 => keyboard::COMMA,
-		65 => keyboard::SPACE,
-		77 => keyboard::EXT_NUM_LOCK,
-		110 => keyboard::EXT_HOME,
-		115 => keyboard::EXT_END,
-		112 => keyboard::EXT_PAGE_UP,
-		117 => keyboard::EXT_PAGE_DOWN,
-		118 => keyboard::EXT_INSERT,
-		119 => keyboard::EXT_DELETE,
-		111 => keyboard::UP,
-		113 => keyboard::LEFT,
-		114 => keyboard::RIGHT,
-		116 => keyboard::DOWN,
+		49 => Key::Backtick,
+		86 => Key::Plus,
+		63 => Key::Asterisk,
+		61 | 106 => Key::Slash,
+		36 | 104 => Key::Enter,
+		10 | 87 => Key::Num1,
+		11 | 88 => Key::Num2,
+		12 | 89 => Key::Num3,
+		13 | 83 => Key::Num4,
+		14 | 84 => Key::Num5,
+		15 | 85 => Key::Num6,
+		16 | 79 => Key::Num7,
+		17 | 80 => Key::Num8,
+		18 | 81 => Key::Num9,
+		19 | 90 => Key::Num0,
+		60 | 91 => Key::Period,
+		20 | 82 => Key::Minus,
+		21 => Key::EqualSign,
+		22 => Key::Backspace,
+		23 => Key::Tab,
+		38 => Key::A,
+		56 => Key::B,
+		54 => Key::C,
+		40 => Key::D,
+		26 => Key::E,
+		41 => Key::F,
+		42 => Key::G,
+		43 => Key::H,
+		31 => Key::I,
+		44 => Key::J,
+		45 => Key::K,
+		46 => Key::L,
+		58 => Key::M,
+		57 => Key::N,
+		32 => Key::O,
+		33 => Key::P,
+		24 => Key::Q,
+		27 => Key::R,
+		39 => Key::S,
+		28 => Key::T,
+		30 => Key::U,
+		55 => Key::V,
+		25 => Key::W,
+		53 => Key::X,
+		29 => Key::Y,
+		52 => Key::Z,
+		34 => Key::BracketOpen,
+		35 => Key::BracketClose,
+		37 => Key::LCtrl,
+		105 => Key::RCtrl,
+		50 => Key::LShift,
+		62 => Key::RShift,
+		64 => Key::Alt,
+		108 => Key::AltGr,
+		66 => Key::Compose,
+		47 => Key::Semicolon,
+		48 => Key::Apostrophe,
+		51 => Key::Backslash,
+		59 => Key::Comma,
+		65 => Key::Space,
+		77 => Key::NumLock,
+		110 => Key::Home,
+		115 => Key::End,
+		112 => Key::PageUp,
+		117 => Key::PageDown,
+		118 => Key::Insert,
+		119 => Key::Delete,
+		111 => Key::Up,
+		113 => Key::Left,
+		114 => Key::Right,
+		116 => Key::Down,
 		_ => return None,
 	} )
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Little endian: an integer, a string, a color and another integer.
+	fn settings() -> Vec<u8> {
+		[
+			// Byte order, unused, serial and count.
+			&[0, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0][..],
+			// Integer: name padded from 19 to 20 bytes.
+			&[0, 0, 19, 0], b"Net/DoubleClickTime\0",
+			&[7, 0, 0, 0], &[0x90, 0x01, 0, 0],
+			// String: name padded to 16, value padded to 8.
+			&[1, 0, 13, 0], b"Net/ThemeName\0\0\0",
+			&[7, 0, 0, 0], &[5, 0, 0, 0], b"Theme\0\0\0",
+			// Color: 4 u16s.
+			&[2, 0, 9, 0], b"Gtk/Color\0\0\0",
+			&[7, 0, 0, 0], &[0xFF, 0xFF, 0, 0, 0, 0, 0xFF, 0xFF],
+			// Integer: name without padding.
+			&[0, 0, 20, 0], b"Net/DndDragThreshold",
+			&[7, 0, 0, 0], &[0xFF, 0xFF, 0xFF, 0xFF],
+		].concat()
+	}
+
+	#[test]
+	fn xsettings() {
+		let all = settings();
+		let big = [
+			&[1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1][..],
+			&[0, 0, 0, 4], b"Test", &[0, 0, 0, 7],
+			&[0, 0, 1, 0],
+		].concat();
+		let mut unknown = settings();
+		unknown[44] = 3;
+
+		let click: (&[u8], i32) = (b"Net/DoubleClickTime", 400);
+		let drag: (&[u8], i32) = (b"Net/DndDragThreshold", -1);
+
+		for &(data, ref expected) in &[
+			(&all[..], vec![click, drag]),
+			(&big[..], vec![(&b"Test"[..], 256)]),
+			// Unknown types stop parsing.
+			(&unknown[..], vec![click]),
+			// Cut off in the header, and in each part of a setting.
+			(&[][..], vec![]),
+			(&all[..11], vec![]),
+			(&all[..14], vec![]),
+			(&all[..39], vec![]),
+			(&all[..43], vec![]),
+			(&all[..44], vec![click]),
+			(&all[..75], vec![click]),
+			(&all[..108], vec![click]),
+			(&all[..139], vec![click]),
+		] {
+			assert_eq!(&xsettings_ints(data), expected, "{}",
+				data.len());
+		}
+	}
+}
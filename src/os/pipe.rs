@@ -0,0 +1,65 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Waking up `wait()` on unix, shared by the unix backends.
+
+use std::sync::Arc;
+
+use libc;
+
+/// A pipe, written to to interrupt `Window::wait()`.
+pub(crate) struct Pipe {
+	pub(crate) read: i32,
+	write: i32,
+}
+
+impl Pipe {
+	pub(crate) fn new() -> Pipe {
+		let mut fds = [0i32; 2];
+
+		if unsafe {
+			libc::pipe2(fds.as_mut_ptr(),
+				libc::O_NONBLOCK | libc::O_CLOEXEC)
+		} != 0 {
+			eprintln!("ERROR: couldn't create pipe, aborting...");
+			::std::process::abort();
+		}
+
+		Pipe { read: fds[0], write: fds[1] }
+	}
+
+	/// Read everything out of the pipe.
+	pub(crate) fn drain(&self) {
+		let mut buffer = [0u8; 64];
+
+		while unsafe {
+			libc::read(self.read,
+				buffer.as_mut_ptr() as *mut libc::c_void,
+				buffer.len())
+		} > 0 {}
+	}
+}
+
+impl Drop for Pipe {
+	fn drop(&mut self) {
+		unsafe {
+			libc::close(self.read);
+			libc::close(self.write);
+		}
+	}
+}
+
+/// Handle to wake up a window that's waiting for events.
+#[derive(Clone)]
+pub struct Wake(pub(crate) Arc<Pipe>);
+
+impl Wake {
+	pub fn wake(&self) {
+		unsafe {
+			libc::write((self.0).write,
+				&1u8 as *const u8 as *const libc::c_void, 1);
+		}
+	}
+}
@@ -0,0 +1,995 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Input from `/dev/input/event*`, without a display server.
+
+use Key;
+use c_void;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::mem;
+use std::ptr::null;
+
+use libc;
+
+const INPUT_DIR: &str = "/dev/input";
+
+// Event types
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+
+const SYN_REPORT: u16 = 0x00;
+
+// Relative axes
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+
+// Absolute axes
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_MT_SLOT: u16 = 0x2f;
+const ABS_MT_POSITION_X: u16 = 0x35;
+const ABS_MT_POSITION_Y: u16 = 0x36;
+const ABS_MT_TRACKING_ID: u16 = 0x39;
+
+// Input properties
+const INPUT_PROP_DIRECT: u8 = 0x01;
+
+// Most fingers tracked on a multi-touch screen.
+const FINGERS: usize = 10;
+
+// Buttons (keys below `BTN_MISC` are keyboard keys)
+const BTN_MISC: u16 = 0x100;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BTN_TOOL_PEN: u16 = 0x140;
+const BTN_TOUCH: u16 = 0x14a;
+const BTN_STYLUS: u16 = 0x14b;
+const BTN_STYLUS2: u16 = 0x14c;
+
+const KEY_ESC: u16 = 1;
+const KEY_ENTER: u16 = 28;
+const KEY_KPENTER: u16 = 96;
+const KEY_F1: u16 = 59;
+const KEY_F10: u16 = 68;
+const KEY_F11: u16 = 87;
+const KEY_F12: u16 = 88;
+
+#[repr(C)]
+struct AbsInfo {
+	value: i32,
+	minimum: i32,
+	maximum: i32,
+	fuzz: i32,
+	flat: i32,
+	resolution: i32,
+}
+
+/// `EVIOCGABS(axis)`, get the range of an absolute axis.
+fn eviocgabs(axis: u16) -> libc::c_ulong {
+	(2 << 30) | ((mem::size_of::<AbsInfo>() as libc::c_ulong) << 16)
+		| ((b'E' as libc::c_ulong) << 8) | (0x40 + axis as libc::c_ulong)
+}
+
+/// `EVIOCGPROP(len)`, get the input properties bitmask.
+fn eviocgprop(len: usize) -> libc::c_ulong {
+	(2 << 30) | ((len as libc::c_ulong) << 16)
+		| ((b'E' as libc::c_ulong) << 8) | 0x09
+}
+
+/// A finger on a multi-touch screen.
+#[derive(Copy, Clone, Default)]
+struct Finger {
+	// Position, in pixels.
+	xy: (i16, i16),
+	// Touching: Some(just touched), or None.
+	touch: Option<bool>,
+	// Whether it moved since the last `SYN_REPORT`.
+	moved: bool,
+	// Whether it was lifted since the last `SYN_REPORT`.
+	released: bool,
+}
+
+/// An open input device.
+struct Device {
+	fd: i32,
+	name: String,
+	// Ranges of `ABS_X`, `ABS_Y`, `ABS_MT_POSITION_X` and
+	// `ABS_MT_POSITION_Y`, if it's a touchscreen or tablet.
+	range: [(i32, i32); 4],
+	// Whether it's a multi-touch screen (`ABS_MT_POSITION_X` has a range).
+	multitouch: bool,
+	// Whether the absolute axes are on a screen (`INPUT_PROP_DIRECT`), so
+	// it isn't a touchpad.
+	direct: bool,
+	// Touchpad: where the finger was at the last `SYN_REPORT`.
+	last: Option<(i16, i16)>,
+	// Multi-touch slot that `ABS_MT_*` events are for, and each slot.
+	slot: usize,
+	fingers: [Finger; FINGERS],
+	// Position on the absolute axes, in pixels.
+	abs: (i16, i16),
+	// Whether the absolute position changed since the last `SYN_REPORT`.
+	abs_moved: bool,
+	// Relative motion since the last `SYN_REPORT`.
+	rel: (i32, i32),
+	// Whether it's a tablet (has a pen), rather than a touchscreen.
+	pen: bool,
+	// Touching: Some(just touched), or None.
+	touch: Option<bool>,
+	// Whether a touch was released since the last `SYN_REPORT`.
+	released: bool,
+}
+
+impl Device {
+	fn open(name: &str) -> Option<Device> {
+		let path = CString::new(format!("{}/{}", INPUT_DIR, name))
+			.unwrap();
+		let fd = unsafe {
+			libc::open(path.as_ptr(), libc::O_RDONLY
+				| libc::O_NONBLOCK | libc::O_CLOEXEC)
+		};
+
+		// Not an input device, or not allowed to read it.
+		if fd < 0 {
+			return None;
+		}
+
+		let mut range = [(0, 0); 4];
+		let axes = [ABS_X, ABS_Y, ABS_MT_POSITION_X, ABS_MT_POSITION_Y];
+		for (axis, range) in axes.iter().zip(range.iter_mut()) {
+			let mut info: AbsInfo = unsafe { mem::zeroed() };
+
+			if unsafe { libc::ioctl(fd, eviocgabs(*axis), &mut info) }
+				>= 0
+			{
+				*range = (info.minimum, info.maximum);
+			}
+		}
+
+		// Old kernels don't have properties, assume a touchscreen.
+		let mut props = [0u8; 4];
+		let direct = unsafe {
+			libc::ioctl(fd, eviocgprop(props.len()),
+				props.as_mut_ptr())
+		} < 0 || props[0] & (1 << INPUT_PROP_DIRECT) != 0;
+
+		Some(Device {
+			fd, name: name.to_string(), range,
+			multitouch: direct && range[2].1 > range[2].0,
+			direct, last: None, slot: 0,
+			fingers: [Finger::default(); FINGERS], abs: (0, 0),
+			abs_moved: false, rel: (0, 0), pen: false, touch: None,
+			released: false,
+		})
+	}
+
+	/// A multi-touch event, for the current slot.
+	fn finger(&mut self, code: u16, value: i32, wh: (u16, u16)) {
+		let x = self.scale(2, value, wh.0);
+		let y = self.scale(3, value, wh.1);
+		let finger = match self.fingers.get_mut(self.slot) {
+			Some(finger) => finger,
+			None => return,
+		};
+
+		match code {
+			// -1 when the finger is lifted.
+			ABS_MT_TRACKING_ID if value < 0 => {
+				finger.touch = None;
+				finger.released = true;
+			}
+			ABS_MT_TRACKING_ID => finger.touch = Some(true),
+			ABS_MT_POSITION_X => {
+				finger.xy.0 = x;
+				finger.moved = true;
+			}
+			ABS_MT_POSITION_Y => {
+				finger.xy.1 = y;
+				finger.moved = true;
+			}
+			_ => {}
+		}
+	}
+
+	/// Scale an absolute axis value to pixels.
+	fn scale(&self, axis: usize, value: i32, size: u16) -> i16 {
+		let (min, max) = self.range[axis];
+
+		if max <= min {
+			return value as i16;
+		}
+
+		((value - min) as i64 * size as i64 / (max - min + 1) as i64)
+			as i16
+	}
+}
+
+impl Drop for Device {
+	fn drop(&mut self) {
+		unsafe { libc::close(self.fd); }
+	}
+}
+
+/// Keyboard layout from libxkbcommon, for text input.
+struct Xkb {
+	xkb: XkbCommon,
+	context: *mut c_void,
+	keymap: *mut c_void,
+	state: *mut c_void,
+}
+
+impl Xkb {
+	/// Load the layout from `XKB_DEFAULT_*` environment variables, or the
+	/// system default.
+	fn new() -> Option<Xkb> {
+		let xkb = XkbCommon::new().ok()?;
+		let context = unsafe { (xkb.xkb_context_new)(0) };
+
+		if context.is_null() {
+			return None;
+		}
+
+		let keymap = unsafe {
+			(xkb.xkb_keymap_new_from_names)(context, null(), 0)
+		};
+
+		if keymap.is_null() {
+			unsafe { (xkb.xkb_context_unref)(context) };
+			return None;
+		}
+
+		let state = unsafe { (xkb.xkb_state_new)(keymap) };
+
+		Some(Xkb { xkb, context, keymap, state })
+	}
+
+	/// Get the text a key (evdev code) types.
+	fn text(&self, code: u16) -> String {
+		let mut utf8 = [0u8; 16];
+
+		// XKB keycodes are offset by 8 from evdev.
+		unsafe {
+			(self.xkb.xkb_state_key_get_utf8)(self.state,
+				code as u32 + 8, utf8.as_mut_ptr(), utf8.len());
+		}
+
+		match CStr::from_bytes_until_nul(&utf8) {
+			Ok(utf8) => utf8.to_string_lossy().into_owned(),
+			Err(_) => String::new(),
+		}
+	}
+
+	/// Update the modifiers for a key (evdev code) press or release.
+	fn update(&mut self, code: u16, pressed: bool) {
+		unsafe {
+			(self.xkb.xkb_state_update_key)(self.state,
+				code as u32 + 8, pressed as u32);
+		}
+	}
+}
+
+impl Drop for Xkb {
+	fn drop(&mut self) {
+		unsafe {
+			(self.xkb.xkb_state_unref)(self.state);
+			(self.xkb.xkb_keymap_unref)(self.keymap);
+			(self.xkb.xkb_context_unref)(self.context);
+		}
+	}
+}
+
+dl_api!(XkbCommon, "libxkbcommon.so.0",
+	fn xkb_context_new(u32) -> *mut c_void,
+	fn xkb_keymap_new_from_names(*mut c_void, *const c_void, u32)
+		-> *mut c_void,
+	fn xkb_state_new(*mut c_void) -> *mut c_void,
+	fn xkb_state_update_key(*mut c_void, u32, u32) -> u32,
+	fn xkb_state_key_get_utf8(*mut c_void, u32, *mut u8, usize) -> i32,
+	fn xkb_state_unref(*mut c_void) -> (),
+	fn xkb_keymap_unref(*mut c_void) -> (),
+	fn xkb_context_unref(*mut c_void) -> ()
+);
+
+/// All of the input devices, and a watch on `/dev/input` for hotplugging.
+pub(crate) struct Evdev {
+	devices: Vec<Device>,
+	inotify: i32,
+	xkb: Option<Xkb>,
+	// Position of the mouse cursor, in pixels.
+	xy: (i16, i16),
+	// VT to switch to (Ctrl-Alt-F1 to F12), the kernel doesn't see keys.
+	vt: Option<i32>,
+}
+
+impl Evdev {
+	pub(crate) fn new() -> Evdev {
+		let inotify = unsafe {
+			libc::inotify_init1(libc::IN_NONBLOCK
+				| libc::IN_CLOEXEC)
+		};
+
+		if inotify >= 0 {
+			let dir = CString::new(INPUT_DIR).unwrap();
+
+			// Permissions are set after the node is created.
+			unsafe {
+				libc::inotify_add_watch(inotify, dir.as_ptr(),
+					libc::IN_CREATE | libc::IN_ATTRIB);
+			}
+		}
+
+		let mut evdev = Evdev {
+			devices: vec![], inotify, xkb: Xkb::new(), xy: (0, 0),
+			vt: None,
+		};
+
+		if let Ok(entries) = fs::read_dir(INPUT_DIR) {
+			for entry in entries.filter_map(|e| e.ok()) {
+				evdev.add(&entry.file_name().to_string_lossy());
+			}
+		}
+
+		evdev
+	}
+
+	/// Open a newly found device.
+	fn add(&mut self, name: &str) {
+		if !name.starts_with("event")
+			|| self.devices.iter().any(|d| d.name == name)
+		{
+			return;
+		}
+
+		if let Some(device) = Device::open(name) {
+			self.devices.push(device);
+		}
+	}
+
+	/// File descriptors to `poll()` on when waiting for input.
+	pub(crate) fn fds(&self) -> Vec<i32> {
+		let mut fds: Vec<i32> = self.devices.iter().map(|d| d.fd)
+			.collect();
+
+		if self.inotify >= 0 {
+			fds.push(self.inotify);
+		}
+		fds
+	}
+
+	/// Get the VT the user asked to switch to, if any.
+	pub(crate) fn vt(&mut self) -> Option<i32> {
+		self.vt.take()
+	}
+
+	/// Read all pending input, and add it to the queue.
+	pub(crate) fn poll(&mut self, queue: &mut ::input::InputQueue,
+		keyboard: &mut ::Keyboard, wh: (u16, u16))
+	{
+		self.hotplug();
+
+		let mut i = 0;
+		while i < self.devices.len() {
+			if self.read(i, queue, keyboard, wh) {
+				i += 1;
+			} else {
+				// Unplugged.
+				self.devices.remove(i);
+			}
+		}
+	}
+
+	/// Open devices that were plugged in.
+	fn hotplug(&mut self) {
+		if self.inotify < 0 {
+			return;
+		}
+
+		let mut buffer = [0u8; 4096];
+
+		loop {
+			let len = unsafe {
+				libc::read(self.inotify,
+					buffer.as_mut_ptr() as *mut libc::c_void,
+					buffer.len())
+			};
+
+			if len <= 0 {
+				break;
+			}
+
+			let mut offset = 0;
+			while offset < len as usize {
+				let event = unsafe {
+					&*(buffer[offset..].as_ptr()
+						as *const libc::inotify_event)
+				};
+				let name = &buffer[offset
+					+ mem::size_of::<libc::inotify_event>()..]
+					[..event.len as usize];
+
+				if let Ok(name) = CStr::from_bytes_until_nul(name) {
+					self.add(&name.to_string_lossy());
+				}
+
+				offset += mem::size_of::<libc::inotify_event>()
+					+ event.len as usize;
+			}
+		}
+	}
+
+	/// Read a device's events, returns false if it was unplugged.
+	fn read(&mut self, i: usize, queue: &mut ::input::InputQueue,
+		keyboard: &mut ::Keyboard, wh: (u16, u16)) -> bool
+	{
+		let mut events: [libc::input_event; 64]
+			= unsafe { mem::zeroed() };
+
+		loop {
+			let len = unsafe {
+				libc::read(self.devices[i].fd,
+					events.as_mut_ptr() as *mut libc::c_void,
+					mem::size_of_val(&events))
+			};
+
+			if len < 0 {
+				let errno = ::std::io::Error::last_os_error()
+					.raw_os_error();
+
+				return errno == Some(libc::EAGAIN);
+			}
+			if len == 0 {
+				return false;
+			}
+
+			let count = len as usize
+				/ mem::size_of::<libc::input_event>();
+
+			for event in events[..count].iter() {
+				self.event(i, event, queue, keyboard, wh);
+			}
+		}
+	}
+
+	/// Translate one event.
+	fn event(&mut self, i: usize, event: &libc::input_event,
+		queue: &mut ::input::InputQueue, keyboard: &mut ::Keyboard,
+		wh: (u16, u16))
+	{
+		let (code, value) = (event.code, event.value);
+
+		match event.type_ {
+			EV_KEY if code < BTN_MISC => {
+				// 2 is auto-repeat.
+				if let Some(key) = key(code) {
+					if value != 0 {
+						keyboard.press(key);
+					} else {
+						keyboard.release(key);
+					}
+				} else if code == KEY_ESC && value == 1 {
+					queue.exit();
+				}
+
+				if value == 1 && (keyboard.get(Key::LCtrl)
+					|| keyboard.get(Key::RCtrl))
+					&& keyboard.get(Key::Alt)
+				{
+					self.vt = match code {
+						KEY_F1..=KEY_F10 => {
+							Some((code - KEY_F1) as i32 + 1)
+						}
+						KEY_F11 => Some(11),
+						KEY_F12 => Some(12),
+						_ => None,
+					};
+				}
+
+				// Text, including auto-repeat.
+				if value != 0 {
+					if code == KEY_ENTER
+						|| code == KEY_KPENTER
+					{
+						queue.text("\n".to_string());
+					} else if let Some(ref xkb) = self.xkb {
+						let text = xkb.text(code);
+
+						// Ctrl-C and such.
+						if !text.chars()
+							.any(char::is_control)
+						{
+							queue.text(text);
+						}
+					}
+				}
+				if value != 2 {
+					if let Some(ref mut xkb) = self.xkb {
+						xkb.update(code, value == 1);
+					}
+				}
+			}
+			EV_KEY => self.button(i, code, value != 0, queue, wh),
+			EV_REL => {
+				let device = &mut self.devices[i];
+
+				match code {
+					REL_X => device.rel.0 += value,
+					REL_Y => device.rel.1 += value,
+					REL_WHEEL => queue.scroll(wh, self.xy,
+						(0.0, -value as f32)),
+					REL_HWHEEL => queue.scroll(wh, self.xy,
+						(value as f32, 0.0)),
+					_ => {}
+				}
+			}
+			EV_ABS => {
+				let device = &mut self.devices[i];
+
+				match code {
+					ABS_X => {
+						device.abs.0 = device.scale(0,
+							value, wh.0);
+						device.abs_moved = true;
+					}
+					ABS_Y => {
+						device.abs.1 = device.scale(1,
+							value, wh.1);
+						device.abs_moved = true;
+					}
+					ABS_MT_SLOT => {
+						device.slot = value.max(0)
+							as usize
+					}
+					_ => device.finger(code, value, wh),
+				}
+			}
+			EV_SYN if code == SYN_REPORT => self.report(i, queue, wh),
+			_ => {}
+		}
+	}
+
+	/// A mouse, touch or pen button.
+	fn button(&mut self, i: usize, code: u16, pressed: bool,
+		queue: &mut ::input::InputQueue, wh: (u16, u16))
+	{
+		let xy = self.xy;
+		let device = &mut self.devices[i];
+
+		match (code, pressed) {
+			(BTN_LEFT, true) => queue.left_button_press(wh, xy),
+			(BTN_LEFT, false) => queue.left_button_release(wh, xy),
+			(BTN_RIGHT, true) | (BTN_STYLUS, true) => {
+				queue.right_button_press(wh, xy)
+			}
+			(BTN_RIGHT, false) | (BTN_STYLUS, false) => {
+				queue.right_button_release(wh, xy)
+			}
+			(BTN_MIDDLE, true) | (BTN_STYLUS2, true) => {
+				queue.middle_button_press(wh, xy)
+			}
+			(BTN_MIDDLE, false) | (BTN_STYLUS2, false) => {
+				queue.middle_button_release(wh, xy)
+			}
+			(BTN_TOOL_PEN, _) => device.pen = true,
+			// Wait for the position, sent before `SYN_REPORT`.
+			(BTN_TOUCH, true) => device.touch = Some(true),
+			(BTN_TOUCH, false) => {
+				device.touch = None;
+				device.released = true;
+			}
+			_ => {}
+		}
+	}
+
+	/// A set of events from a device is complete.
+	fn report(&mut self, i: usize, queue: &mut ::input::InputQueue,
+		wh: (u16, u16))
+	{
+		let device = &mut self.devices[i];
+
+		// Touchpad, moves the cursor by how far the finger moved.
+		let touchpad = !device.direct && !device.pen
+			&& device.range[0].1 > device.range[0].0;
+		if touchpad {
+			let (abs, last) = (device.abs, device.last);
+
+			if let (Some(_), Some(last)) = (device.touch, last) {
+				device.rel.0 += abs.0 as i32 - last.0 as i32;
+				device.rel.1 += abs.1 as i32 - last.1 as i32;
+			}
+			device.last = device.touch.map(|_| device.abs);
+			device.abs_moved = false;
+			device.released = false;
+		}
+
+		// Mouse
+		if device.rel != (0, 0) {
+			let x = self.xy.0 as i32 + device.rel.0;
+			let y = self.xy.1 as i32 + device.rel.1;
+
+			self.xy = (
+				x.max(0).min(wh.0 as i32 - 1) as i16,
+				y.max(0).min(wh.1 as i32 - 1) as i16,
+			);
+			device.rel = (0, 0);
+			queue.cursor_move(wh, self.xy);
+		}
+		if touchpad {
+			return;
+		}
+
+		// Touchscreen or tablet (multi-touch screens are reported by
+		// finger, below).
+		let touchscreen = !device.pen && !device.multitouch;
+		if device.abs_moved {
+			self.xy = device.abs;
+			device.abs_moved = false;
+
+			match device.touch {
+				Some(true) if touchscreen => {
+					queue.touch_press(0, wh, self.xy);
+					device.touch = Some(false);
+				}
+				Some(false) if touchscreen => {
+					queue.touch_move(0, wh, self.xy)
+				}
+				_ if device.multitouch && !device.pen => {}
+				_ => queue.cursor_move(wh, self.xy),
+			}
+		}
+		if device.touch == Some(true) {
+			device.touch = Some(false);
+			if device.pen {
+				queue.left_button_press(wh, self.xy);
+			} else if touchscreen {
+				queue.touch_press(0, wh, self.xy);
+			}
+		}
+		if device.released {
+			device.released = false;
+			if device.pen {
+				queue.left_button_release(wh, self.xy);
+			} else if touchscreen {
+				queue.touch_release(0, wh, self.xy);
+			}
+		}
+
+		// Multi-touch screen
+		if device.pen {
+			return;
+		}
+		for (i, finger) in device.fingers.iter_mut().enumerate() {
+			// Lifted before the slot was reused for a new finger.
+			if finger.released {
+				queue.touch_release(i, wh, finger.xy);
+			}
+			if finger.touch == Some(true) {
+				finger.touch = Some(false);
+				queue.touch_press(i, wh, finger.xy);
+			} else if finger.moved && finger.touch.is_some() {
+				queue.touch_move(i, wh, finger.xy);
+			}
+			finger.moved = false;
+			finger.released = false;
+		}
+	}
+}
+
+impl Drop for Evdev {
+	fn drop(&mut self) {
+		if self.inotify >= 0 {
+			unsafe { libc::close(self.inotify); }
+		}
+	}
+}
+
+// Keycode translator (evdev codes, see `linux/input-event-codes.h`)
+fn key(code: u16) -> Option<Key> {
+	Some(match code {
+		41 => Key::Backtick,
+		78 => Key::Plus,
+		55 => Key::Asterisk,
+		53 | 98 => Key::Slash,
+		28 | 96 => Key::Enter,
+		2 | 79 => Key::Num1,
+		3 | 80 => Key::Num2,
+		4 | 81 => Key::Num3,
+		5 | 75 => Key::Num4,
+		6 | 76 => Key::Num5,
+		7 | 77 => Key::Num6,
+		8 | 71 => Key::Num7,
+		9 | 72 => Key::Num8,
+		10 | 73 => Key::Num9,
+		11 | 82 => Key::Num0,
+		52 | 83 => Key::Period,
+		12 | 74 => Key::Minus,
+		13 => Key::EqualSign,
+		14 => Key::Backspace,
+		15 => Key::Tab,
+		30 => Key::A,
+		48 => Key::B,
+		46 => Key::C,
+		32 => Key::D,
+		18 => Key::E,
+		33 => Key::F,
+		34 => Key::G,
+		35 => Key::H,
+		23 => Key::I,
+		36 => Key::J,
+		37 => Key::K,
+		38 => Key::L,
+		50 => Key::M,
+		49 => Key::N,
+		24 => Key::O,
+		25 => Key::P,
+		16 => Key::Q,
+		19 => Key::R,
+		31 => Key::S,
+		20 => Key::T,
+		22 => Key::U,
+		47 => Key::V,
+		17 => Key::W,
+		45 => Key::X,
+		21 => Key::Y,
+		44 => Key::Z,
+		26 => Key::BracketOpen,
+		27 => Key::BracketClose,
+		29 => Key::LCtrl,
+		97 => Key::RCtrl,
+		42 => Key::LShift,
+		54 => Key::RShift,
+		56 => Key::Alt,
+		100 => Key::AltGr,
+		58 => Key::Compose,
+		39 => Key::Semicolon,
+		40 => Key::Apostrophe,
+		43 => Key::Backslash,
+		51 => Key::Comma,
+		57 => Key::Space,
+		69 => Key::NumLock,
+		102 => Key::Home,
+		107 => Key::End,
+		104 => Key::PageUp,
+		109 => Key::PageDown,
+		110 => Key::Insert,
+		111 => Key::Delete,
+		103 => Key::Up,
+		105 => Key::Left,
+		106 => Key::Right,
+		108 => Key::Down,
+		_ => return None,
+	} )
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use input::InputQueue;
+	use std::thread;
+	use std::time::Duration;
+
+	const BTN_TOOL_FINGER: u16 = 0x145;
+	const INPUT_PROP_POINTER: u16 = 0x00;
+	const KEY_LEFTCTRL: u16 = 29;
+	const KEY_C: u16 = 46;
+
+	/// `_IOC(dir, 'U', nr, size)`, a `/dev/uinput` ioctl.
+	fn ui(dir: libc::c_ulong, nr: libc::c_ulong, size: usize)
+		-> libc::c_ulong
+	{
+		(dir << 30) | ((size as libc::c_ulong) << 16)
+			| ((b'U' as libc::c_ulong) << 8) | nr
+	}
+
+	/// A virtual input device, made with `/dev/uinput`.
+	struct Uinput(i32);
+
+	impl Uinput {
+		/// Make a device with some keys, absolute axes (code, min,
+		/// max) and properties, and find its `/dev/input` name.
+		fn new(keys: &[u16], axes: &[(u16, i32, i32)], props: &[u16])
+			-> Option<(Uinput, String)>
+		{
+			let path = CString::new("/dev/uinput").unwrap();
+			let fd = unsafe {
+				libc::open(path.as_ptr(), libc::O_WRONLY
+					| libc::O_NONBLOCK | libc::O_CLOEXEC)
+			};
+
+			if fd < 0 {
+				return None;
+			}
+
+			let uinput = Uinput(fd);
+			let int = mem::size_of::<libc::c_int>();
+			let set = |nr, value: u16| unsafe {
+				libc::ioctl(fd, ui(1, nr, int),
+					value as libc::c_int);
+			};
+
+			set(100, EV_KEY);
+			for key in keys {
+				set(101, *key);
+			}
+			if !axes.is_empty() {
+				set(100, EV_ABS);
+			}
+			for &(code, minimum, maximum) in axes {
+				let mut abs: libc::uinput_abs_setup
+					= unsafe { mem::zeroed() };
+
+				abs.code = code;
+				abs.absinfo.minimum = minimum;
+				abs.absinfo.maximum = maximum;
+				set(103, code);
+				unsafe {
+					libc::ioctl(fd, ui(1, 4,
+						mem::size_of_val(&abs)), &abs);
+				}
+			}
+			for prop in props {
+				set(110, *prop);
+			}
+
+			let mut setup: libc::uinput_setup
+				= unsafe { mem::zeroed() };
+			setup.id.bustype = 0x06; // BUS_VIRTUAL
+			for (a, b) in setup.name.iter_mut().zip(b"awi test") {
+				*a = *b as libc::c_char;
+			}
+
+			let mut sysname = [0u8; 64];
+			if unsafe {
+				libc::ioctl(fd, ui(1, 3,
+					mem::size_of_val(&setup)), &setup) < 0
+				|| libc::ioctl(fd, ui(0, 1, 0)) < 0
+				|| libc::ioctl(fd, ui(2, 44, sysname.len()),
+					sysname.as_mut_ptr()) < 0
+			} {
+				return None;
+			}
+
+			let sysname = CStr::from_bytes_until_nul(&sysname)
+				.ok()?.to_string_lossy().into_owned();
+			let name = fs::read_dir(format!("/sys/class/input/{}",
+				sysname)).ok()?.filter_map(|e| e.ok())
+				.map(|e| e.file_name().to_string_lossy()
+					.into_owned())
+				.find(|name| name.starts_with("event"))?;
+
+			Some((uinput, name))
+		}
+
+		/// Send some events (type, code, value), then `SYN_REPORT`.
+		fn send(&self, events: &[(u16, u16, i32)]) {
+			for &(type_, code, value) in events.iter()
+				.chain(&[(EV_SYN, SYN_REPORT, 0)])
+			{
+				let mut event: libc::input_event
+					= unsafe { mem::zeroed() };
+
+				event.type_ = type_;
+				event.code = code;
+				event.value = value;
+				unsafe {
+					libc::write(self.0, &event as *const _
+						as *const libc::c_void,
+						mem::size_of_val(&event));
+				}
+			}
+		}
+	}
+
+	impl Drop for Uinput {
+		fn drop(&mut self) {
+			unsafe {
+				libc::ioctl(self.0, ui(0, 2, 0));
+				libc::close(self.0);
+			}
+		}
+	}
+
+	/// Open the device with evdev, waiting for udev to make the node.
+	fn open(name: &str) -> Evdev {
+		let mut evdev = Evdev {
+			devices: vec![], inotify: -1, xkb: Xkb::new(),
+			xy: (0, 0), vt: None,
+		};
+
+		for _ in 0..100 {
+			evdev.add(name);
+			if !evdev.devices.is_empty() {
+				return evdev;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		panic!("couldn't open /dev/input/{}", name);
+	}
+
+	/// Read the events evdev translated.
+	fn read(evdev: &mut Evdev) -> Vec<String> {
+		let mut queue = InputQueue::new();
+		let mut keyboard = ::Keyboard::new();
+		let mut events = vec![];
+
+		evdev.read(0, &mut queue, &mut keyboard, (1000, 1000));
+		while let Some(event) = queue.pop() {
+			events.push(event.to_string());
+		}
+
+		events
+	}
+
+	/// A touchpad or touchscreen, with a finger dragged across it.
+	fn drag(props: &[u16]) -> Vec<String> {
+		let (uinput, name) = Uinput::new(
+			&[BTN_LEFT, BTN_TOUCH, BTN_TOOL_FINGER],
+			&[(ABS_X, 0, 999), (ABS_Y, 0, 999), (ABS_MT_SLOT, 0, 9),
+				(ABS_MT_POSITION_X, 0, 999),
+				(ABS_MT_POSITION_Y, 0, 999),
+				(ABS_MT_TRACKING_ID, 0, 65535)],
+			props).expect("can't write /dev/uinput");
+		let mut evdev = open(&name);
+
+		uinput.send(&[(EV_ABS, ABS_MT_TRACKING_ID, 1),
+			(EV_ABS, ABS_MT_POSITION_X, 100),
+			(EV_ABS, ABS_MT_POSITION_Y, 100),
+			(EV_KEY, BTN_TOUCH, 1), (EV_ABS, ABS_X, 100),
+			(EV_ABS, ABS_Y, 100)]);
+		uinput.send(&[(EV_ABS, ABS_MT_POSITION_X, 300),
+			(EV_ABS, ABS_X, 300)]);
+		uinput.send(&[(EV_ABS, ABS_MT_TRACKING_ID, -1),
+			(EV_KEY, BTN_TOUCH, 0)]);
+
+		read(&mut evdev)
+	}
+
+	#[test]
+	#[ignore = "needs write access to /dev/uinput"]
+	fn touchpad_moves_cursor() {
+		let events = drag(&[INPUT_PROP_POINTER]);
+
+		assert!(!events.iter().any(|e| e.starts_with("Touch")),
+			"{:?}", events);
+		assert!(events.iter().any(|e| e.starts_with("Cursor")),
+			"{:?}", events);
+	}
+
+	#[test]
+	#[ignore = "needs write access to /dev/uinput"]
+	fn touchscreen_touches() {
+		let events = drag(&[INPUT_PROP_DIRECT as u16]);
+
+		assert!(events.iter().any(|e| e.starts_with("Touch")),
+			"{:?}", events);
+	}
+
+	#[test]
+	#[ignore = "needs write access to /dev/uinput, and libxkbcommon"]
+	fn ctrl_letter_has_no_text() {
+		let (uinput, name) = Uinput::new(&[KEY_LEFTCTRL, KEY_C],
+			&[], &[]).expect("can't write /dev/uinput");
+		let mut evdev = open(&name);
+
+		assert!(evdev.xkb.is_some(), "can't load libxkbcommon");
+
+		uinput.send(&[(EV_KEY, KEY_C, 1)]);
+		uinput.send(&[(EV_KEY, KEY_C, 0)]);
+		assert!(read(&mut evdev).iter().any(|e| e.starts_with("Text")));
+
+		uinput.send(&[(EV_KEY, KEY_LEFTCTRL, 1)]);
+		uinput.send(&[(EV_KEY, KEY_C, 1)]);
+		uinput.send(&[(EV_KEY, KEY_C, 0)]);
+		uinput.send(&[(EV_KEY, KEY_LEFTCTRL, 0)]);
+		let events = read(&mut evdev);
+		assert!(!events.iter().any(|e| e.starts_with("Text")),
+			"{:?}", events);
+	}
+}
@@ -0,0 +1,477 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Mode setting and page flipping with DRM/KMS, and GBM buffers for EGL to
+//! render to.
+
+use c_void;
+use std::cell::Cell;
+use std::ffi::CString;
+use std::ptr::null_mut;
+
+use libc;
+
+const DRM_MODE_CONNECTED: i32 = 1;
+const DRM_MODE_TYPE_PREFERRED: u32 = 1 << 3;
+const DRM_MODE_PAGE_FLIP_EVENT: u32 = 0x01;
+
+const GBM_BO_USE_SCANOUT: u32 = 1 << 0;
+const GBM_BO_USE_RENDERING: u32 = 1 << 2;
+const GBM_FORMAT_ARGB8888: u32 = 0x34325241;
+
+#[repr(C)]
+struct DrmModeRes {
+	count_fbs: i32,
+	fbs: *mut u32,
+	count_crtcs: i32,
+	crtcs: *mut u32,
+	count_connectors: i32,
+	connectors: *mut u32,
+	count_encoders: i32,
+	encoders: *mut u32,
+	min_width: u32,
+	max_width: u32,
+	min_height: u32,
+	max_height: u32,
+}
+
+#[repr(C)] #[derive(Copy, Clone)]
+struct DrmModeModeInfo {
+	clock: u32,
+	hdisplay: u16,
+	hsync_start: u16,
+	hsync_end: u16,
+	htotal: u16,
+	hskew: u16,
+	vdisplay: u16,
+	vsync_start: u16,
+	vsync_end: u16,
+	vtotal: u16,
+	vscan: u16,
+	vrefresh: u32,
+	flags: u32,
+	type_: u32,
+	name: [u8; 32],
+}
+
+#[repr(C)]
+struct DrmModeConnector {
+	connector_id: u32,
+	encoder_id: u32,
+	connector_type: u32,
+	connector_type_id: u32,
+	connection: i32,
+	mm_width: u32,
+	mm_height: u32,
+	subpixel: i32,
+	count_modes: i32,
+	modes: *mut DrmModeModeInfo,
+	count_props: i32,
+	props: *mut u32,
+	prop_values: *mut u64,
+	count_encoders: i32,
+	encoders: *mut u32,
+}
+
+#[repr(C)]
+struct DrmModeEncoder {
+	encoder_id: u32,
+	encoder_type: u32,
+	crtc_id: u32,
+	possible_crtcs: u32,
+	possible_clones: u32,
+}
+
+#[repr(C)]
+struct DrmModeCrtc {
+	crtc_id: u32,
+	buffer_id: u32,
+	x: u32,
+	y: u32,
+	width: u32,
+	height: u32,
+	mode_valid: i32,
+	mode: DrmModeModeInfo,
+	gamma_size: i32,
+}
+
+type DrmHandler = extern "C" fn(i32, u32, u32, u32, *mut c_void);
+
+#[repr(C)]
+struct DrmEventContext {
+	version: i32,
+	vblank_handler: Option<DrmHandler>,
+	page_flip_handler: Option<DrmHandler>,
+}
+
+dl_api!(Drm, "libdrm.so.2",
+	fn drmModeGetResources(i32) -> *mut DrmModeRes,
+	fn drmModeFreeResources(*mut DrmModeRes) -> (),
+	fn drmModeGetConnector(i32, u32) -> *mut DrmModeConnector,
+	fn drmModeFreeConnector(*mut DrmModeConnector) -> (),
+	fn drmModeGetEncoder(i32, u32) -> *mut DrmModeEncoder,
+	fn drmModeFreeEncoder(*mut DrmModeEncoder) -> (),
+	fn drmModeGetCrtc(i32, u32) -> *mut DrmModeCrtc,
+	fn drmModeFreeCrtc(*mut DrmModeCrtc) -> (),
+	fn drmModeSetCrtc(i32, u32, u32, u32, u32, *mut u32, i32,
+		*mut DrmModeModeInfo) -> i32,
+	fn drmModeAddFB(i32, u32, u32, u8, u8, u32, u32, *mut u32) -> i32,
+	fn drmModeRmFB(i32, u32) -> i32,
+	fn drmModePageFlip(i32, u32, u32, u32, *mut c_void) -> i32,
+	fn drmHandleEvent(i32, *mut DrmEventContext) -> i32,
+	fn drmSetMaster(i32) -> i32,
+	fn drmDropMaster(i32) -> i32
+);
+
+dl_api!(Gbm, "libgbm.so.1",
+	fn gbm_create_device(i32) -> *mut c_void,
+	fn gbm_device_destroy(*mut c_void) -> (),
+	fn gbm_surface_create(*mut c_void, u32, u32, u32, u32) -> *mut c_void,
+	fn gbm_surface_destroy(*mut c_void) -> (),
+	fn gbm_surface_lock_front_buffer(*mut c_void) -> *mut c_void,
+	fn gbm_surface_release_buffer(*mut c_void, *mut c_void) -> (),
+	fn gbm_bo_get_handle(*mut c_void) -> u64,
+	fn gbm_bo_get_stride(*mut c_void) -> u32
+);
+
+/// A connector (monitor) driven by a CRTC, with a GBM surface to show.
+pub(crate) struct Kms {
+	drm: Drm,
+	gbm: Gbm,
+	fd: i32,
+	connector: u32,
+	crtc: u32,
+	mode: DrmModeModeInfo,
+	// The CRTC's state before, restored on drop.
+	saved: *mut DrmModeCrtc,
+	format: u32,
+	device: *mut c_void,
+	surface: *mut c_void,
+	// Framebuffers made from GBM buffers (the surface reuses a few).
+	framebuffers: Vec<(*mut c_void, u32)>,
+	// Buffer on the screen.
+	front: *mut c_void,
+	// Whether the mode needs to be set (first frame, and VT switches).
+	modeset: bool,
+	// Whether this is the active VT, and owns the display.
+	active: bool,
+}
+
+impl Kms {
+	/// Open the first DRM card with a connected monitor, and create a
+	/// fullscreen GBM surface with `format` for it's preferred mode.
+	pub(crate) fn new(format: u32) -> Result<Kms, String> {
+		let drm = Drm::new().map_err(|e| e.to_string())?;
+		let gbm = Gbm::new().map_err(|e| e.to_string())?;
+
+		for card in 0..8 {
+			let path = CString::new(format!("/dev/dri/card{}", card))
+				.unwrap();
+			let fd = unsafe {
+				libc::open(path.as_ptr(),
+					libc::O_RDWR | libc::O_CLOEXEC)
+			};
+
+			if fd < 0 {
+				continue;
+			}
+
+			match output(&drm, fd) {
+				Some((connector, crtc, mode)) => {
+					return Kms::with(drm, gbm, fd, connector,
+						crtc, mode, format);
+				}
+				None => unsafe { libc::close(fd); },
+			}
+		}
+
+		Err("No DRM card with a connected monitor".to_string())
+	}
+
+	fn with(drm: Drm, gbm: Gbm, fd: i32, connector: u32, crtc: u32,
+		mode: DrmModeModeInfo, format: u32) -> Result<Kms, String>
+	{
+		let saved = unsafe { (drm.drmModeGetCrtc)(fd, crtc) };
+		let device = unsafe { (gbm.gbm_create_device)(fd) };
+		let surface = if device.is_null() { null_mut() } else {
+			unsafe {
+				(gbm.gbm_surface_create)(device,
+					mode.hdisplay as u32,
+					mode.vdisplay as u32, format,
+					GBM_BO_USE_SCANOUT
+						| GBM_BO_USE_RENDERING)
+			}
+		};
+
+		// Frees everything on error.
+		let kms = Kms {
+			drm, gbm, fd, connector, crtc, mode, saved, format, device,
+			surface, framebuffers: vec![], front: null_mut(),
+			modeset: true, active: true,
+		};
+
+		if surface.is_null() {
+			return Err("Couldn't create GBM surface".to_string());
+		}
+
+		Ok(kms)
+	}
+
+	/// The size of the mode, in pixels.
+	pub(crate) fn wh(&self) -> (u16, u16) {
+		(self.mode.hdisplay, self.mode.vdisplay)
+	}
+
+	/// The `gbm_device` and `gbm_surface`, for EGL.
+	pub(crate) fn connection(&self) -> ::WindowConnection {
+		::WindowConnection::Kms(self.device, self.surface)
+	}
+
+	/// Show the buffer EGL last swapped to, waiting for vertical sync.
+	pub(crate) fn flip(&mut self) {
+		let bo = unsafe {
+			(self.gbm.gbm_surface_lock_front_buffer)(self.surface)
+		};
+
+		if bo.is_null() {
+			return;
+		}
+
+		// Switched to another VT: drop the frame.
+		if !self.active {
+			unsafe {
+				(self.gbm.gbm_surface_release_buffer)(
+					self.surface, bo);
+			}
+			return;
+		}
+
+		let fb = self.framebuffer(bo);
+
+		if self.modeset {
+			unsafe {
+				(self.drm.drmModeSetCrtc)(self.fd, self.crtc, fb,
+					0, 0, &mut self.connector, 1,
+					&mut self.mode);
+			}
+			self.modeset = false;
+		} else {
+			self.page_flip(fb);
+		}
+
+		if !self.front.is_null() {
+			unsafe {
+				(self.gbm.gbm_surface_release_buffer)(
+					self.surface, self.front);
+			}
+		}
+		self.front = bo;
+	}
+
+	/// Flip to `fb` on the next vertical blank, and block until done.
+	fn page_flip(&self, fb: u32) {
+		extern "C" fn page_flip(_fd: i32, _sequence: u32, _sec: u32,
+			_usec: u32, flipped: *mut c_void)
+		{
+			unsafe { (*(flipped as *const Cell<bool>)).set(true) };
+		}
+
+		let flipped = Cell::new(false);
+		let mut context = DrmEventContext {
+			version: 2,
+			vblank_handler: None,
+			page_flip_handler: Some(page_flip),
+		};
+
+		if unsafe {
+			(self.drm.drmModePageFlip)(self.fd, self.crtc, fb,
+				DRM_MODE_PAGE_FLIP_EVENT,
+				&flipped as *const _ as *mut c_void)
+		} != 0 {
+			return;
+		}
+
+		while !flipped.get() {
+			let mut fd = libc::pollfd {
+				fd: self.fd, events: libc::POLLIN, revents: 0
+			};
+
+			if unsafe { libc::poll(&mut fd, 1, 1000) } <= 0 {
+				// Lost the display (or the driver is stuck).
+				break;
+			}
+
+			unsafe {
+				(self.drm.drmHandleEvent)(self.fd, &mut context)
+			};
+		}
+	}
+
+	/// Get (or make) the framebuffer for a GBM buffer.
+	fn framebuffer(&mut self, bo: *mut c_void) -> u32 {
+		if let Some(&(_, fb)) = self.framebuffers.iter()
+			.find(|&&(b, _)| b == bo)
+		{
+			return fb;
+		}
+
+		let depth = if self.format == GBM_FORMAT_ARGB8888 { 32 }
+			else { 24 };
+		let mut fb = 0;
+
+		unsafe {
+			(self.drm.drmModeAddFB)(self.fd,
+				self.mode.hdisplay as u32,
+				self.mode.vdisplay as u32, depth, 32,
+				(self.gbm.gbm_bo_get_stride)(bo),
+				(self.gbm.gbm_bo_get_handle)(bo) as u32,
+				&mut fb);
+		}
+
+		self.framebuffers.push((bo, fb));
+		fb
+	}
+
+	/// Give up the display, for another VT.
+	pub(crate) fn release(&mut self) {
+		self.active = false;
+		unsafe { (self.drm.drmDropMaster)(self.fd) };
+	}
+
+	/// Take back the display, after switching back to our VT.
+	pub(crate) fn acquire(&mut self) {
+		unsafe { (self.drm.drmSetMaster)(self.fd) };
+		self.active = true;
+		// The other VT changed the mode.
+		self.modeset = true;
+	}
+}
+
+impl Drop for Kms {
+	fn drop(&mut self) {
+		unsafe {
+			// Put back the console.
+			if !self.saved.is_null() {
+				let saved = &mut *self.saved;
+
+				if self.active {
+					(self.drm.drmModeSetCrtc)(self.fd,
+						saved.crtc_id, saved.buffer_id,
+						saved.x, saved.y,
+						&mut self.connector, 1,
+						&mut saved.mode);
+				}
+				(self.drm.drmModeFreeCrtc)(self.saved);
+			}
+
+			for &(_, fb) in self.framebuffers.iter() {
+				(self.drm.drmModeRmFB)(self.fd, fb);
+			}
+			if !self.front.is_null() {
+				(self.gbm.gbm_surface_release_buffer)(
+					self.surface, self.front);
+			}
+			if !self.surface.is_null() {
+				(self.gbm.gbm_surface_destroy)(self.surface);
+			}
+			if !self.device.is_null() {
+				(self.gbm.gbm_device_destroy)(self.device);
+			}
+			libc::close(self.fd);
+		}
+	}
+}
+
+/// Find a connected connector, a CRTC to drive it and it's preferred mode.
+fn output(drm: &Drm, fd: i32) -> Option<(u32, u32, DrmModeModeInfo)> {
+	let resources = unsafe { (drm.drmModeGetResources)(fd) };
+
+	if resources.is_null() {
+		return None;
+	}
+
+	let res = unsafe { &*resources };
+	let connectors = unsafe { slice(res.connectors, res.count_connectors) };
+	let crtcs = unsafe { slice(res.crtcs, res.count_crtcs) };
+	let mut output = None;
+
+	for &id in connectors {
+		let connector = unsafe { (drm.drmModeGetConnector)(fd, id) };
+
+		if connector.is_null() {
+			continue;
+		}
+
+		let conn = unsafe { &*connector };
+		let modes = unsafe { slice(conn.modes, conn.count_modes) };
+
+		if conn.connection == DRM_MODE_CONNECTED && !modes.is_empty() {
+			let mode = modes.iter()
+				.find(|m| m.type_ & DRM_MODE_TYPE_PREFERRED != 0)
+				.unwrap_or(&modes[0]);
+
+			if let Some(crtc) = crtc(drm, fd, conn, crtcs) {
+				output = Some((id, crtc, *mode));
+			}
+		}
+
+		unsafe { (drm.drmModeFreeConnector)(connector) };
+
+		if output.is_some() {
+			break;
+		}
+	}
+
+	unsafe { (drm.drmModeFreeResources)(resources) };
+
+	output
+}
+
+/// Find a CRTC for a connector, preferring the one it's already using.
+fn crtc(drm: &Drm, fd: i32, connector: &DrmModeConnector, crtcs: &[u32])
+	-> Option<u32>
+{
+	let encoders = unsafe {
+		slice(connector.encoders, connector.count_encoders)
+	};
+	let current = if connector.encoder_id != 0 {
+		Some(connector.encoder_id)
+	} else {
+		None
+	};
+
+	for &id in current.iter().chain(encoders.iter()) {
+		let encoder = unsafe { (drm.drmModeGetEncoder)(fd, id) };
+
+		if encoder.is_null() {
+			continue;
+		}
+
+		let (crtc_id, possible) = unsafe {
+			((*encoder).crtc_id, (*encoder).possible_crtcs)
+		};
+
+		unsafe { (drm.drmModeFreeEncoder)(encoder) };
+
+		if crtc_id != 0 {
+			return Some(crtc_id);
+		}
+		if let Some(i) = (0..crtcs.len()).find(|i| possible & (1 << i) != 0)
+		{
+			return Some(crtcs[i]);
+		}
+	}
+
+	None
+}
+
+/// A slice from a libdrm array and count.
+unsafe fn slice<'a, T>(pointer: *mut T, count: i32) -> &'a [T] {
+	if pointer.is_null() || count <= 0 {
+		&[]
+	} else {
+		::std::slice::from_raw_parts(pointer, count as usize)
+	}
+}
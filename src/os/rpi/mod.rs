@@ -3,17 +3,216 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-use Input;
+//! Kiosk backend: fullscreen without X11 or Wayland, such as on a Raspberry
+//! Pi.  Input comes from evdev (`/dev/input/event*`), so the user needs to
+//! be able to read those devices (usually the `input` group).  Rendering
+//! goes straight to the display, with KMS (OpenGL) or `VK_KHR_display`.
 
-pub struct FBWindow {
-	// TODO
+use std::fs;
+use std::ptr::null_mut;
+use std::sync::Arc;
+use std::time::Duration;
+
+use libc;
+
+#[path = "../pipe.rs"] mod pipe;
+mod evdev;
+mod kms;
+mod vt;
+
+use self::pipe::Pipe;
+pub use self::pipe::Wake;
+
+pub struct Window {
+	evdev: evdev::Evdev,
+	// Display, when rendering with OpenGL (Vulkan sets up it's own).
+	kms: Option<kms::Kms>,
+	// Our VT, unless not started from one.
+	vt: Option<vt::Vt>,
+	// Whether our VT is the one shown.
+	active: bool,
+	wh: (u16, u16),
+	// Pipe to wake up `wait()` from other threads.
+	pipe: Arc<Pipe>,
+	// Window created by another library, which handles it's input.
+	external: Option<::WindowConnection>,
 }
 
-impl FBWindow {
-	pub fn poll_event(&self, input: &mut Vec<Input>, wh: &mut (u32, u32))
-		-> bool
+impl Window {
+	/// `v` is the GBM format for OpenGL, or `None` for Vulkan.
+	pub fn new(v: Option<i32>, _builder: &::WindowBuilder) -> Self {
+		let kms = v.and_then(|format| {
+			kms::Kms::new(format as u32)
+				.map_err(|e| eprintln!("awi: {}", e)).ok()
+		});
+		let wh = match kms {
+			Some(ref kms) => kms.wh(),
+			None => framebuffer_size(),
+		};
+
+		Window {
+			evdev: evdev::Evdev::new(), kms, vt: vt::Vt::new(),
+			active: true, wh, pipe: Arc::new(Pipe::new()),
+			external: None,
+		}
+	}
+
+	/// Wrap a window created by another library, only to render to it.
+	/// The other library handles it's input.
+	pub fn external(connection: ::WindowConnection, wh: (u16, u16)) -> Self {
+		Window {
+			evdev: evdev::Evdev::new(), kms: None, vt: None,
+			active: true, wh, pipe: Arc::new(Pipe::new()),
+			external: Some(connection),
+		}
+	}
+
+	pub fn poll_event(&mut self, input: &mut ::input::InputQueue,
+		keyboard: &mut ::input::keyboard::Keyboard) -> bool
 	{
-		// TODO: this breaks
-		false
+		if self.external.is_some() {
+			return false;
+		}
+
+		let len = input.len();
+
+		self.switch(input);
+
+		if self.active {
+			self.evdev.poll(input, keyboard, self.wh);
+		} else {
+			// Another VT is using the keyboard.
+			self.evdev.poll(&mut ::input::InputQueue::new(),
+				&mut ::input::keyboard::Keyboard::new(),
+				self.wh);
+		}
+
+		// Ctrl-Alt-F1 to F12
+		if let (Some(number), Some(vt)) = (self.evdev.vt(), self.vt.as_ref())
+		{
+			vt.activate(number);
+		}
+
+		input.len() != len
+	}
+
+	/// Handle VT switches.
+	fn switch(&mut self, input: &mut ::input::InputQueue) {
+		let vt = match self.vt {
+			Some(ref vt) => vt,
+			None => return,
+		};
+
+		while let Some(switch) = vt.switch() {
+			match switch {
+				vt::Switch::Release => {
+					if let Some(ref mut kms) = self.kms {
+						kms.release();
+					}
+					self.active = false;
+					input.pause();
+				}
+				vt::Switch::Acquire => {
+					if let Some(ref mut kms) = self.kms {
+						kms.acquire();
+					}
+					self.active = true;
+					input.resume();
+				}
+			}
+			vt.done(switch);
+		}
+	}
+
+	/// Put the last rendered buffer on the screen.
+	pub fn flip(&mut self) {
+		if let Some(ref mut kms) = self.kms {
+			kms.flip();
+		}
+	}
+
+	/// Block until there's input, `Wake::wake()` is called, or the timeout
+	/// (if any) runs out.
+	pub fn wait(&mut self, timeout: Option<Duration>) {
+		let timeout = match timeout {
+			// Long timeouts wait as long as `poll()` can (-1 is
+			// forever).
+			Some(t) => t.as_millis().min(i32::MAX as u128) as i32,
+			None => -1,
+		};
+
+		let mut fds = vec![libc::pollfd {
+			fd: self.pipe.read, events: libc::POLLIN, revents: 0
+		}];
+
+		if let Some(ref vt) = self.vt {
+			fds.push(libc::pollfd {
+				fd: vt.fd(), events: libc::POLLIN, revents: 0
+			});
+		}
+
+		// Another library reads the external window's input.
+		if self.external.is_none() {
+			fds.extend(self.evdev.fds().iter().map(|&fd| {
+				libc::pollfd { fd, events: libc::POLLIN, revents: 0 }
+			}));
+		}
+
+		unsafe {
+			libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t,
+				timeout)
+		};
+
+		self.pipe.drain();
+	}
+
+	/// Get a handle to interrupt `wait()` from another thread.
+	pub fn wake(&self) -> Wake {
+		Wake(self.pipe.clone())
+	}
+
+	pub fn get_connection(&self) -> ::WindowConnection {
+		match (self.external.as_ref(), self.kms.as_ref()) {
+			(Some(connection), _) => connection.clone(),
+			(None, Some(kms)) => kms.connection(),
+			// Vulkan renders to the display itself.
+			(None, None) => {
+				::WindowConnection::Kms(null_mut(), null_mut())
+			}
+		}
+	}
+
+	pub fn wh(&self) -> (u16, u16) {
+		self.wh
+	}
+
+	/// Set the size, for an external window (only the other library knows
+	/// when it resizes), or the display mode picked by Vulkan.
+	pub fn resize(&mut self, wh: (u16, u16)) {
+		if self.kms.is_none() {
+			self.wh = wh;
+		}
+	}
+
+	// There's no window manager or screensaver.
+	pub fn attention(&mut self) {}
+	pub fn inhibit_screensaver(&mut self, _inhibit: bool)
+		-> Result<(), String> { Ok(()) }
+	pub fn key_label(&self, _key: ::Key) -> Option<String> { None }
+	pub fn click_settings(&self) -> (f32, u16) {
+		(::input::pointer::DOUBLE_CLICK_TIME,
+			::input::pointer::DRAG_THRESHOLD)
+	}
+}
+
+/// Get the size of the screen from the framebuffer device.
+fn framebuffer_size() -> (u16, u16) {
+	let size = fs::read_to_string("/sys/class/graphics/fb0/virtual_size")
+		.unwrap_or_default();
+	let mut size = size.trim().split(',').map(|n| n.parse().unwrap_or(0));
+
+	match (size.next(), size.next()) {
+		(Some(w), Some(h)) if w != 0 && h != 0 => (w, h),
+		_ => (640, 360),
 	}
 }
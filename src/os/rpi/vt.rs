@@ -0,0 +1,192 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Virtual terminal switching.  The kernel asks before switching away from
+//! (`SIGUSR1`) and back to (`SIGUSR2`) our VT, so the display can be given
+//! up and taken back.
+
+use std::ffi::CString;
+use std::mem;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use libc;
+
+const KDSETMODE: libc::c_ulong = 0x4B3A;
+const KDGETMODE: libc::c_ulong = 0x4B3B;
+const KDGKBMODE: libc::c_ulong = 0x4B44;
+const KDSKBMODE: libc::c_ulong = 0x4B45;
+const VT_GETMODE: libc::c_ulong = 0x5601;
+const VT_SETMODE: libc::c_ulong = 0x5602;
+const VT_RELDISP: libc::c_ulong = 0x5605;
+const VT_ACTIVATE: libc::c_ulong = 0x5606;
+
+const KD_GRAPHICS: i32 = 1;
+const K_OFF: i32 = 4;
+const VT_PROCESS: i8 = 1;
+const VT_ACKACQ: i32 = 2;
+
+// Write end of the pipe the signal handler writes to, or -1.
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+#[repr(C)]
+struct VtMode {
+	mode: i8,
+	waitv: i8,
+	relsig: i16,
+	acqsig: i16,
+	frsig: i16,
+}
+
+/// What the kernel asked for.
+pub(crate) enum Switch {
+	/// Switching away from our VT.
+	Release,
+	/// Switched back to our VT.
+	Acquire,
+}
+
+/// The VT we're running on, in graphics mode.
+pub(crate) struct Vt {
+	tty: i32,
+	// Settings before, restored on drop.
+	kd_mode: i32,
+	kb_mode: i32,
+	vt_mode: VtMode,
+	// Pipe from the signal handler.
+	read: i32,
+	write: i32,
+}
+
+impl Vt {
+	/// Take over the controlling terminal, if it's a VT.
+	pub(crate) fn new() -> Option<Vt> {
+		let path = CString::new("/dev/tty").unwrap();
+		let tty = unsafe {
+			libc::open(path.as_ptr(), libc::O_RDWR | libc::O_CLOEXEC)
+		};
+
+		if tty < 0 {
+			return None;
+		}
+
+		let mut kd_mode = 0;
+		let mut kb_mode = 0;
+		let mut vt_mode: VtMode = unsafe { mem::zeroed() };
+
+		// Not a VT (such as over SSH).
+		if unsafe {
+			libc::ioctl(tty, KDGETMODE, &mut kd_mode) < 0
+				|| libc::ioctl(tty, KDGKBMODE, &mut kb_mode) < 0
+				|| libc::ioctl(tty, VT_GETMODE, &mut vt_mode) < 0
+		} {
+			unsafe { libc::close(tty) };
+			return None;
+		}
+
+		let mut fds = [0i32; 2];
+		if unsafe {
+			libc::pipe2(fds.as_mut_ptr(),
+				libc::O_NONBLOCK | libc::O_CLOEXEC)
+		} != 0 {
+			unsafe { libc::close(tty) };
+			return None;
+		}
+		SIGNAL_PIPE.store(fds[1], Ordering::SeqCst);
+
+		unsafe {
+			let mut action: libc::sigaction = mem::zeroed();
+			action.sa_sigaction = signal as extern "C" fn(i32) as usize;
+			action.sa_flags = libc::SA_RESTART;
+			libc::sigaction(libc::SIGUSR1, &action, null_mut());
+			libc::sigaction(libc::SIGUSR2, &action, null_mut());
+
+			// No console text over our graphics, and keys only go
+			// to evdev.
+			libc::ioctl(tty, KDSETMODE, KD_GRAPHICS);
+			libc::ioctl(tty, KDSKBMODE, K_OFF);
+			libc::ioctl(tty, VT_SETMODE, &VtMode {
+				mode: VT_PROCESS, waitv: 0,
+				relsig: libc::SIGUSR1 as i16,
+				acqsig: libc::SIGUSR2 as i16, frsig: 0,
+			});
+		}
+
+		Some(Vt {
+			tty, kd_mode, kb_mode, vt_mode, read: fds[0],
+			write: fds[1],
+		})
+	}
+
+	/// File descriptor to `poll()` on for VT switches.
+	pub(crate) fn fd(&self) -> i32 {
+		self.read
+	}
+
+	/// Get the next VT switch the kernel asked for, if any.  Call `done()`
+	/// once handled.
+	pub(crate) fn switch(&self) -> Option<Switch> {
+		let mut signal = 0u8;
+
+		if unsafe {
+			libc::read(self.read, &mut signal as *mut u8
+				as *mut libc::c_void, 1)
+		} != 1 {
+			return None;
+		}
+
+		Some(if signal as i32 == libc::SIGUSR1 {
+			Switch::Release
+		} else {
+			Switch::Acquire
+		})
+	}
+
+	/// Let the kernel finish a VT switch.
+	pub(crate) fn done(&self, switch: Switch) {
+		let arg = match switch {
+			Switch::Release => 1,
+			Switch::Acquire => VT_ACKACQ,
+		};
+
+		unsafe { libc::ioctl(self.tty, VT_RELDISP, arg) };
+	}
+
+	/// Switch to VT number `vt` (Ctrl-Alt-F`vt`).
+	pub(crate) fn activate(&self, vt: i32) {
+		unsafe { libc::ioctl(self.tty, VT_ACTIVATE, vt) };
+	}
+}
+
+impl Drop for Vt {
+	fn drop(&mut self) {
+		SIGNAL_PIPE.store(-1, Ordering::SeqCst);
+
+		unsafe {
+			libc::ioctl(self.tty, VT_SETMODE, &self.vt_mode);
+			libc::ioctl(self.tty, KDSKBMODE, self.kb_mode);
+			libc::ioctl(self.tty, KDSETMODE, self.kd_mode);
+			libc::signal(libc::SIGUSR1, libc::SIG_DFL);
+			libc::signal(libc::SIGUSR2, libc::SIG_DFL);
+			libc::close(self.tty);
+			libc::close(self.read);
+			libc::close(self.write);
+		}
+	}
+}
+
+// Only async-signal-safe calls in here.
+extern "C" fn signal(signal: i32) {
+	let fd = SIGNAL_PIPE.load(Ordering::SeqCst);
+
+	if fd >= 0 {
+		let signal = signal as u8;
+
+		unsafe {
+			libc::write(fd, &signal as *const u8
+				as *const libc::c_void, 1);
+		}
+	}
+}
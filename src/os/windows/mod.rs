@@ -10,12 +10,33 @@ mod window_create;
 mod window_poll_event;
 
 use winapi::ctypes::c_int;
-use input::keyboard;
+use Key;
 use c_void;
 use input::InputQueue;
 
 use winapi::shared::windef::HWND;
 use winapi::shared::minwindef::{ WPARAM, LPARAM, LRESULT, HINSTANCE };
+use winapi::um::winuser::{
+	MsgWaitForMultipleObjectsEx, PostMessageW, QS_ALLINPUT, WM_NULL,
+	MWMO_INPUTAVAILABLE,
+	FlashWindowEx, FLASHWINFO, FLASHW_ALL, FLASHW_TIMERNOFG,
+	GetDoubleClickTime, GetSystemMetrics, SM_CXDRAG,
+};
+use winapi::um::winbase::{INFINITE, SetThreadExecutionState};
+use winapi::um::winnt::{ES_CONTINUOUS, ES_DISPLAY_REQUIRED};
+
+use std::ptr::{null, null_mut};
+use std::time::Duration;
+
+/// Handle to wake up a window that's waiting for events.
+#[derive(Clone)]
+pub struct Wake(usize); // HWND isn't `Send`.
+
+impl Wake {
+	pub fn wake(&self) {
+		unsafe { PostMessageW(self.0 as HWND, WM_NULL, 0, 0) };
+	}
+}
 
 struct Connection { native: HINSTANCE }
 impl Connection {
@@ -52,9 +73,13 @@ pub struct Window {
 	connection: Connection,
 	miw: bool, // Mouse In Window
 	wh: (u16, u16),
+	xy: (i32, i32),
+	// Window created by another library, which handles it's input.
+	external: bool,
 }
 impl Window {
-	pub fn new(_title: &str, _icon: &::afi::Video, _v: Option<i32>) -> Self
+	pub fn new(_title: &str, _icon: &::afi::Video, _v: Option<i32>,
+		_builder: &::WindowBuilder) -> Self
 	{
 		let connection = Connection::create();
 		let class = Class::create(&connection, _title,
@@ -63,18 +88,63 @@ impl Window {
 			class);
 
 		Window { connection: connection, window: window, miw: true,
-			wh: (640, 360),
+			wh: (640, 360), xy: (0, 0), external: false,
+		}
+	}
+
+	/// Wrap a window created by another library, only to render to it.
+	/// The other library handles it's input.
+	pub fn external(connection: ::WindowConnection, wh: (u16, u16)) -> Self {
+		let (connection, window) = match connection {
+			::WindowConnection::Windows(connection, window) => {
+				(connection as HINSTANCE, window as HWND)
+			}
+			_ => (null_mut(), null_mut()),
+		};
+
+		Window { connection: Connection { native: connection },
+			window: NativeWindow { native: window }, miw: true,
+			wh, xy: (0, 0), external: true,
 		}
 	}
 
 	pub fn poll_event(&mut self, input: &mut InputQueue,
 		keyboard: &mut ::Keyboard) -> bool
 	{
+		if self.external {
+			return false;
+		}
+
 		let miw = &mut self.miw;
 		let window = self.window.native;
 
 		window_poll_event::window_poll_event(window, input, miw,
-			keyboard, &mut self.wh)
+			keyboard, &mut self.wh, &mut self.xy)
+	}
+
+	/// Block until there's a message, `Wake::wake()` is called, or the
+	/// timeout (if any) runs out.
+	pub fn wait(&mut self, timeout: Option<Duration>) {
+		let timeout = match timeout {
+			// Long timeouts wait as long as possible, short of
+			// `INFINITE`.
+			Some(t) => {
+				t.as_millis().min(INFINITE as u128 - 1) as u32
+			}
+			None => INFINITE,
+		};
+
+		// Don't wait if there's input already on the queue, but not
+		// peeked at yet.
+		unsafe {
+			MsgWaitForMultipleObjectsEx(0, null(), timeout,
+				QS_ALLINPUT, MWMO_INPUTAVAILABLE);
+		}
+	}
+
+	/// Get a handle to interrupt `wait()` from another thread.
+	pub fn wake(&self) -> Wake {
+		Wake(self.window.native as usize)
 	}
 
 	pub fn get_connection(&self) -> ::WindowConnection {
@@ -85,86 +155,137 @@ impl Window {
 	pub fn wh(&self) -> (u16, u16) {
 		self.wh
 	}
+
+	/// Flash the window in the taskbar until it's focused.
+	pub fn attention(&mut self) {
+		let mut info = FLASHWINFO {
+			cbSize: ::std::mem::size_of::<FLASHWINFO>() as u32,
+			hwnd: self.window.native,
+			dwFlags: FLASHW_ALL | FLASHW_TIMERNOFG,
+			uCount: 0,
+			dwTimeout: 0,
+		};
+
+		unsafe { FlashWindowEx(&mut info) };
+	}
+
+	/// Keep the display on (or let it turn off).
+	pub fn inhibit_screensaver(&mut self, inhibit: bool)
+		-> Result<(), String>
+	{
+		let flags = if inhibit {
+			ES_CONTINUOUS | ES_DISPLAY_REQUIRED
+		} else {
+			ES_CONTINUOUS
+		};
+
+		// Returns the last state, or 0 if it failed.
+		if unsafe { SetThreadExecutionState(flags) } == 0 {
+			return Err("SetThreadExecutionState failed".to_string());
+		}
+
+		Ok(())
+	}
+
+	/// Keys are virtual keys, which already follow the keyboard layout.
+	pub fn key_label(&self, _key: Key) -> Option<String> {
+		None
+	}
+
+	/// Get the double-click time and drag threshold from the system.
+	pub fn click_settings(&self) -> (f32, u16) {
+		let time = unsafe { GetDoubleClickTime() } as f32 / 1000.0;
+		let drag = unsafe { GetSystemMetrics(SM_CXDRAG) };
+
+		(time, drag.max(0) as u16)
+	}
+
+	/// The external window was resized.
+	pub fn resize(&mut self, wh: (u16, u16)) {
+		if self.external {
+			self.wh = wh;
+		}
+	}
 }
 
 // Keycode translator
-fn key(physical_key: c_int) -> Option<u8> {
+fn key(physical_key: c_int) -> Option<Key> {
 	const RCTRL: c_int = (17 | (0b_1_0001_1101 << 16));
 	const RSHIFT: c_int = (16 | (0b_0011_0110 << 16));
 	const ALT_GR: c_int = (18 | (0b_1_0011_1000 << 16));
 
 	Some(match physical_key {
-		192 => keyboard::EXT_BACKTICK,
-		107 => keyboard::EXT_PLUS,
-		106 => keyboard::EXT_ASTERISK,
-		111 | 191 => keyboard::SLASH,
-		13 => keyboard::ENTER,
-		49 | 97 => keyboard::NUM1,
-		50 | 98 => keyboard::NUM2,
-		51 | 99 => keyboard::NUM3,
-		52 | 100 => keyboard::NUM4,
-		53 | 101 => keyboard::NUM5,
-		54 | 102 => keyboard::NUM6,
-		55 | 103 => keyboard::NUM7,
-		56 | 104 => keyboard::NUM8,
-		57 | 105 => keyboard::NUM9,
-		48 | 96 => keyboard::NUM0,
-		190 | 110 => keyboard::PERIOD,
-		189 | 109 => keyboard::MINUS,
-		187 => keyboard::EQUAL_SIGN,
-		8 => keyboard::BACKSPACE,
-		9 => keyboard::TAB,
-		65 => keyboard::A,
-		66 => keyboard::B,
-		67 => keyboard::C,
-		68 => keyboard::D,
-		69 => keyboard::E,
-		70 => keyboard::F,
-		71 => keyboard::G,
-		72 => keyboard::H,
-		73 => keyboard::I,
-		74 => keyboard::J,
-		75 => keyboard::K,
-		76 => keyboard::L,
-		77 => keyboard::M,
-		78 => keyboard::N,
-		79 => keyboard::O,
-		80 => keyboard::P,
-		81 => keyboard::Q,
-		82 => keyboard::R,
-		83 => keyboard::S,
-		84 => keyboard::T,
-		85 => keyboard::U,
-		86 => keyboard::V,
-		87 => keyboard::W,
-		88 => keyboard::X,
-		89 => keyboard::Y,
-		90 => keyboard::Z,
-		219 => keyboard::BRACKET_OPEN,
-		221 => keyboard::BRACKET_CLOSE,
-		17 => keyboard::LCTRL,
-		RCTRL => keyboard::RCTRL,
-		16 => keyboard::LSHIFT,
-		RSHIFT => keyboard::RSHIFT,
-		18 => keyboard::ALT,
-		ALT_GR => keyboard::EXT_ALT_GR,
-		20 => keyboard::COMPOSE,
-		186 => keyboard::SEMICOLON,
-		222 => keyboard::APOSTROPHE,
-		220 => keyboard::BACKSLASH,
-		188 => keyboard::COMMA,
-		32 => keyboard::SPACE,
-		144 => keyboard::EXT_NUM_LOCK,
-		36 => keyboard::EXT_HOME,
-		35 => keyboard::EXT_END,
-		33 => keyboard::EXT_PAGE_UP,
-		34 => keyboard::EXT_PAGE_DOWN,
-		45 => keyboard::EXT_INSERT,
-		46 => keyboard::EXT_DELETE,
-		38 => keyboard::UP,
-		37 => keyboard::LEFT,
-		39 => keyboard::RIGHT,
-		40 => keyboard::DOWN,
+		192 => Key::Backtick,
+		107 => Key::Plus,
+		106 => Key::Asterisk,
+		111 | 191 => Key::Slash,
+		13 => Key::Enter,
+		49 | 97 => Key::Num1,
+		50 | 98 => Key::Num2,
+		51 | 99 => Key::Num3,
+		52 | 100 => Key::Num4,
+		53 | 101 => Key::Num5,
+		54 | 102 => Key::Num6,
+		55 | 103 => Key::Num7,
+		56 | 104 => Key::Num8,
+		57 | 105 => Key::Num9,
+		48 | 96 => Key::Num0,
+		190 | 110 => Key::Period,
+		189 | 109 => Key::Minus,
+		187 => Key::EqualSign,
+		8 => Key::Backspace,
+		9 => Key::Tab,
+		65 => Key::A,
+		66 => Key::B,
+		67 => Key::C,
+		68 => Key::D,
+		69 => Key::E,
+		70 => Key::F,
+		71 => Key::G,
+		72 => Key::H,
+		73 => Key::I,
+		74 => Key::J,
+		75 => Key::K,
+		76 => Key::L,
+		77 => Key::M,
+		78 => Key::N,
+		79 => Key::O,
+		80 => Key::P,
+		81 => Key::Q,
+		82 => Key::R,
+		83 => Key::S,
+		84 => Key::T,
+		85 => Key::U,
+		86 => Key::V,
+		87 => Key::W,
+		88 => Key::X,
+		89 => Key::Y,
+		90 => Key::Z,
+		219 => Key::BracketOpen,
+		221 => Key::BracketClose,
+		17 => Key::LCtrl,
+		RCTRL => Key::RCtrl,
+		16 => Key::LShift,
+		RSHIFT => Key::RShift,
+		18 => Key::Alt,
+		ALT_GR => Key::AltGr,
+		20 => Key::Compose,
+		186 => Key::Semicolon,
+		222 => Key::Apostrophe,
+		220 => Key::Backslash,
+		188 => Key::Comma,
+		32 => Key::Space,
+		144 => Key::NumLock,
+		36 => Key::Home,
+		35 => Key::End,
+		33 => Key::PageUp,
+		34 => Key::PageDown,
+		45 => Key::Insert,
+		46 => Key::Delete,
+		38 => Key::Up,
+		37 => Key::Left,
+		39 => Key::Right,
+		40 => Key::Down,
 		_ => return None,
 	} )
 }
\ No newline at end of file
@@ -14,6 +14,7 @@ use winapi::um::winuser::{
 	WM_RBUTTONDOWN, WM_RBUTTONUP, WM_CLOSE, WM_MOUSEMOVE, WM_HSCROLL, 
 	WM_CHAR, WM_SYSCHAR, VK_RSHIFT,
 	PeekMessageW, TranslateMessage, DispatchMessageW, GetCursorPos,
+	SetCapture, ReleaseCapture,
 	ScreenToClient, PostQuitMessage, DefWindowProcW,
 	MSG,
 };
@@ -23,10 +24,17 @@ use winapi::shared::minwindef::{ WPARAM, LPARAM, LRESULT, HIWORD, LOWORD, DWORD
 
 static mut ADI_WNDPROCMSG : u8 = 0b0000_0000;
 static mut AWI_DIMENSIONS: (u16, u16) = (0, 0);
+static mut AWI_POSITION: (i32, i32) = (0, 0);
+// Whether the window is minimized, so restoring it is `Shown`.
+static mut AWI_MINIMIZED: bool = false;
 
 const RESIZED: u8 = 0b1000_0000;
 const PAUSED: u8 = 0b0100_0000;
 const RESUMED: u8 = 0b0010_0000;
+const MOVED: u8 = 0b0001_0000;
+const MINIMIZED: u8 = 0b0000_1000;
+const SHOWN: u8 = 0b0000_0100;
+const REDRAW: u8 = 0b0000_0010;
 
 pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 	l_param: LPARAM) -> LRESULT
@@ -41,6 +49,21 @@ pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 		0x0005 => {
 			unsafe { ADI_WNDPROCMSG |= RESIZED };
 
+			match w_param {
+				1 /*SIZE_MINIMIZED*/ => unsafe {
+					AWI_MINIMIZED = true;
+					ADI_WNDPROCMSG |= MINIMIZED
+				},
+				// Not just resized.
+				0 | 2 /*SIZE_RESTORED | SIZE_MAXIMIZED*/ => unsafe {
+					if AWI_MINIMIZED {
+						AWI_MINIMIZED = false;
+						ADI_WNDPROCMSG |= SHOWN
+					}
+				},
+				_ => {}
+			}
+
 			let h = HIWORD(l_param as DWORD);
 			let w = LOWORD(l_param as DWORD);
 
@@ -48,6 +71,18 @@ pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 
 			return 0;
 		},
+		0x000F => unsafe { ADI_WNDPROCMSG |= REDRAW }, // WM_PAINT
+		0x0003 => {
+			unsafe { ADI_WNDPROCMSG |= MOVED };
+
+			// Signed, for windows on monitors left of the primary.
+			let y = HIWORD(l_param as DWORD) as i16;
+			let x = LOWORD(l_param as DWORD) as i16;
+
+			unsafe { AWI_POSITION = (x as i32, y as i32); }
+
+			return 0;
+		},
 		_ => {},
 	}
 
@@ -91,7 +126,8 @@ fn create_key_id(w: WPARAM, l: LPARAM) -> c_int {
 }
 
 pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
-	miw: &mut bool, keyboard: &mut ::Keyboard, wh2: &mut (u16, u16)) -> bool
+	miw: &mut bool, keyboard: &mut ::Keyboard, wh2: &mut (u16, u16),
+	xy: &mut (i32, i32)) -> bool
 {
 	if unsafe { ADI_WNDPROCMSG & RESIZED != 0 } {
 		println!("RESIZE {:?}", unsafe { AWI_DIMENSIONS });
@@ -100,6 +136,30 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 		return true;
 	}
 	
+	if unsafe { ADI_WNDPROCMSG & MINIMIZED != 0 } {
+		queue.minimized();
+		unsafe { ADI_WNDPROCMSG &= !MINIMIZED };
+		return true;
+	}
+
+	if unsafe { ADI_WNDPROCMSG & SHOWN != 0 } {
+		queue.shown();
+		unsafe { ADI_WNDPROCMSG &= !SHOWN };
+		return true;
+	}
+
+	if unsafe { ADI_WNDPROCMSG & REDRAW != 0 } {
+		queue.redraw();
+		unsafe { ADI_WNDPROCMSG &= !REDRAW };
+		return true;
+	}
+
+	if unsafe { ADI_WNDPROCMSG & MOVED != 0 } {
+		queue.moved(xy, unsafe { AWI_POSITION });
+		unsafe { ADI_WNDPROCMSG &= !MOVED };
+		return true;
+	}
+
 	if unsafe { ADI_WNDPROCMSG & PAUSED != 0 } {
 		queue.pause();
 		unsafe { ADI_WNDPROCMSG &= !PAUSED };
@@ -211,5 +271,19 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 		}
 	};
 
+	// Keep getting the cursor outside of the window while a mouse button
+	// is held.
+	match msg.message {
+		WM_LBUTTONDOWN | WM_MBUTTONDOWN | WM_RBUTTONDOWN => unsafe {
+			SetCapture(window);
+		},
+		WM_LBUTTONUP | WM_MBUTTONUP | WM_RBUTTONUP => {
+			if !queue.captured() {
+				unsafe { ReleaseCapture(); }
+			}
+		}
+		_ => {}
+	}
+
 	true
 }
\ No newline at end of file
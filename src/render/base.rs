@@ -14,6 +14,11 @@ use std::cmp::Ordering;
 pub use	afi;
 pub use afi::VFrame;
 pub use Event;
+pub use Overflow;
+pub use {Axis, AxisConfig, Calibration, Effect, Key};
+pub use Waker;
+pub use WindowConnection;
+pub use std::time::Duration;
 pub use self::ami::*;
 pub use std::f32::consts::PI;
 
@@ -21,12 +26,70 @@ pub use std::f32::consts::PI;
 pub trait Display {
 	/// Set the background color for the `Display`.
 	///
-	/// * `color`: The background color for the display.
-	fn color(&mut self, color: (u8, u8, u8)) -> ();
+	/// * `color`: The background color for the display, and it's alpha
+	///   (ignored unless the window is transparent).
+	fn color(&mut self, color: (u8, u8, u8, u8)) -> ();
 
 	/// Get input, if there's any.
 	fn input(&mut self) -> Option<Event>;
 
+	/// Limit the number of queued input events, `None` for no limit.
+	fn input_capacity(&mut self, capacity: Option<usize>,
+		overflow: Overflow) -> ();
+
+	/// Set whether consecutive cursor, scroll, touch move and gesture events
+	/// are merged.
+	fn input_coalesce(&mut self, coalesce: bool) -> ();
+
+	/// Add controller mappings in SDL's `gamecontrollerdb.txt` format.
+	/// Returns how many were added.
+	fn controller_mappings(&mut self, mappings: &str) -> usize;
+
+	/// Set the deadzone and response curve of a controller axis.
+	fn axis_config(&mut self, axis: Axis, config: AxisConfig) -> ();
+
+	/// Start (`true`) or finish (`false`) calibrating controller `id`.
+	fn calibrate(&mut self, id: usize, calibrate: bool) -> ();
+
+	/// Get the name of controller `id`, found when it was plugged in.
+	fn controller_name(&self, id: usize) -> Option<String>;
+
+	/// Get the calibration of `axis` on controller `id`.
+	fn calibration(&self, id: usize, axis: Axis) -> Calibration;
+
+	/// Set the calibration of `axis` on controller `id`.
+	fn set_calibration(&mut self, id: usize, axis: Axis,
+		calibration: Calibration) -> ();
+
+	/// Play a force feedback effect on controller `id` for `duration`
+	/// (0 to stop), replacing the last one.  Returns why, if the
+	/// controller can't.
+	fn force_feedback(&mut self, id: usize, effect: Effect,
+		duration: Duration) -> Result<(), String>;
+
+	/// Block until there's input, a `Waker` is used, or the timeout (if
+	/// any) runs out.
+	fn wait(&mut self, timeout: Option<Duration>) -> ();
+
+	/// Get a handle for other threads to interrupt `wait()`.
+	fn waker(&self) -> Waker;
+
+	/// Ask the window manager to get the user's attention (flash the
+	/// window in the taskbar, etc.), if the window isn't focused.
+	fn attention(&mut self) -> ();
+
+	/// Keep the screensaver and idle blanking from starting (`true`), or
+	/// let them start again (`false`).  Returns why, if it can't.
+	fn inhibit_screensaver(&mut self, inhibit: bool) -> Result<(), String>;
+
+	/// Get what the keyboard layout prints on a key that types a character
+	/// ("Z" for `Key::Y` on QWERTZ), if known.
+	fn key_label(&self, key: Key) -> Option<String>;
+
+	/// Get the system's double-click time (seconds) and drag threshold
+	/// (pixels).
+	fn click_settings(&self) -> (f32, u16);
+
 	/// Update the `Display`.
 	fn update(&mut self) -> f32;
 
@@ -100,6 +163,9 @@ pub trait Display {
 	/// Get the width and height of the window, as a tuple.
 	fn wh(&self) -> (u16, u16);
 
+	/// Get the native handles of the window.  See `WindowConnection`.
+	fn connection(&self) -> WindowConnection;
+
 	// 
 	fn draw(&self, writer: &Fn(u16, u16) -> [u8; 4]) -> ();
 }
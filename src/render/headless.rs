@@ -0,0 +1,216 @@
+// Copyright Jeron A. Lau 2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Headless implementation for adi_gpu, which doesn't draw anything (not
+//! even offscreen: there are no pixels to read back).
+
+use std::time::Instant;
+
+use super::base;
+use super::base::*;
+
+/// A `Display` for the headless window, which keeps track of handles but
+/// doesn't draw anything.
+pub struct Display {
+	window: ::Window,
+	models: usize,
+	textures: usize,
+	gradients: usize,
+	texcoords: usize,
+	shapes: u32,
+	earlier: Instant,
+}
+
+pub fn new(builder: &::WindowBuilder) -> Result<Box<Display>, String> {
+	// There's nothing drawn to show in it.
+	if builder.external.is_some() {
+		return Err("Headless can't render to external windows"
+			.to_string());
+	}
+
+	let window = ::Window::new(None, builder);
+
+	Ok(Box::new(Display {
+		window, models: 0, textures: 0, gradients: 0, texcoords: 0,
+		shapes: 0, earlier: Instant::now(),
+	}))
+}
+
+impl Display {
+	fn shape(&mut self) -> Shape {
+		self.shapes += 1;
+		base::new_shape(ShapeHandle::Opaque(self.shapes - 1))
+	}
+}
+
+impl base::Display for Display {
+	fn color(&mut self, _color: (u8, u8, u8, u8)) {}
+
+	fn input(&mut self) -> Option<base::Event> {
+		self.window.update()
+	}
+
+	fn input_capacity(&mut self, capacity: Option<usize>,
+		overflow: base::Overflow)
+	{
+		self.window.input_capacity(capacity, overflow)
+	}
+
+	fn input_coalesce(&mut self, coalesce: bool) {
+		self.window.input_coalesce(coalesce)
+	}
+
+	fn controller_mappings(&mut self, mappings: &str) -> usize {
+		self.window.controller_mappings(mappings)
+	}
+
+	fn axis_config(&mut self, axis: Axis, config: AxisConfig) {
+		self.window.axis_config(axis, config)
+	}
+
+	fn calibrate(&mut self, id: usize, calibrate: bool) {
+		self.window.calibrate(id, calibrate)
+	}
+
+	fn controller_name(&self, id: usize) -> Option<String> {
+		self.window.controller_name(id)
+	}
+
+	fn calibration(&self, id: usize, axis: Axis) -> Calibration {
+		self.window.calibration(id, axis)
+	}
+
+	fn set_calibration(&mut self, id: usize, axis: Axis,
+		calibration: Calibration)
+	{
+		self.window.set_calibration(id, axis, calibration)
+	}
+
+	fn force_feedback(&mut self, id: usize, effect: Effect,
+		duration: Duration) -> Result<(), String>
+	{
+		self.window.force_feedback(id, effect, duration)
+	}
+
+	fn wait(&mut self, timeout: Option<Duration>) {
+		self.window.wait(timeout)
+	}
+
+	fn waker(&self) -> Waker {
+		self.window.waker()
+	}
+
+	fn attention(&mut self) {
+		self.window.attention()
+	}
+
+	fn inhibit_screensaver(&mut self, inhibit: bool) -> Result<(), String> {
+		self.window.inhibit_screensaver(inhibit)
+	}
+
+	fn key_label(&self, key: Key) -> Option<String> {
+		self.window.key_label(key)
+	}
+
+	fn click_settings(&self) -> (f32, u16) {
+		self.window.click_settings()
+	}
+
+	fn update(&mut self) -> f32 {
+		// Get the time step for the next frame.
+		let new = Instant::now();
+		let dt = new.duration_since(self.earlier);
+		self.earlier = new;
+
+		dt.as_secs() as f32 + dt.subsec_nanos() as f32 / 1_000_000_000.0
+	}
+
+	fn model(&mut self, _vertices: &[f32], _fans: Vec<(u32, u32)>)
+		-> Model
+	{
+		self.models += 1;
+		Model(self.models - 1)
+	}
+
+	fn texture(&mut self, wh: (u16,u16), _graphic: &VFrame) -> Texture {
+		self.textures += 1;
+		Texture(self.textures - 1, wh.0, wh.1)
+	}
+
+	fn gradient(&mut self, _colors: &[f32]) -> Gradient {
+		self.gradients += 1;
+		Gradient(self.gradients - 1)
+	}
+
+	fn texcoords(&mut self, _texcoords: &[(f32, f32)]) -> TexCoords {
+		self.texcoords += 1;
+		TexCoords(self.texcoords - 1)
+	}
+
+	fn set_texture(&mut self, texture: &mut Texture, wh: (u16,u16),
+		_graphic: &VFrame)
+	{
+		texture.1 = wh.0;
+		texture.2 = wh.1;
+	}
+
+	fn shape_solid(&mut self, _model: &Model, _transform: Matrix,
+		_color: [f32; 4], _blending: bool, _fog: bool, _camera: bool)
+		-> Shape
+	{
+		self.shape()
+	}
+
+	fn shape_gradient(&mut self, _model: &Model, _transform: Matrix,
+		_gradient: Gradient, _blending: bool, _fog: bool,
+		_camera: bool) -> Shape
+	{
+		self.shape()
+	}
+
+	fn shape_texture(&mut self, _model: &Model, _transform: Matrix,
+		_texture: &Texture, _tc: TexCoords, _blending: bool,
+		_fog: bool, _camera: bool) -> Shape
+	{
+		self.shape()
+	}
+
+	fn shape_faded(&mut self, _model: &Model, _transform: Matrix,
+		_texture: &Texture, _tc: TexCoords, _alpha: f32,
+		_fog: bool, _camera: bool) -> Shape
+	{
+		self.shape()
+	}
+
+	fn shape_tinted(&mut self, _model: &Model, _transform: Matrix,
+		_texture: &Texture, _tc: TexCoords, _tint: [f32; 4],
+		_blending: bool, _fog: bool, _camera: bool) -> Shape
+	{
+		self.shape()
+	}
+
+	fn shape_complex(&mut self, _model: &Model, _transform: Matrix,
+		_texture: &Texture, _tc: TexCoords, _gradient: Gradient,
+		_blending: bool, _fog: bool, _camera: bool) -> Shape
+	{
+		self.shape()
+	}
+
+	fn drop_shape(&mut self, _shape: &Shape) {}
+
+	fn transform(&self, _shape: &Shape, _transform: Matrix) {}
+
+	fn resize(&mut self, _wh: (u16, u16)) {}
+
+	fn wh(&self) -> (u16, u16) {
+		self.window.wh()
+	}
+
+	fn connection(&self) -> WindowConnection {
+		self.window.get_connection()
+	}
+
+	fn draw(&self, _writer: &Fn(u16, u16) -> [u8; 4]) {}
+}
@@ -9,27 +9,42 @@ mod base;
 
 pub use self::base::*;
 
-#[cfg(any(
+#[cfg(all(not(feature = "headless"), any(
 	target_os="macos", target_os="android", target_os="linux",
 	target_os="windows", target_os="nintendo_switch"
-))] mod vulkan;
+)))] mod vulkan;
 
-#[cfg(any(
+#[cfg(all(not(feature = "headless"), any(
 	target_os="android", target_os="linux", target_os="windows",
 	target_os="web"
-))] mod opengl;
+)))] mod opengl;
+
+#[cfg(feature = "headless")] mod headless;
 
 /// Create a new Vulkan / OpenGL Display.
-pub fn new_display() -> Result<Box<Display>, String> {
+pub fn new_display(builder: &::WindowBuilder)
+	-> Result<Box<Display>, String>
+{
 	let mut err = "".to_string();
 
-	// Try Vulkan first.
-	#[cfg(any(
+	// Headless doesn't render anything.
+	#[cfg(feature = "headless")]
+	{
+		match headless::new(builder) {
+			Ok(headless) => return Ok(headless),
+			Err(headless) => err.push_str(&headless),
+		}
+		err.push('\n');
+	}
+
+	// Try Vulkan first.  Without a display server (feature `kiosk`),
+	// Vulkan renders with `VK_KHR_display`, and OpenGL with KMS.
+	#[cfg(all(not(feature = "headless"), any(
 		target_os="macos", target_os="android", target_os="linux",
 		target_os="windows", target_os="nintendo_switch"
-	))]
+	)))]
 	{
-		match vulkan::new() {
+		match vulkan::new(builder) {
 			Ok(vulkan) => return Ok(vulkan),
 			Err(vulkan) => err.push_str(&vulkan),
 		}
@@ -37,11 +52,11 @@ pub fn new_display() -> Result<Box<Display>, String> {
 	}
 
 	// Fallback on OpenGL/OpenGLES
-	#[cfg(any(
+	#[cfg(all(not(feature = "headless"), any(
 		target_os="android", target_os="linux", target_os="windows",
-	))]
+	)))]
 	{
-		match opengl::new() {
+		match opengl::new(builder) {
 			Ok(opengl) => return Ok(opengl),
 			Err(opengl) => err.push_str(opengl),
 		}
@@ -52,3 +67,18 @@ pub fn new_display() -> Result<Box<Display>, String> {
 	err.push_str("No more backend options");
 	Err(err)
 }
+
+/// Create a new Vulkan Display that renders to a window created by another
+/// library (an XCB connection and window, or a Wayland display and surface),
+/// with size `wh`.  The other library handles input, so call
+/// `Display::resize()` when the window is resized.  OpenGL and the headless
+/// backend can't render to external windows, so this returns an error if
+/// Vulkan isn't available.
+pub fn new_display_external(connection: ::WindowConnection, wh: (u16, u16))
+	-> Result<Box<Display>, String>
+{
+	let mut builder = ::WindowBuilder::new();
+	builder.external = Some((connection, wh));
+
+	new_display(&builder)
+}
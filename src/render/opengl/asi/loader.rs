@@ -106,17 +106,24 @@ impl Lib {
 
 	/// Initialize the opengl (connect to the display)
 	#[cfg(windows)]
-	pub fn init(&self) -> (Display, i32) {
+	pub fn init(&self, _alpha: bool, _native: *mut c_void,
+		_visuals: &[i32]) -> (Display, i32)
+	{
 		(Display {
 			dc: None,
 		}, 0)
 	}
 	
-	/// Initialize the opengl (connect to the display)
+	/// Initialize the opengl (connect to the `native` display, or the
+	/// default if null).  Chooses a config with one of the native visual
+	/// ids in `visuals` (ARGB X11 visuals, or the format of a GBM surface)
+	/// if there is one.  Returns the config's native visual id.
 	#[cfg(not(windows))]
-	pub fn init(&self) -> (Display, i32) {
+	pub fn init(&self, alpha: bool, native: EGLNativeDisplayType,
+		visuals: &[i32]) -> (Display, i32)
+	{
 		let display = unsafe {
-			(self.gl.eglGetDisplay)(ptr::null_mut())
+			(self.gl.eglGetDisplay)(native)
 		};
 		if display.is_null() {
 			panic!("EGL: Couldn't load display.");
@@ -130,25 +137,49 @@ impl Lib {
 		}
 
 		// Config
-		let mut config = ptr::null_mut();
-		let mut nconfigs = unsafe { mem::uninitialized() };
-
+		let attribs = [
+			EGL_RED_SIZE, 8,
+			EGL_GREEN_SIZE, 8,
+			EGL_BLUE_SIZE, 8,
+			EGL_ALPHA_SIZE, if alpha { 8 } else { 0 },
+			EGL_DEPTH_SIZE, 24,
+			EGL_NONE
+		];
+		let mut nconfigs = 0;
+
+		// Count the configs, then get all of them.
 		if unsafe {
-			(self.gl.eglChooseConfig)(display, [
-				EGL_RED_SIZE, 8,
-				EGL_GREEN_SIZE, 8,
-				EGL_BLUE_SIZE, 8,
-				EGL_DEPTH_SIZE, 24,
-				EGL_NONE
-			].as_ptr(), &mut config, 1, &mut nconfigs)
+			(self.gl.eglChooseConfig)(display, attribs.as_ptr(),
+				ptr::null_mut(), 0, &mut nconfigs)
 		} == 0 {
 			panic!("Couldn't choose the config");
 		}
 
-		if nconfigs == 0 {
+		if nconfigs <= 0 {
 			panic!("No configs!");
 		}
 
+		let mut configs = vec![ptr::null_mut(); nconfigs as usize];
+
+		if unsafe {
+			(self.gl.eglChooseConfig)(display, attribs.as_ptr(),
+				configs.as_mut_ptr(), nconfigs, &mut nconfigs)
+		} == 0 || nconfigs <= 0 {
+			panic!("Couldn't choose the config");
+		}
+
+		let configs = &configs[..nconfigs as usize];
+		let config = configs.iter().find(|&&config| {
+			let mut id = 0;
+
+			unsafe {
+				(self.gl.eglGetConfigAttrib)(display, config,
+					EGL_NATIVE_VISUAL_ID, &mut id)
+			};
+
+			visuals.contains(&id)
+		}).cloned().unwrap_or(configs[0]);
+
 		if unsafe { (self.gl.eglBindAPI)(EGL_OPENGL_ES_API) } == 0 {
 			panic!("Couldn't bind OpenGLES");
 		}
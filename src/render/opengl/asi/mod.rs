@@ -70,10 +70,16 @@ pub struct OpenGLBuilder {
 }
 
 impl OpenGLBuilder {
-	/// Begin the building.
-	pub fn new() -> Option<(OpenGLBuilder, i32)> {
+	/// Begin the building.  If `alpha`, choose a config with an alpha
+	/// channel (for transparent windows).  `native` is the native display
+	/// (null for the default), and `visual` the native visual id the config
+	/// must have, if any.
+	pub fn new(alpha: bool, native: *mut c_void, visuals: &[i32])
+		-> Option<(OpenGLBuilder, i32)>
+	{
 		if let Ok(lib) = loader::Lib::new() {
-			let (mut display, visual_id) = lib.init();
+			let (mut display, visual_id) = lib.init(alpha, native,
+				visuals);
 
 			Some((OpenGLBuilder {
 				lib,
@@ -205,8 +211,8 @@ struct OpenGLContext {
 
 impl OpenGL {
 	/// Set the color for `clear`.
-	pub fn color(&self, r: f32, g: f32, b: f32) {
-		gl!(self, (self.get().clear_color)(r, g, b, 1.0));
+	pub fn color(&self, r: f32, g: f32, b: f32, a: f32) {
+		gl!(self, (self.get().clear_color)(r, g, b, a));
 	}
 
 	/// Update the screen
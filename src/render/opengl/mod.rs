@@ -128,7 +128,8 @@ struct TextureData {
 pub struct Display {
 	window: ::Window,
 	context: OpenGL,
-	color: (f32, f32, f32),
+	color: (f32, f32, f32, f32),
+	transparent: bool,
 	opaque_ind: Vec<u32>,
 	alpha_ind: Vec<u32>,
 	opaque_vec: Cell<Vec<ShapeData>>,
@@ -145,13 +146,61 @@ pub struct Display {
 	projection: Matrix,
 }
 
-pub fn new() -> Result<Box<Display>, &'static str> {
-	if let Some(tuple) = OpenGLBuilder::new() {
-		let (builder, v) = tuple;
-		let window = ::Window::new(Some(v));
+pub fn new(window_builder: &::WindowBuilder)
+	-> Result<Box<Display>, &'static str>
+{
+	let transparent = window_builder.transparent;
 
+	// EGL would open it's own connection to the display server, with a
+	// visual that may not match the external window's.
+	if window_builder.external.is_some() {
+		return Err("OpenGL can't render to external windows");
+	}
+
+	// X11: the window's visual comes from the EGL config, which must be
+	// a 32-bit visual for the window to be transparent.
+	#[cfg(not(feature = "kiosk"))]
+	let opengl = {
+		#[cfg(unix)]
+		let visuals = if transparent { ::os::argb_visuals() }
+			else { vec![] };
+		#[cfg(not(unix))]
+		let visuals: Vec<i32> = vec![];
+
+		OpenGLBuilder::new(transparent, ::std::ptr::null_mut(),
+			&visuals).map(|(builder, v)| {
+			if cfg!(unix) && transparent && !visuals.contains(&v) {
+				eprintln!("awi: no EGL config with a 32-bit \
+					visual, window won't be transparent");
+			}
+			(builder, ::Window::new(Some(v), window_builder))
+		})
+	};
+
+	// KMS: EGL needs the GBM device, so the window comes first, and the
+	// EGL config must match the GBM surface's format.
+	#[cfg(feature = "kiosk")]
+	let opengl = {
+		const FORMAT_XRGB8888: i32 = 0x34325258;
+		const FORMAT_ARGB8888: i32 = 0x34325241;
+
+		let format = if transparent { FORMAT_ARGB8888 }
+			else { FORMAT_XRGB8888 };
+		let window = ::Window::new(Some(format), window_builder);
+		let device = match window.get_connection() {
+			WindowConnection::Kms(device, _) if !device.is_null() => {
+				device
+			}
+			_ => return Err("Couldn't set up KMS for OpenGL"),
+		};
+
+		OpenGLBuilder::new(transparent, device, &[format])
+			.map(|(builder, _)| (builder, window))
+	};
+
+	if let Some((builder, window)) = opengl {
 		let context = builder.to_opengl(match window.get_connection() {
-			WindowConnection::Xcb(_, window) => // |
+			WindowConnection::Xcb(_, window, _) => // |
 			//	WindowConnection::Windows(_, window) =>
 			{
 				unsafe {mem::transmute(window as usize)}
@@ -159,10 +208,11 @@ pub fn new() -> Result<Box<Display>, &'static str> {
 			WindowConnection::Windows(_, window) => {
 				window
 			}
-			WindowConnection::Wayland => return Err(
+			WindowConnection::Wayland(_, _) => return Err(
 				"OpenGL support on Wayland is WIP"),
 			WindowConnection::DirectFB => return Err(
 				"OpenGL support on DirectFB is WIP"),
+			WindowConnection::Kms(_, surface) => surface,
 			WindowConnection::Android => return Err(
 				"OpenGL support on Android is WIP"),
 			WindowConnection::IOS => return Err(
@@ -210,7 +260,8 @@ pub fn new() -> Result<Box<Display>, &'static str> {
 		let mut display = self::Display {
 			window,
 			context,
-			color: (0.0, 0.0, 0.0),
+			color: (0.0, 0.0, 0.0, 1.0),
+			transparent,
 			alpha_ind: vec![],
 			opaque_ind: vec![],
 			alpha_vec: Cell::new(vec![]),
@@ -249,16 +300,86 @@ fn as_mut(slf: &Cell<Vec<ShapeData>>) -> &mut Vec<ShapeData> {
 }
 
 impl base::Display for Display {
-	fn color(&mut self, color: (u8, u8, u8)) {
-		self.color = (color.0 as f32 / 255.0,
-			color.1 as f32 / 255.0, color.2 as f32 / 255.0);
-		self.context.color(self.color.0, self.color.1, self.color.2);
+	fn color(&mut self, color: (u8, u8, u8, u8)) {
+		// Compositors expect premultiplied alpha.
+		let a = if self.transparent { color.3 as f32 / 255.0 } else { 1.0 };
+
+		self.color = (color.0 as f32 / 255.0 * a,
+			color.1 as f32 / 255.0 * a, color.2 as f32 / 255.0 * a, a);
+		self.context.color(self.color.0, self.color.1, self.color.2,
+			self.color.3);
 	}
 
 	fn input(&mut self) -> Option<base::Event> {
 		self.window.update()
 	}
 
+	fn input_capacity(&mut self, capacity: Option<usize>,
+		overflow: base::Overflow)
+	{
+		self.window.input_capacity(capacity, overflow)
+	}
+
+	fn input_coalesce(&mut self, coalesce: bool) {
+		self.window.input_coalesce(coalesce)
+	}
+
+	fn controller_mappings(&mut self, mappings: &str) -> usize {
+		self.window.controller_mappings(mappings)
+	}
+
+	fn axis_config(&mut self, axis: Axis, config: AxisConfig) {
+		self.window.axis_config(axis, config)
+	}
+
+	fn calibrate(&mut self, id: usize, calibrate: bool) {
+		self.window.calibrate(id, calibrate)
+	}
+
+	fn controller_name(&self, id: usize) -> Option<String> {
+		self.window.controller_name(id)
+	}
+
+	fn calibration(&self, id: usize, axis: Axis) -> Calibration {
+		self.window.calibration(id, axis)
+	}
+
+	fn set_calibration(&mut self, id: usize, axis: Axis,
+		calibration: Calibration)
+	{
+		self.window.set_calibration(id, axis, calibration)
+	}
+
+	fn force_feedback(&mut self, id: usize, effect: Effect,
+		duration: Duration) -> Result<(), String>
+	{
+		self.window.force_feedback(id, effect, duration)
+	}
+
+	fn wait(&mut self, timeout: Option<Duration>) {
+		self.window.wait(timeout)
+	}
+
+	fn waker(&self) -> Waker {
+		self.window.waker()
+	}
+
+	fn attention(&mut self) {
+		self.window.attention()
+	}
+
+	fn inhibit_screensaver(&mut self, inhibit: bool) -> Result<(), String> {
+		self.window.inhibit_screensaver(inhibit)
+	}
+
+	fn key_label(&self, key: Key) -> Option<String> {
+		self.window.key_label(key)
+	}
+
+	fn click_settings(&self) -> (f32, u16) {
+		self.window.click_settings()
+	}
+
 	fn update(&mut self) -> f32 {
 		// Opaque & Alpha Shapes need a camera.
 		for i in (&self.styles).iter() {
@@ -290,7 +411,12 @@ impl base::Display for Display {
 			draw_shape(&self.styles[shape.style], shape);
 		}
 
-		self.context.update()
+		let dt = self.context.update();
+
+		// KMS: show the buffer EGL just swapped to.
+		#[cfg(feature = "kiosk")] self.window.flip();
+
+		dt
 	}
 
 	fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>) -> Model {
@@ -649,6 +775,7 @@ impl base::Display for Display {
 		let xyz = self.xyz;
 		let rotate_xyz = self.rotate_xyz;
 
+		self.window.resize(wh);
 		self.ar = wh.0 as f32 / wh.1 as f32;
 		self.context.viewport(wh.0, wh.1);
 
@@ -659,6 +786,10 @@ impl base::Display for Display {
 		self.window.wh()
 	}
 
+	fn connection(&self) -> WindowConnection {
+		self.window.get_connection()
+	}
+
 	fn draw(&self, _writer: &Fn(u16, u16) -> [u8; 4]) {
 		// TODO
 	}
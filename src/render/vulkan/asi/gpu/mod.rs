@@ -12,6 +12,7 @@ use super::types::*;
 
 use std::{ rc::Rc, cell::RefCell };
 use Vector;
+use WindowConnection;
 
 mod surface;
 mod device;
@@ -66,8 +67,8 @@ pub(crate) unsafe fn sym<T>(vk: &GpuContext, name: &[u8])
 }
 
 unsafe fn create_instance(vk_create_instance: unsafe extern "system" fn(
-	*const VkInstanceCreateInfo, *mut c_void, *mut VkInstance) -> VkResult)
-	-> VkInstance
+	*const VkInstanceCreateInfo, *mut c_void, *mut VkInstance) -> VkResult,
+	connection: &WindowConnection) -> VkInstance
 {
 	// This variables must be defined separately so it stays in scope.
 	let validation = CString::new("VK_LAYER_LUNARG_standard_validation")
@@ -75,7 +76,15 @@ unsafe fn create_instance(vk_create_instance: unsafe extern "system" fn(
 	let dump = CString::new("VK_LAYER_LUNARG_api_dump").unwrap();
 	let s1 = CString::new("VK_KHR_surface").unwrap();
 	let s2 = CString::new(
-		if cfg!(target_os = "linux") {
+		if cfg!(target_os = "linux") && matches!(*connection,
+			WindowConnection::Wayland(_, _))
+		{
+			"VK_KHR_wayland_surface"
+		} else if cfg!(target_os = "linux") && matches!(*connection,
+			WindowConnection::Kms(_, _))
+		{
+			"VK_KHR_display"
+		} else if cfg!(target_os = "linux") {
 			"VK_KHR_xcb_surface"
 		} else if cfg!(target_os = "android") {
 			"VK_KHR_android_surface"
@@ -149,6 +158,9 @@ pub(crate) struct GpuContext {
 	pub(crate) command_pool: u64,
 	pub(crate) sampler: VkSampler,
 	pub(crate) rgb: Vector,
+	pub(crate) alpha: f32,
+	// Whether the swapchain is composited with alpha.
+	pub(crate) transparent: bool,
 	pub(crate) api: VulkanApi,
 	pub(crate) format: VkFormat,
 	pub(crate) extent: VkExtent2D,
@@ -288,17 +300,31 @@ pub(crate) struct GpuContext {
 
 impl Gpu {
 	/// Create the GPU context, and optionally a window to render to.
-	pub(crate) fn new(rgb: Vector) -> Result<(Gpu, ::Window), String> { unsafe {
+	pub(crate) fn new(rgb: Vector, builder: &::WindowBuilder)
+		-> Result<(Gpu, ::Window), String>
+	{ unsafe {
 		// Load the Vulkan library
 		let api = VulkanApi::new()?;
 
+		// Create the window first, to know which surface extension.
+		let mut window = ::Window::new(None, builder);
+
 		let vk = create_instance(
-			vk_sym(mem::zeroed(), &api, b"vkCreateInstance\0")?
+			vk_sym(mem::zeroed(), &api, b"vkCreateInstance\0")?,
+			&window.get_connection()
 		);
 
 		// Create Surface
-		let window = ::Window::new(None);
-		let surface = surface::new(vk, &api, window.get_connection());
+		let surface = match window.get_connection() {
+			// No window system: render straight to a display.
+			WindowConnection::Kms(_, _) => {
+				let (surface, wh) = surface::new_display(vk, &api)?;
+
+				window.resize(wh);
+				surface
+			}
+			connection => surface::new(vk, &api, connection),
+		};
 		let (gpu, pqi, sampled, format)
 			= device::get_gpu(vk, &api, surface)?;
 		let device = device::create_device(vk, &api, gpu, pqi);
@@ -319,6 +345,7 @@ impl Gpu {
 
 		Ok((Gpu(Rc::new(RefCell::new(GpuContext {
 			vk, surface, gpu, sampled, device, rgb, swapchain,
+			alpha: 1.0, transparent: builder.transparent,
 			present_queue, command_buffer, command_pool, sampler,
 			format, extent,
 			// TODO: use vkd_sym.
@@ -395,8 +422,11 @@ impl Gpu {
 	} }
 
 	/// Set the clear color.
-	pub fn color(&self, rgb: Vector) {
-		self.get_mut().rgb = rgb;
+	pub fn color(&self, rgb: Vector, alpha: f32) {
+		let mut connection = self.get_mut();
+
+		connection.rgb = rgb;
+		connection.alpha = alpha;
 	}
 
 /*	/// Update
@@ -19,19 +19,22 @@ pub(crate) fn new(vk: VkInstance, lib: &VulkanApi, connection: WindowConnection)
 	-> VkSurfaceKHR
 {
 	match connection {
-		WindowConnection::Xcb(connection,window) => {
+		WindowConnection::Xcb(connection, window, _) => {
 			if cfg!(unix) {
 				new_xcb(vk, lib, connection, window)
 			} else { unreachable!() }
 		}
-		WindowConnection::Wayland => {
-			println!("Wayland not supported yet");
-			unreachable!()
-		},
+		WindowConnection::Wayland(display, surface) => {
+			if cfg!(unix) {
+				new_wayland(vk, lib, display, surface)
+			} else { unreachable!() }
+		}
 		WindowConnection::DirectFB => {
 			println!("DirectFB not supported yet");
 			unreachable!()
 		},
+		// Created with `new_display()`, which picks the display.
+		WindowConnection::Kms(_, _) => unreachable!(),
 		WindowConnection::Windows(connection, window) => {
 			if cfg!(windows) {
 				new_windows(vk, lib, connection, window)
@@ -77,6 +80,48 @@ pub(crate) fn new(vk: VkInstance, lib: &VulkanApi, connection: WindowConnection)
 	window: u32,
 }
 
+#[repr(C)] struct SurfaceCreateInfoWayland {
+	s_type: VkStructureType,
+	p_next: *mut c_void,
+	flags: u32,
+	display: *mut c_void,
+	surface: *mut c_void,
+}
+
+#[repr(C)] struct SurfaceCreateInfoDisplay {
+	s_type: VkStructureType,
+	p_next: *mut c_void,
+	flags: u32,
+	display_mode: u64,
+	plane_index: u32,
+	plane_stack_index: u32,
+	transform: u32,
+	global_alpha: f32,
+	alpha_mode: u32,
+	image_extent: VkExtent2D,
+}
+
+#[repr(C)] struct DisplayProperties {
+	display: u64,
+	display_name: *const i8,
+	physical_dimensions: VkExtent2D,
+	physical_resolution: VkExtent2D,
+	supported_transforms: u32,
+	plane_reorder_possible: VkBool32,
+	persistent_content: VkBool32,
+}
+
+#[repr(C)] struct DisplayModeProperties {
+	display_mode: u64,
+	visible_region: VkExtent2D,
+	refresh_rate: u32,
+}
+
+#[repr(C)] struct DisplayPlaneProperties {
+	current_display: u64,
+	current_stack_index: u32,
+}
+
 #[repr(C)] struct SurfaceCreateInfoWindows {
 	s_type: VkStructureType,
 	p_next: *mut c_void,
@@ -124,6 +169,158 @@ fn new_xcb(vk: VkInstance, lib: &VulkanApi, wc: *mut c_void, w: u32)
 	surface
 }
 
+fn new_wayland(vk: VkInstance, lib: &VulkanApi, display: *mut c_void,
+	surface: *mut c_void) -> VkSurfaceKHR
+{
+	let mut vk_surface = unsafe { mem::zeroed() };
+	let surface_create_info = SurfaceCreateInfoWayland {
+		s_type: VkStructureType::SurfaceCreateInfoWayland,
+		p_next: null_mut(),
+		flags: 0,
+		display,
+		surface,
+	};
+
+	let new_surface : unsafe extern "system" fn(
+		instance: VkInstance,
+		create_info: *const SurfaceCreateInfoWayland,
+		allocator: *mut c_void,
+		surface: *mut VkSurfaceKHR) -> VkResult
+		= unsafe
+	{
+		gpu::vk_sym(vk, lib, b"vkCreateWaylandSurfaceKHR\0").unwrap()
+	};
+
+	unsafe {
+		(new_surface)(vk, &surface_create_info, null_mut(),
+			&mut vk_surface)
+		.unwrap();
+	};
+
+	vk_surface
+}
+
+/// Create a surface on the first connected display (`VK_KHR_display`), with
+/// it's largest, fastest mode.  Returns the surface, and the mode's size.
+pub(crate) fn new_display(vk: VkInstance, lib: &VulkanApi)
+	-> Result<(VkSurfaceKHR, (u16, u16)), String>
+{
+	const TRANSFORM_IDENTITY: u32 = 0x00000001;
+	const ALPHA_OPAQUE: u32 = 0x00000001;
+
+	type ListGpus = unsafe extern "system" fn(VkInstance, *mut u32,
+		*mut VkPhysicalDevice) -> VkResult;
+	type ListDisplays = unsafe extern "system" fn(VkPhysicalDevice,
+		*mut u32, *mut DisplayProperties) -> VkResult;
+	type ListModes = unsafe extern "system" fn(VkPhysicalDevice, u64,
+		*mut u32, *mut DisplayModeProperties) -> VkResult;
+	type ListPlanes = unsafe extern "system" fn(VkPhysicalDevice,
+		*mut u32, *mut DisplayPlaneProperties) -> VkResult;
+	type ListPlaneDisplays = unsafe extern "system" fn(VkPhysicalDevice,
+		u32, *mut u32, *mut u64) -> VkResult;
+	type NewSurface = unsafe extern "system" fn(VkInstance,
+		*const SurfaceCreateInfoDisplay, *mut c_void,
+		*mut VkSurfaceKHR) -> VkResult;
+
+	unsafe {
+		let list_gpus: ListGpus = gpu::vk_sym(vk, lib,
+			b"vkEnumeratePhysicalDevices\0")?;
+		let list_displays: ListDisplays = gpu::vk_sym(vk, lib,
+			b"vkGetPhysicalDeviceDisplayPropertiesKHR\0")?;
+		let list_modes: ListModes = gpu::vk_sym(vk, lib,
+			b"vkGetDisplayModePropertiesKHR\0")?;
+		let list_planes: ListPlanes = gpu::vk_sym(vk, lib,
+			b"vkGetPhysicalDeviceDisplayPlanePropertiesKHR\0")?;
+		let list_plane_displays: ListPlaneDisplays = gpu::vk_sym(vk,
+			lib, b"vkGetDisplayPlaneSupportedDisplaysKHR\0")?;
+		let new_surface: NewSurface = gpu::vk_sym(vk, lib,
+			b"vkCreateDisplayPlaneSurfaceKHR\0")?;
+
+		let mut count = 0;
+		list_gpus(vk, &mut count, null_mut()).unwrap();
+		let mut gpus = vec![mem::zeroed(); count as usize];
+		list_gpus(vk, &mut count, gpus.as_mut_ptr()).unwrap();
+
+		for gpu in gpus {
+			let mut count = 0;
+			list_displays(gpu, &mut count, null_mut()).unwrap();
+			let mut displays: Vec<DisplayProperties> = (0..count)
+				.map(|_| mem::zeroed()).collect();
+			list_displays(gpu, &mut count, displays.as_mut_ptr())
+				.unwrap();
+
+			let mut count = 0;
+			list_planes(gpu, &mut count, null_mut()).unwrap();
+			let planes = count;
+
+			for display in displays.iter().map(|d| d.display) {
+				let mut count = 0;
+				list_modes(gpu, display, &mut count, null_mut())
+					.unwrap();
+				let mut modes: Vec<DisplayModeProperties> =
+					(0..count).map(|_| mem::zeroed())
+					.collect();
+				list_modes(gpu, display, &mut count,
+					modes.as_mut_ptr()).unwrap();
+
+				let mode = match modes.iter().max_by_key(|m| (
+					m.visible_region.width
+						* m.visible_region.height,
+					m.refresh_rate,
+				)) {
+					Some(mode) => mode,
+					None => continue,
+				};
+
+				// Find a plane that can show on this display.
+				let plane = (0..planes).find(|&plane| {
+					let mut count = 0;
+					list_plane_displays(gpu, plane,
+						&mut count, null_mut()).unwrap();
+					let mut supported = vec![0; count as usize];
+					list_plane_displays(gpu, plane,
+						&mut count, supported.as_mut_ptr())
+						.unwrap();
+
+					supported.contains(&display)
+				});
+				let plane = match plane {
+					Some(plane) => plane,
+					None => continue,
+				};
+
+				let mut surface = 0;
+				let create_info = SurfaceCreateInfoDisplay {
+					s_type: VkStructureType
+						::DisplaySurfaceCreateInfo,
+					p_next: null_mut(),
+					flags: 0,
+					display_mode: mode.display_mode,
+					plane_index: plane,
+					plane_stack_index: 0,
+					transform: TRANSFORM_IDENTITY,
+					global_alpha: 1.0,
+					alpha_mode: ALPHA_OPAQUE,
+					image_extent: mode.visible_region,
+				};
+
+				if new_surface(vk, &create_info, null_mut(),
+					&mut surface) != VkResult::Success
+				{
+					continue;
+				}
+
+				return Ok((surface, (
+					mode.visible_region.width as u16,
+					mode.visible_region.height as u16,
+				)));
+			}
+		}
+	}
+
+	Err("No display to render to with VK_KHR_display".to_string())
+}
+
 fn new_windows(vk: VkInstance, lib: &VulkanApi, wc: *mut c_void, w: *mut c_void)
 	-> VkSurfaceKHR
 {
@@ -271,10 +271,12 @@ pub unsafe fn draw_begin(connection: &Gpu, render_pass: VkRenderPass,
 		0, 0, null(), 0, null(), 1, &layout_transition_barrier);
 
 	let rgb = connection.rgb;
+	// Premultiplied alpha, see `create_swapchain()`.
+	let a = if connection.transparent { connection.alpha } else { 1.0 };
 
 	// activate render pass:
 	let clear_value = [
-		VkClearValue { color: VkClearColorValue { float32: [rgb.x, rgb.y, rgb.z, 1.0] } },
+		VkClearValue { color: VkClearColorValue { float32: [rgb.x * a, rgb.y * a, rgb.z * a, a] } },
 		VkClearValue { depth_stencil: VkClearDepthStencilValue { depth: 1.0, stencil: 0 } },
 	];
 
@@ -426,6 +428,17 @@ pub unsafe fn get_buffering(connection: &Gpu) -> u32 {
 	// Update extent.
 	connection.extent = surface_info.max_image_extent;
 
+	// Blend with what's behind the window, if it's transparent and the
+	// surface supports it.
+	let premultiplied = VkCompositeAlphaFlagBitsKHR::PreMultiplied as u32;
+	connection.transparent = connection.transparent
+		&& surface_info.supported_composite_alpha & premultiplied != 0;
+	let composite_alpha = if connection.transparent {
+		VkCompositeAlphaFlagBitsKHR::PreMultiplied
+	} else {
+		VkCompositeAlphaFlagBitsKHR::Opaque
+	};
+
 	(connection.new_swapchain)(
 		connection.device,
 		&VkSwapchainCreateInfoKHR {
@@ -441,7 +454,7 @@ pub unsafe fn get_buffering(connection: &Gpu) -> u32 {
 			image_usage: VkImageUsage::ColorAttachmentBit,
 			image_sharing_mode: VkSharingMode::Exclusive,
 			pre_transform: VkSurfaceTransformFlagBitsKHR::Identity,
-			composite_alpha: VkCompositeAlphaFlagBitsKHR::Opaque,
+			composite_alpha,
 			present_mode: VkPresentModeKHR::Fifo,
 			clipped: 1/*do the clipping rendering optimization*/,
 			old_swapchain: mem::zeroed(), // vulkan->swapchain,
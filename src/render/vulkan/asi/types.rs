@@ -1314,7 +1314,9 @@ pub enum VkPresentModeKHR {
 	LoaderInstanceCreateInfo = 47,
 	LoaderDeviceCreateInfo = 48,
 	SwapchainCreateInfo = 1000001000,
+	DisplaySurfaceCreateInfo = 1000002001,
 	SurfaceCreateInfoXcb = 1000005000,
+	SurfaceCreateInfoWayland = 1000006000,
 	SurfaceCreateInfoWindows = 1000009000,
 	SurfaceCreateInfoAndroid = 1000008000,
 	PresentInfo = 1000001001,
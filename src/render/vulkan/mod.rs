@@ -28,24 +28,91 @@ pub struct Display {
 	renderer: renderer::Renderer,
 }
 
-pub fn new() -> Result<Box<Display>, String> {
+pub fn new(builder: &::WindowBuilder) -> Result<Box<Display>, String> {
 	let (renderer, window) = renderer::Renderer::new(
-		vector!()
+		vector!(), builder
 	)?;
 
 	Ok(Box::new(Display { window, renderer }))
 }
 
 impl base::Display for Display {
-	fn color(&mut self, color: (u8, u8, u8)) {
+	fn color(&mut self, color: (u8, u8, u8, u8)) {
 		self.renderer.bg_color(vector!(color.0 as f32 / 255.0,
-			color.1 as f32 / 255.0, color.2 as f32 / 255.0));
+			color.1 as f32 / 255.0, color.2 as f32 / 255.0),
+			color.3 as f32 / 255.0);
 	}
 
 	fn input(&mut self) -> Option<base::Event> {
 		self.window.update()
 	}
 
+	fn input_capacity(&mut self, capacity: Option<usize>,
+		overflow: base::Overflow)
+	{
+		self.window.input_capacity(capacity, overflow)
+	}
+
+	fn input_coalesce(&mut self, coalesce: bool) {
+		self.window.input_coalesce(coalesce)
+	}
+
+	fn controller_mappings(&mut self, mappings: &str) -> usize {
+		self.window.controller_mappings(mappings)
+	}
+
+	fn axis_config(&mut self, axis: Axis, config: AxisConfig) {
+		self.window.axis_config(axis, config)
+	}
+
+	fn calibrate(&mut self, id: usize, calibrate: bool) {
+		self.window.calibrate(id, calibrate)
+	}
+
+	fn controller_name(&self, id: usize) -> Option<String> {
+		self.window.controller_name(id)
+	}
+
+	fn calibration(&self, id: usize, axis: Axis) -> Calibration {
+		self.window.calibration(id, axis)
+	}
+
+	fn set_calibration(&mut self, id: usize, axis: Axis,
+		calibration: Calibration)
+	{
+		self.window.set_calibration(id, axis, calibration)
+	}
+
+	fn force_feedback(&mut self, id: usize, effect: Effect,
+		duration: Duration) -> Result<(), String>
+	{
+		self.window.force_feedback(id, effect, duration)
+	}
+
+	fn wait(&mut self, timeout: Option<Duration>) {
+		self.window.wait(timeout)
+	}
+
+	fn waker(&self) -> Waker {
+		self.window.waker()
+	}
+
+	fn attention(&mut self) {
+		self.window.attention()
+	}
+
+	fn inhibit_screensaver(&mut self, inhibit: bool) -> Result<(), String> {
+		self.window.inhibit_screensaver(inhibit)
+	}
+
+	fn key_label(&self, key: Key) -> Option<String> {
+		self.window.key_label(key)
+	}
+
+	fn click_settings(&self) -> (f32, u16) {
+		self.window.click_settings()
+	}
+
 	fn update(&mut self) -> f32 {
 		self.renderer.update()
 	}
@@ -146,6 +213,7 @@ impl base::Display for Display {
 	}
 
 	fn resize(&mut self, wh: (u16, u16)) -> () {
+		self.window.resize(wh);
 		self.renderer.resize(wh);
 	}
 
@@ -153,6 +221,10 @@ impl base::Display for Display {
 		self.window.wh()
 	}
 
+	fn connection(&self) -> WindowConnection {
+		self.window.get_connection()
+	}
+
 	fn draw(&self, writer: &Fn(u16, u16) -> [u8; 4]) {
 		self.renderer.draw(self.window.wh(), writer)
 	}
@@ -230,8 +230,11 @@ fn set_texture(vw: &Vw, texture: &mut Texture, writer: &Fn(u16, u16) -> [u8; 4])
 }
 
 impl Vw {
-	pub(crate) fn new(rgb: Vector) -> Result<(Vw, ::Window), String> {
-		let (mut connection, window) = super::asi::Gpu::new(rgb)?;
+	pub(crate) fn new(rgb: Vector, builder: &::WindowBuilder)
+		-> Result<(Vw, ::Window), String>
+	{
+		let (mut connection, window) = super::asi::Gpu::new(rgb,
+			builder)?;
 
 		// END BLOCK 2
 		let mut image_count = unsafe {
@@ -306,7 +309,7 @@ pub struct Renderer {
 	style_nacomplex: Style,
 	style_gui: Style,
 	projection: Matrix,
-	clear_color: (f32, f32, f32),
+	clear_color: (f32, f32, f32, f32),
 	xyz: Vector,
 	rotate_xyz: Vector,
 }
@@ -328,8 +331,10 @@ impl Renderer {
 			writer);
 	}
 
-	pub(crate) fn new(rgb: Vector) -> Result<(Renderer, ::Window), String> {
-		let (mut vw, window) = Vw::new(rgb)?;
+	pub(crate) fn new(rgb: Vector, builder: &::WindowBuilder)
+		-> Result<(Renderer, ::Window), String>
+	{
+		let (mut vw, window) = Vw::new(rgb, builder)?;
 
 		let solid_vert = super::asi::ShaderModule::new(
 			&mut vw.connection, include_bytes!(
@@ -483,7 +488,7 @@ impl Renderer {
 			style_faded,
 			style_tinted, style_natinted,
 			style_complex, style_nacomplex, style_gui,
-			clear_color: (rgb.x, rgb.y, rgb.z),
+			clear_color: (rgb.x, rgb.y, rgb.z, 1.0),
 			xyz: vector!(0.0, 0.0, 0.0),
 			rotate_xyz: vector!(0.0, 0.0, 0.0),
 		};
@@ -491,9 +496,9 @@ impl Renderer {
 		Ok((renderer, window))
 	}
 
-	pub fn bg_color(&mut self, rgb: Vector) {
-		self.clear_color = (rgb.x, rgb.y, rgb.z);
-		self.vw.connection.color(rgb);
+	pub fn bg_color(&mut self, rgb: Vector, alpha: f32) {
+		self.clear_color = (rgb.x, rgb.y, rgb.z, alpha);
+		self.vw.connection.color(rgb, alpha);
 	}
 
 	pub fn update(&mut self) -> f32 {
@@ -11,11 +11,29 @@ use render::{Display, new_display};
 pub use render::{Shape, Gradient, Model, Texture, TexCoords};
 
 use render::{Event};
+use Overflow;
+use Playback;
+use WindowBuilder;
+use input::record::{self, Recorder, Player};
+use {Axis, AxisConfig, Calibration, Controller, Effect};
+use {Actions, Binding, Key, KeyCombo, Keyboard, Locale, MouseButtons};
+use input::gesture::Gestures;
+use input::mouse::Mouse;
+use input::pointer::Pointer;
 use afi::{VFrame, PathOp};
 
 use Matrix;
 use Vector;
 
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `LoopMode::Poll` sleeps between checking for input while frames
+/// are skipped (about one frame at 60 Hz).
+const HIDDEN_POLL: Duration = Duration::from_millis(16);
+
 #[cfg(target_arch="wasm32")] mod win {mod wasm32; pub use self::wasm32::*;}
 
 #[cfg(target_arch="wasm32")]
@@ -40,12 +58,55 @@ pub struct Screen<Ctx> where Ctx: Default {
 	// current function pointer.
 	run: fn(&mut Screen<Ctx>, Event, f32),
 	running: bool,
+	// Where events are recorded to, if recording.
+	recorder: Option<Recorder>,
+	// How the main loop waits for input.
+	mode: LoopMode,
+	// Whether a redraw was requested, for `LoopMode::OnDemand`.
+	redraw: bool,
+	// Whether any of the window can be seen.
+	visible: bool,
+	// Whether to skip frames while the window can't be seen.
+	throttle: bool,
+	// State of each controller, from controller events.
+	controllers: Vec<Controller>,
+	// Which keys are held, from key events.
+	keyboard: Keyboard,
+	// Cursor and mouse buttons, from mouse events.
+	mouse: Mouse,
+	// Gestures recognized from touch events.
+	gestures: Gestures,
+	// Double clicks, drags and hovering, from mouse events.
+	pointer: Pointer,
+	// When the screen opened, to time events.
+	started: Instant,
+	// Named actions, and their bindings.
+	actions: Actions,
+	// Names of keys and buttons in the user's language.
+	locale: Locale,
+}
+
+/// How `Screen::start()` waits for input between frames.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LoopMode {
+	/// Don't wait: check for input and draw a frame as often as possible
+	/// (the default).  Use for games and animations.
+	Poll,
+	/// Block until there's input, a `Waker` is used, or the timeout (if
+	/// any) runs out, then draw a frame.  Controllers don't wake the
+	/// screen, so use a timeout if you need them.
+	Wait(Option<Duration>),
+	/// Block until there's input or a `Waker` is used, and only draw a
+	/// frame (and send `Event::Timestep`) when `Screen::redraw()` has
+	/// been called.  Use for tools that are idle most of the time.
+	OnDemand,
 }
 
 /// An error in the connection to the screen.
 #[derive(Debug)]
 pub enum ScreenError {
-	
+	/// Couldn't read an input recording.
+	Recording(io::Error),
 }
 
 impl<Ctx> Screen<Ctx> where Ctx: Default {
@@ -53,44 +114,413 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 	pub fn start(run: fn(&mut Screen<Ctx>, Event, f32))
 		-> Result<(), ScreenError>
 	{
-		let mut screen = Screen::new(run);
+		Screen::start_with(run, WindowBuilder::new())
+	}
+
+	/// Start the program, opening the window with `builder`'s options.
+	pub fn start_with(run: fn(&mut Screen<Ctx>, Event, f32),
+		builder: WindowBuilder) -> Result<(), ScreenError>
+	{
+		let mut screen = Screen::new(run, &builder);
 		let mut dt = 0.0;
 
 		while screen.running {
+			// Nothing to draw while hidden, so don't spin.
+			let hidden = screen.throttle && !screen.visible;
+
+			match screen.mode {
+				LoopMode::Poll if hidden => {
+					thread::sleep(HIDDEN_POLL)
+				}
+				LoopMode::Poll => {}
+				LoopMode::Wait(timeout) => {
+					screen.display.wait(timeout)
+				}
+				LoopMode::OnDemand => {
+					if hidden || !screen.redraw {
+						screen.display.wait(None)
+					}
+				}
+			}
+
 			while let Some(input) = screen.display.input() {
-				(screen.run)(&mut screen, input, dt);
+				let time = screen.now();
+				let name = screen.controller_name(input);
+
+				screen.event(input, dt, time, name);
+			}
+
+			if screen.throttle && !screen.visible {
+				continue;
 			}
+			if screen.mode == LoopMode::OnDemand && !screen.redraw {
+				continue;
+			}
+			screen.redraw = false;
+
+			let time = screen.now();
 
-			(screen.run)(&mut screen, Event::Timestep, dt);
+			screen.event(Event::Timestep, dt, time, None);
 			dt = screen.display.update();
 		}
 
 		Ok(())
 	}
 
+	/// Set how the main loop waits for input between frames.
+	pub fn loop_mode(&mut self, mode: LoopMode) {
+		self.mode = mode;
+	}
+
+	/// Set whether to skip frames (and `Event::Timestep`) while the window
+	/// is minimized or covered (off by default).  Turn it on if nothing
+	/// needs to keep running (game logic, networking or audio) while the
+	/// window can't be seen.  While skipping, `LoopMode::Poll` checks for
+	/// input about once a frame instead of as often as possible.
+	pub fn throttle(&mut self, throttle: bool) {
+		self.throttle = throttle;
+	}
+
+	/// Get whether any of the window can be seen.
+	pub fn visible(&self) -> bool {
+		self.visible
+	}
+
+	/// Get the state of controller `id` (the `usize` in controller events).
+	/// Controllers that haven't been plugged in aren't `connected`.
+	pub fn controller(&self, id: usize) -> Controller {
+		self.controllers.get(id).cloned().unwrap_or_default()
+	}
+
+	/// Get whether `key` is held down.
+	pub fn key_pressed(&self, key: Key) -> bool {
+		self.keyboard.get(key)
+	}
+
+	/// Get the cursor position (-1 to 1, from the top-left corner), or
+	/// `None` if it's outside of the window.  While a mouse button is
+	/// held, the cursor is captured and can be outside of -1 to 1.
+	pub fn cursor(&self) -> Option<(f32, f32)> {
+		self.mouse.cursor
+	}
+
+	/// Get which mouse buttons are held down.
+	pub fn mouse_buttons(&self) -> MouseButtons {
+		self.mouse.buttons
+	}
+
+	/// Get the value of `action` (-1 to 1), from the keys, mouse buttons
+	/// and controller inputs it's bound to.  0 if it isn't bound.
+	pub fn action(&self, action: &str) -> f32 {
+		self.actions.value(action, &self.keyboard, self.mouse.buttons,
+			&self.controllers)
+	}
+
+	/// Get whether `action` is held down (pushed more than halfway).
+	pub fn action_held(&self, action: &str) -> bool {
+		self.action(action).abs() > 0.5
+	}
+
+	/// Get the action bindings.
+	pub fn actions(&self) -> &Actions {
+		&self.actions
+	}
+
+	/// Get the action bindings, to rebind or load them.
+	pub fn actions_mut(&mut self) -> &mut Actions {
+		&mut self.actions
+	}
+
+	/// Get the names of keys and buttons, in the system's language.
+	pub fn locale(&self) -> &Locale {
+		&self.locale
+	}
+
+	/// Get the names of keys and buttons, to change the language or add
+	/// translations.
+	pub fn locale_mut(&mut self) -> &mut Locale {
+		&mut self.locale
+	}
+
+	/// Get the name of a key in the user's language, or what the keyboard
+	/// layout prints on it for keys that type a character ("Z" for
+	/// `Key::Y` on a German keyboard).  For key binding settings.
+	pub fn key_name(&self, key: Key) -> String {
+		#[cfg(not(target_arch="wasm32"))] {
+			if let Some(label) = self.display.key_label(key) {
+				return label;
+			}
+		}
+
+		self.locale.key(key).to_string()
+	}
+
+	/// Get the name of a key combination in the user's language, like
+	/// "Strg+S" in German.
+	pub fn combo_name(&self, combo: KeyCombo) -> String {
+		self.locale.combo_named(combo, &self.key_name(combo.key))
+	}
+
+	/// Get the name of a binding in the user's language.
+	pub fn binding_name(&self, binding: Binding) -> String {
+		match binding {
+			Binding::Key(key) => self.key_name(key),
+			binding => self.locale.binding(binding),
+		}
+	}
+
+	/// Add controller mappings in SDL's `gamecontrollerdb.txt` format, such
+	/// as the contents of that file.  Mappings replace earlier ones for the
+	/// same controller, including the ones included with awi, and apply to
+	/// controllers already plugged in.  Returns how many were added.
+	/// Mappings are only used on Linux.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn controller_mappings(&mut self, mappings: &str) -> usize {
+		self.display.controller_mappings(mappings)
+	}
+
+	/// Set the deadzone and response curve of `axis`, on every controller.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn axis_config(&mut self, axis: Axis, config: AxisConfig) {
+		self.display.axis_config(axis, config)
+	}
+
+	/// Start calibrating controller `id` (`true`): the user should let go
+	/// of the sticks, then move every axis as far as it goes.  Finish
+	/// calibrating (`false`) to use the range that was recorded.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn calibrate(&mut self, id: usize, calibrate: bool) {
+		self.display.calibrate(id, calibrate)
+	}
+
+	/// Get the calibration of `axis` on controller `id`, to save it.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn calibration(&self, id: usize, axis: Axis) -> Calibration {
+		self.display.calibration(id, axis)
+	}
+
+	/// Set the calibration of `axis` on controller `id`, such as one saved
+	/// from `Screen::calibration()`.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn set_calibration(&mut self, id: usize, axis: Axis,
+		calibration: Calibration)
+	{
+		self.display.set_calibration(id, axis, calibration)
+	}
+
+	/// Vibrate controller `id`'s strong (low frequency) and weak (high
+	/// frequency) rumble motors (0 to 1) for `duration` (0 to stop).
+	/// Replaces the effect that's playing.  Returns why, if the controller
+	/// can't (only Linux, with write access to the device).
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn rumble(&mut self, id: usize, strong: f32, weak: f32,
+		duration: Duration) -> Result<(), String>
+	{
+		self.display.force_feedback(id, Effect::Rumble(strong, weak),
+			duration)
+	}
+
+	/// Play a force feedback `effect` on controller `id` for `duration`
+	/// (0 to stop), replacing the effect that's playing.  Returns why, if
+	/// the controller can't.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn force_feedback(&mut self, id: usize, effect: Effect,
+		duration: Duration) -> Result<(), String>
+	{
+		self.display.force_feedback(id, effect, duration)
+	}
+
+	/// Request a frame to be drawn, for `LoopMode::OnDemand`.
+	pub fn redraw(&mut self) {
+		self.redraw = true;
+	}
+
+	/// Get a handle for other threads to wake up the main loop while it's
+	/// waiting for input, and send it `Event::User`s.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn waker(&self) -> ::Waker {
+		self.display.waker()
+	}
+
+	/// Ask for the user's attention, if the window isn't focused.  On X11,
+	/// this sets `_NET_WM_STATE_DEMANDS_ATTENTION`, which the window manager
+	/// clears when the window is focused.
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn attention(&mut self) {
+		self.display.attention()
+	}
+
+	/// Keep the screensaver and idle blanking off while `inhibit` is
+	/// `true`, such as while playing a video.  On X11, this uses the
+	/// MIT-SCREEN-SAVER extension.  Returns why, if it can't (such as
+	/// when the extension's library isn't installed).
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn inhibit_screensaver(&mut self, inhibit: bool)
+		-> Result<(), String>
+	{
+		self.display.inhibit_screensaver(inhibit)
+	}
+
+	/// Start the program, replaying the input recording at `path` (see
+	/// `Screen::record()`) in place of live input.  Each event is passed
+	/// with the `dt` it was recorded with, so replay is deterministic.
+	/// Returns when the recording ends.
+	pub fn replay<P: AsRef<Path>>(run: fn(&mut Screen<Ctx>, Event, f32),
+		path: P, playback: Playback) -> Result<(), ScreenError>
+	{
+		let mut player = Player::new(path, playback)
+			.map_err(ScreenError::Recording)?;
+		let mut screen = Screen::new(run, &WindowBuilder::new());
+
+		while screen.running {
+			let (time, input, dt, name) = match player.next() {
+				Some(record) => record,
+				None => break,
+			};
+
+			screen.event(input, dt, time, name);
+
+			if input == Event::Timestep {
+				// Keep the window responsive, ignoring live
+				// input.
+				while screen.display.input().is_some() {}
+				screen.display.update();
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Get the name of the controller plugged in, for `CPluggedIn`.
+	#[cfg(not(target_arch="wasm32"))]
+	fn controller_name(&self, input: Event) -> Option<String> {
+		match input {
+			Event::CPluggedIn(js, _) => {
+				self.display.controller_name(js)
+			}
+			_ => None,
+		}
+	}
+
+	#[cfg(target_arch="wasm32")]
+	fn controller_name(&self, _input: Event) -> Option<String> {
+		None
+	}
+
+	/// Pass an event, which happened at `time` (seconds), to the run
+	/// function, recording it if recording.  `name` is the name of the
+	/// controller, for `CPluggedIn`.
+	fn event(&mut self, input: Event, dt: f32, time: f64,
+		name: Option<String>)
+	{
+		match input {
+			Event::Resize(w, h) => {
+				self.resize((w, h));
+				self.redraw = true;
+			}
+			Event::Redraw => self.redraw = true,
+			Event::Minimized | Event::Hidden => self.visible = false,
+			Event::Shown => {
+				self.visible = true;
+				self.redraw = true;
+			}
+			_ => Controller::update(&mut self.controllers, input,
+				name.clone()),
+		}
+
+		self.keyboard.update(input);
+		self.mouse.update(input);
+
+		if let Some(mut recorder) = self.recorder.take() {
+			match recorder.record(input, dt, name.as_deref()) {
+				Ok(()) => self.recorder = Some(recorder),
+				Err(e) => eprintln!("awi: recording stopped: {}", e),
+			}
+		}
+
+		(self.run)(self, input, dt);
+
+		// Gestures, clicks and drags aren't recorded: they're recognized
+		// again on replay.
+		self.gestures.update(input, dt);
+		while let Some(gesture) = self.gestures.pop() {
+			(self.run)(self, gesture, dt);
+		}
+		self.pointer.update(input, time);
+		while let Some(event) = self.pointer.pop() {
+			(self.run)(self, event, dt);
+		}
+	}
+
+	/// Get the time since the screen opened (seconds).
+	fn now(&self) -> f64 {
+		record::seconds(self.started.elapsed())
+	}
+
+	/// Start recording every event (and it's timestamp and `dt`) to the
+	/// file at `path`, for replay with `Screen::replay()`.
+	pub fn record<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+		self.stop_recording()?;
+		self.recorder = Some(Recorder::new(path)?);
+		Ok(())
+	}
+
+	/// Stop recording events, if recording.
+	pub fn stop_recording(&mut self) -> io::Result<()> {
+		if let Some(mut recorder) = self.recorder.take() {
+			recorder.flush()?;
+		}
+		Ok(())
+	}
+
 	/// Open a new Window to the Screen.
-	fn new(run: fn(&mut Screen<Ctx>, Event, f32)) -> Self {
+	fn new(run: fn(&mut Screen<Ctx>, Event, f32), builder: &WindowBuilder)
+		-> Self
+	{
 		let mut screen = Screen {
 			ctx: Ctx::default(),
 			vframe: VFrame(vec![]),
 
 			#[cfg(not(target_arch="wasm32"))]
-			display: new_display().unwrap(),
+			display: new_display(builder).unwrap(),
 			#[cfg(target_arch="wasm32")]
 			display: Display::new(),
 
 			run,
 			running: true,
+			recorder: None,
+			mode: LoopMode::Poll,
+			redraw: true,
+			visible: true,
+			throttle: false,
+			controllers: Vec::new(),
+			keyboard: Keyboard::new(),
+			mouse: Mouse::default(),
+			gestures: Gestures::new((0, 0)),
+			pointer: Pointer::new((0, 0), (0.0, 0)),
+			started: Instant::now(),
+			actions: Actions::new(),
+			locale: Locale::system(),
 		};
 
 		let wh = screen.display.wh();
 		screen.vframe.0.resize((wh.0 as usize * wh.1 as usize) * 4, 0);
+		screen.gestures = Gestures::new(wh);
+		#[cfg(not(target_arch="wasm32"))]
+		let settings = screen.display.click_settings();
+		#[cfg(target_arch="wasm32")]
+		let settings = (::input::pointer::DOUBLE_CLICK_TIME,
+			::input::pointer::DRAG_THRESHOLD);
+		screen.pointer = Pointer::new(wh, settings);
 
 		screen
 	}
 
 	/// Stop the program.
 	pub fn stop(&mut self) {
+		if let Err(e) = self.stop_recording() {
+			eprintln!("awi: couldn't save recording: {}", e);
+		}
 		::std::process::exit(0);
 	}
 
@@ -99,8 +529,27 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.run = run;
 	}
 
-	/// Update the clear color of the Window.
-	pub fn clear(&mut self, color: (u8, u8, u8)) {
+	/// Limit the number of input events queued per frame, `None` for no
+	/// limit (the default).  `overflow` decides which event is thrown
+	/// away when the queue is full.
+	pub fn input_capacity(&mut self, capacity: Option<usize>,
+		overflow: Overflow)
+	{
+		self.display.input_capacity(capacity, overflow)
+	}
+
+	/// Set whether consecutive `Cursor` events (and moves of the same
+	/// `Touch` finger) are merged into the last one, and consecutive
+	/// `Scroll`, `Pan`, `Pinch` and `Rotate` events are summed (off by
+	/// default).
+	pub fn input_coalesce(&mut self, coalesce: bool) {
+		self.display.input_coalesce(coalesce)
+	}
+
+	/// Update the clear color of the Window, `(r, g, b, a)`.  The alpha
+	/// is ignored unless the window was opened with
+	/// `WindowBuilder::transparent()`.
+	pub fn clear(&mut self, color: (u8, u8, u8, u8)) {
 		self.display.color(color)
 	}
 
@@ -197,7 +646,8 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.display.transform(shape, matrix)
 	}
 
-	/// Call this function when you get a resize event.
+	/// Resize the viewport and projection.  This is done automatically
+	/// before your run function gets `Event::Resize`.
 	pub fn resize(&mut self, wh: (u16, u16)) {
 		self.vframe.0.resize((wh.0 as usize * wh.1 as usize) * 4, 0);
 		self.display.resize(wh);
@@ -213,3 +663,31 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.display.draw(writer)
 	}
 }
+
+#[cfg(all(feature = "raw-window-handle", not(target_arch="wasm32")))]
+impl<Ctx> ::raw_window_handle::HasWindowHandle for Screen<Ctx>
+	where Ctx: Default
+{
+	fn window_handle(&self) -> Result<::raw_window_handle::WindowHandle,
+		::raw_window_handle::HandleError>
+	{
+		let raw = self.display.connection().raw_window_handle()?;
+
+		// The window lives as long as the `Screen` does.
+		Ok(unsafe { ::raw_window_handle::WindowHandle::borrow_raw(raw) })
+	}
+}
+
+#[cfg(all(feature = "raw-window-handle", not(target_arch="wasm32")))]
+impl<Ctx> ::raw_window_handle::HasDisplayHandle for Screen<Ctx>
+	where Ctx: Default
+{
+	fn display_handle(&self) -> Result<::raw_window_handle::DisplayHandle,
+		::raw_window_handle::HandleError>
+	{
+		let raw = self.display.connection().raw_display_handle()?;
+
+		// The connection lives as long as the `Screen` does.
+		Ok(unsafe { ::raw_window_handle::DisplayHandle::borrow_raw(raw) })
+	}
+}
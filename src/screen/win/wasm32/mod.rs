@@ -91,8 +91,9 @@ impl Display {
 		}
 	}
 
-	pub(crate) fn clear(&mut self, color: (u8, u8, u8)) {
-		let [r, g, b, a] = to_f32_rgba([color.0, color.1, color.2]);
+	pub(crate) fn clear(&mut self, color: (u8, u8, u8, u8)) {
+		let [r, g, b, a] = to_f32_rgba([color.0, color.1, color.2,
+			color.3]);
 
 		self.context.clear_color(r, g, b, a);
 	}
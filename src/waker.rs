@@ -0,0 +1,37 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use os;
+
+/// Handle for other threads to wake up a `Screen` that's waiting for input
+/// (see `LoopMode`).  Get one with `Screen::waker()`.
+#[derive(Clone)]
+pub struct Waker {
+	wake: os::Wake,
+	posted: Arc<Mutex<VecDeque<u64>>>,
+}
+
+impl Waker {
+	/// Create a waker for a window.
+	pub(crate) fn new(wake: os::Wake, posted: Arc<Mutex<VecDeque<u64>>>)
+		-> Waker
+	{
+		Waker { wake, posted }
+	}
+
+	/// Interrupt the wait, without sending an event.
+	pub fn wake(&self) {
+		self.wake.wake();
+	}
+
+	/// Interrupt the wait, and send `Event::User(value)` to the `Screen`.
+	pub fn post(&self, value: u64) {
+		self.posted.lock().unwrap().push_back(value);
+		self.wake.wake();
+	}
+}
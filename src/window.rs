@@ -5,6 +5,10 @@
 
 // use c_void;
 
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use os;
 
 /// A graphics window on a computer, linked to a rendering API.
@@ -13,7 +17,9 @@ pub(crate) struct Window {
 	input_queue: ::input::InputQueue,
 	keyboard: ::Keyboard,
 	reset: bool,
-	cm: ::stick::ControllerManager,
+	gamepads: ::input::gamepad::Gamepads,
+	// Values posted with `Waker::post()`.
+	posted: Arc<Mutex<VecDeque<u64>>>,
 }
 
 impl Window {
@@ -21,14 +27,23 @@ impl Window {
 	/// window icon.  The format of icon is as follows:
 	/// `(width, height, pixels)`.  You can load icons with aci.  `v` should
 	/// be either `None` or `Some(visual_id from EGL)`.
-	pub fn new(v: Option<i32>) -> Window {
-		let os_window = os::Window::new(v);
+	pub fn new(v: Option<i32>, builder: &::WindowBuilder) -> Window {
+		let os_window = match builder.external {
+			Some((ref connection, wh)) => {
+				os::Window::external(connection.clone(), wh)
+			}
+			None => os::Window::new(v, builder),
+		};
 		let input_queue = ::input::InputQueue::new();
 		let keyboard = ::Keyboard::new();
 		let reset = false;
-		let cm = ::stick::ControllerManager::new(vec![]);
+		let gamepads = ::input::gamepad::Gamepads::new();
+		let posted = Arc::new(Mutex::new(VecDeque::new()));
 
-		Window { os_window, input_queue, keyboard, reset, cm }
+		Window {
+			os_window, input_queue, keyboard, reset, gamepads,
+			posted,
+		}
 	}
 
 	/// Get the type of connection, plus native window and connection
@@ -42,6 +57,105 @@ impl Window {
 		self.os_window.wh()
 	}
 
+	/// Update the size of an external window.
+	pub fn resize(&mut self, wh: (u16, u16)) {
+		self.os_window.resize(wh)
+	}
+
+	/// Put the last rendered buffer on the screen (KMS page flip).
+	#[cfg(all(feature = "kiosk", not(feature = "headless")))]
+	pub fn flip(&mut self) {
+		self.os_window.flip()
+	}
+
+	/// Limit the number of queued input events, `None` for no limit.
+	pub fn input_capacity(&mut self, capacity: Option<usize>,
+		overflow: ::Overflow)
+	{
+		self.input_queue.set_capacity(capacity, overflow)
+	}
+
+	/// Set whether consecutive cursor and scroll events are merged.
+	pub fn input_coalesce(&mut self, coalesce: bool) {
+		self.input_queue.set_coalesce(coalesce)
+	}
+
+	/// Add controller mappings (SDL's `gamecontrollerdb.txt` format).
+	pub fn controller_mappings(&mut self, mappings: &str) -> usize {
+		self.gamepads.add_mappings(mappings)
+	}
+
+	/// Set the deadzone and response curve of a controller axis.
+	pub fn axis_config(&mut self, axis: ::Axis, config: ::AxisConfig) {
+		self.gamepads.axis_config(axis, config)
+	}
+
+	/// Start or finish calibrating a controller.
+	pub fn calibrate(&mut self, id: usize, calibrate: bool) {
+		self.gamepads.calibrate(id, calibrate)
+	}
+
+	/// Get the name of a controller.
+	pub fn controller_name(&self, id: usize) -> Option<String> {
+		self.gamepads.name(id)
+	}
+
+	/// Get the calibration of a controller axis.
+	pub fn calibration(&self, id: usize, axis: ::Axis) -> ::Calibration {
+		self.gamepads.calibration(id, axis)
+	}
+
+	/// Set the calibration of a controller axis.
+	pub fn set_calibration(&mut self, id: usize, axis: ::Axis,
+		calibration: ::Calibration)
+	{
+		self.gamepads.set_calibration(id, axis, calibration)
+	}
+
+	/// Play a force feedback effect on a controller.
+	pub fn force_feedback(&mut self, id: usize, effect: ::Effect,
+		duration: Duration) -> Result<(), String>
+	{
+		self.gamepads.force_feedback(id, effect, duration)
+	}
+
+	/// Block until there's input, a `Waker` is used, or the timeout (if
+	/// any) runs out.  Returns immediately if input is already queued.
+	pub fn wait(&mut self, timeout: Option<Duration>) {
+		if self.input_queue.is_empty()
+			&& self.posted.lock().unwrap().is_empty()
+		{
+			self.os_window.wait(timeout);
+		}
+	}
+
+	/// Get a handle for other threads to interrupt `wait()`.
+	pub fn waker(&self) -> ::Waker {
+		::Waker::new(self.os_window.wake(), self.posted.clone())
+	}
+
+	/// Request the user's attention.
+	pub fn attention(&mut self) {
+		self.os_window.attention()
+	}
+
+	/// Inhibit (or uninhibit) the screensaver.
+	pub fn inhibit_screensaver(&mut self, inhibit: bool)
+		-> Result<(), String>
+	{
+		self.os_window.inhibit_screensaver(inhibit)
+	}
+
+	/// Get the keyboard layout's label for a key.
+	pub fn key_label(&self, key: ::Key) -> Option<String> {
+		self.os_window.key_label(key)
+	}
+
+	/// Get the double-click time and drag threshold.
+	pub fn click_settings(&self) -> (f32, u16) {
+		self.os_window.click_settings()
+	}
+
 	/// Poll window input, return `None` when finished.  After returning
 	/// `None`, the next call will update the window.
 	pub fn update(&mut self) -> Option<::Event> {
@@ -71,6 +185,11 @@ impl Window {
 		self.keyboard.add(&mut self.input_queue);
 
 		// Generate controller events from stick
-		self.input_queue.stick(&mut self.cm);
+		self.gamepads.update(&mut self.input_queue);
+
+		// Generate user events posted from other threads.
+		while let Some(value) = self.posted.lock().unwrap().pop_front() {
+			self.input_queue.user(value);
+		}
 	}
 }
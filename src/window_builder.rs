@@ -0,0 +1,100 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use window_connection::WindowConnection;
+
+/// What the window is for, so the window manager can decorate, place and
+/// stack it accordingly (`_NET_WM_WINDOW_TYPE` on X11).
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum WindowType {
+	/// A normal, top-level window (the default).
+	#[default]
+	Normal,
+	/// A dialog window.
+	Dialog,
+	/// A small persistent utility window, such as a palette or toolbox.
+	Utility,
+	/// A splash screen shown while the application is starting up.
+	Splash,
+	/// A tooltip.
+	Tooltip,
+	/// A dock or panel.
+	Dock,
+}
+
+/// Options for opening a window, passed to `Screen::start_with()`.
+#[derive(Clone, Debug, Default)]
+pub struct WindowBuilder {
+	pub(crate) transparent: bool,
+	pub(crate) window_type: WindowType,
+	pub(crate) above: bool,
+	pub(crate) skip_taskbar: bool,
+	pub(crate) skip_pager: bool,
+	pub(crate) transient_for: Option<WindowConnection>,
+	pub(crate) parent: Option<WindowConnection>,
+	// Render to a window created by another library, see
+	// `render::new_display_external()`.
+	pub(crate) external: Option<(WindowConnection, (u16, u16))>,
+}
+
+impl WindowBuilder {
+	/// Start with the default options.
+	pub fn new() -> WindowBuilder {
+		WindowBuilder::default()
+	}
+
+	/// Whether the window has an alpha channel (default `false`).  When
+	/// `true`, the alpha value passed to `Screen::clear()` shows through to
+	/// whatever's behind the window.  Needs a compositing window manager.
+	/// Currently only supported on X11.
+	pub fn transparent(mut self, transparent: bool) -> WindowBuilder {
+		self.transparent = transparent;
+		self
+	}
+
+	/// Set the type of window (default `WindowType::Normal`).  Only
+	/// `Normal` windows start maximized.
+	pub fn window_type(mut self, window_type: WindowType) -> WindowBuilder {
+		self.window_type = window_type;
+		self
+	}
+
+	/// Keep the window above other windows (default `false`).
+	pub fn above(mut self, above: bool) -> WindowBuilder {
+		self.above = above;
+		self
+	}
+
+	/// Leave the window out of the taskbar (default `false`).
+	pub fn skip_taskbar(mut self, skip_taskbar: bool) -> WindowBuilder {
+		self.skip_taskbar = skip_taskbar;
+		self
+	}
+
+	/// Leave the window out of the pager / workspace switcher (default
+	/// `false`).
+	pub fn skip_pager(mut self, skip_pager: bool) -> WindowBuilder {
+		self.skip_pager = skip_pager;
+		self
+	}
+
+	/// Make the window transient for `parent` (a dialog or palette that
+	/// belongs to it), so it's kept above and minimized with the parent.
+	pub fn transient_for(mut self, parent: WindowConnection)
+		-> WindowBuilder
+	{
+		self.transient_for = Some(parent);
+		self
+	}
+
+	/// Embed the window in a foreign `parent` window, such as one passed
+	/// to a plugin by it's host.  The window fills the parent, and isn't
+	/// managed by the window manager, so the other options are ignored.
+	/// On X11, the XEmbed protocol is used for focus.
+	pub fn parent(mut self, parent: WindowConnection) -> WindowBuilder {
+		self.parent = Some(parent);
+		self
+	}
+}
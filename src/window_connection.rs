@@ -5,15 +5,28 @@
 
 use c_void;
 
-/// Connection is listed first, then window.
-#[allow(unused)] #[derive(Clone)]
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{
+	RawWindowHandle, RawDisplayHandle, HandleError, XcbWindowHandle,
+	XcbDisplayHandle, WaylandWindowHandle, WaylandDisplayHandle,
+	Win32WindowHandle, WindowsDisplayHandle, GbmWindowHandle,
+	GbmDisplayHandle,
+};
+
+/// Native window handles, to pass to ffi.  Connection is listed first, then
+/// window.
+#[allow(unused)] #[derive(Clone, Debug)]
 pub enum WindowConnection {
-	/// XCB Window Handles
-	Xcb(*mut c_void, u32),
-	/// Wayland Window Handles
-	Wayland,
+	/// XCB Window Handles (`xcb_connection_t`, window, and the screen
+	/// number from `xcb_connect()`)
+	Xcb(*mut c_void, u32, i32),
+	/// Wayland Window Handles (`wl_display`, `wl_surface`)
+	Wayland(*mut c_void, *mut c_void),
 	/// DirectFB Window Handles
 	DirectFB,
+	/// KMS Window Handles (`gbm_device`, `gbm_surface`), both null when
+	/// rendering with `VK_KHR_display`
+	Kms(*mut c_void, *mut c_void),
 	/// Windows Window Handles
 	Windows(*mut c_void, *mut c_void),
 	/// Android Window Handles
@@ -31,3 +44,78 @@ pub enum WindowConnection {
 	/// No OS Window Handles
 	NoOS,
 }
+
+#[cfg(feature = "raw-window-handle")]
+impl WindowConnection {
+	/// Convert to a `raw-window-handle` window handle.
+	pub(crate) fn raw_window_handle(&self)
+		-> Result<RawWindowHandle, HandleError>
+	{
+		use std::num::{NonZeroU32, NonZeroIsize};
+		use std::ptr::NonNull;
+
+		match *self {
+			WindowConnection::Xcb(_, window, _) => {
+				let window = NonZeroU32::new(window)
+					.ok_or(HandleError::Unavailable)?;
+
+				Ok(XcbWindowHandle::new(window).into())
+			}
+			WindowConnection::Wayland(_, surface) => {
+				let surface = NonNull::new(surface)
+					.ok_or(HandleError::Unavailable)?;
+
+				Ok(WaylandWindowHandle::new(surface).into())
+			}
+			WindowConnection::Kms(_, surface) => {
+				let surface = NonNull::new(surface)
+					.ok_or(HandleError::Unavailable)?;
+
+				Ok(GbmWindowHandle::new(surface).into())
+			}
+			WindowConnection::Windows(hinstance, hwnd) => {
+				let mut handle = Win32WindowHandle::new(
+					NonZeroIsize::new(hwnd as isize)
+						.ok_or(HandleError::Unavailable)?
+				);
+				handle.hinstance = NonZeroIsize::new(
+					hinstance as isize);
+
+				Ok(handle.into())
+			}
+			WindowConnection::NoOS => Err(HandleError::Unavailable),
+			_ => Err(HandleError::NotSupported),
+		}
+	}
+
+	/// Convert to a `raw-window-handle` display handle.
+	pub(crate) fn raw_display_handle(&self)
+		-> Result<RawDisplayHandle, HandleError>
+	{
+		use std::ptr::NonNull;
+
+		match *self {
+			WindowConnection::Xcb(connection, _, screen) => {
+				Ok(XcbDisplayHandle::new(NonNull::new(connection),
+					screen).into())
+			}
+			WindowConnection::Wayland(display, _) => {
+				let display = NonNull::new(display)
+					.ok_or(HandleError::Unavailable)?;
+
+				Ok(WaylandDisplayHandle::new(display).into())
+			}
+			WindowConnection::Kms(device, _) => {
+				let device = NonNull::new(device)
+					.ok_or(HandleError::Unavailable)?;
+
+				Ok(GbmDisplayHandle::new(device).into())
+			}
+			WindowConnection::Windows(_, _) => {
+				Ok(WindowsDisplayHandle::new().into())
+			}
+			WindowConnection::NoOS => Err(HandleError::Unavailable),
+			_ => Err(HandleError::NotSupported),
+		}
+	}
+}